@@ -0,0 +1,112 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Heap allocation counters, fed by a global allocator wrapping the system
+// one. Counting is opt-in:
+//
+//     BINARY=custom_types cargo run --features count-allocations
+//
+// Without the feature the allocator isn't installed, `section` just runs its
+// closure and nothing is reported.
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[cfg(feature = "count-allocations")]
+struct Counting;
+
+#[cfg(feature = "count-allocations")]
+unsafe impl std::alloc::GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let ptr = std::alloc::System.alloc(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    // A reallocation counts as a new allocation of the new size
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = std::alloc::System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+            allocated(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(feature = "count-allocations")]
+fn allocated(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED.fetch_add(size, Ordering::Relaxed);
+    let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+// What a piece of code did to the heap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub allocations: usize,
+    // Total of the sizes requested, freed memory included
+    pub bytes: usize,
+    // Highest amount of memory the code had allocated at the same time,
+    // on top of what was live when it started
+    pub peak: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} bytes allocated, {} bytes peak live",
+            self.allocations, self.bytes, self.peak
+        )
+    }
+}
+
+pub fn enabled() -> bool {
+    cfg!(feature = "count-allocations")
+}
+
+// Run `f` and return the allocations it made. Measurements can be nested.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Stats) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED.load(Ordering::Relaxed);
+    let live = LIVE.load(Ordering::Relaxed);
+    // Track the peak of `f` alone, then restore the one of any enclosing
+    // measurement
+    let outer_peak = PEAK.swap(live, Ordering::Relaxed);
+
+    let result = f();
+
+    let peak = PEAK.fetch_max(outer_peak, Ordering::Relaxed);
+    let stats = Stats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        bytes: ALLOCATED.load(Ordering::Relaxed) - bytes,
+        peak: peak.saturating_sub(live),
+    };
+    (result, stats)
+}
+
+// Run a section of an example, reporting its allocations after it when
+// counting is enabled.
+pub fn section<R>(name: &str, f: impl FnOnce() -> R) -> R {
+    if !enabled() {
+        return f();
+    }
+    let (result, stats) = measure(f);
+    println!("[{}: {}]", name, stats);
+    result
+}
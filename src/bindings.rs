@@ -0,0 +1,739 @@
+use std::error;
+use std::fmt;
+
+use crate::source::{self, Span};
+
+mod check;
+mod ownership;
+mod run;
+mod syntax;
+mod timeline;
+
+pub use syntax::{parse, Block, Expr, Program, Stmt};
+
+// A way out of a branch that leaves a binding uninitialized, on the path to
+// one of its reads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Branch {
+    // The condition of an `if`, true or false
+    If(bool),
+    // The condition of a `while`, false from the start
+    While,
+    Break,
+}
+
+// A tiny language with the statements of the variable bindings example, and
+// a checker reporting the errors its commented out `// Error!` lines would
+// give, before running the program:
+//
+//     let output = bindings::run("let x = 1; { let x = x * 2; println!(\"{}\", x); }")?;
+//     let errors = bindings::run("let x = 1;\nx = 2;").unwrap_err();
+//     println!("{}", errors[0].render(source));
+//
+//     error[E0384]: cannot assign twice to immutable variable `x`
+//       |
+//     1 | let x = 1;
+//       |     - help: make `x` mutable: `mut x`
+//     2 | x = 2;
+//       | ^^^^^ cannot assign twice
+//
+// The checker knows about scopes, shadowing, deferred initialization along
+// every path through `if`s, loops and `return`s, and freezing, then about
+// moves and borrows: `String::from`, `&`, `&mut`, `*r` and `drop`. The
+// interpreter knows about integers, strings, `bool`s and references.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnexpectedCharacter(char),
+    InvalidNumber(String),
+    UnterminatedString,
+    // What was found, and what was expected instead
+    UnexpectedToken(String, &'static str),
+    UnexpectedEnd(&'static str),
+    // `{}` placeholders, and arguments
+    FormatArguments(usize, usize),
+    NotInScope(String),
+    UnknownFunction(String),
+    // The arguments the function takes, and those it was given
+    ArgumentCount(usize, usize),
+    // Read before being assigned, declared at `declared`. `path` is empty
+    // when no path assigns it, and the branches of one that doesn't when
+    // some do.
+    Uninitialized {
+        name: String,
+        declared: Span,
+        path: Vec<(Span, Branch)>,
+    },
+    // Assigned a second time, while not `mut`
+    Immutable {
+        name: String,
+        declared: Span,
+    },
+    // Immutable because it shadows a mutable binding, declared at `shadowed`
+    Frozen {
+        name: String,
+        declared: Span,
+        shadowed: Span,
+    },
+    // Used after being moved at `moved`
+    Moved {
+        name: String,
+        moved: Span,
+    },
+    // Borrowed as `&mut` while the `&mut` borrow at `first` is used later at
+    // `used`
+    MutableTwice {
+        name: String,
+        first: Span,
+        used: Span,
+    },
+    // The same, with one of the borrows a `&`
+    Conflict {
+        name: String,
+        first: Span,
+        first_mutable: bool,
+        used: Span,
+    },
+    // Read, moved or assigned while the borrow at `borrow` is used later at
+    // `used`
+    UsedWhileBorrowed {
+        name: String,
+        borrow: Span,
+        used: Span,
+    },
+    MovedWhileBorrowed {
+        name: String,
+        borrow: Span,
+        used: Span,
+    },
+    AssignedWhileBorrowed {
+        name: String,
+        borrow: Span,
+        used: Span,
+    },
+    // Borrowed as `&mut` while not `mut`
+    NotMutable {
+        name: String,
+        declared: Span,
+    },
+    // Assigned through the `&` reference declared at `declared`
+    BehindSharedReference {
+        name: String,
+        declared: Span,
+    },
+    // Dereferenced, declared at `declared` with a value that isn't a
+    // reference
+    NotAReference {
+        name: String,
+        declared: Span,
+    },
+    // Dropped at `dropped` while its borrow is used later at `used`
+    DoesNotLiveLongEnough {
+        name: String,
+        dropped: Span,
+        used: Span,
+    },
+    BreakOutsideLoop,
+    // The operator, and the types of its operands
+    UnsupportedOperation(String, &'static str, Option<&'static str>),
+    // The type of a condition that isn't a `bool`
+    NotABool(&'static str),
+    Overflow,
+    DivisionByZero,
+    // Loops stop after that many iterations, which are likely endless
+    TooManyIterations(usize),
+}
+
+// Parses, checks and runs `source`, returning what it printed
+pub fn run(source: &str) -> Result<String, Vec<Error>> {
+    let program = parse(source).map_err(|error| vec![error])?;
+    program.check()?;
+    program.run().map_err(|error| vec![error])
+}
+
+// What `source` prints, or its first error rendered under its line
+pub fn report(source: &str) -> String {
+    match run(source) {
+        Ok(output) => output,
+        Err(errors) => format!("{}\n", errors[0].render(source)),
+    }
+}
+
+// The lines of `source`, with the life of each binding drawn next to them.
+// Errors other than syntax ones don't stop the drawing.
+pub fn timeline(source: &str) -> Result<String, Error> {
+    let program = parse(source)?;
+    Ok(timeline::draw(source, &program))
+}
+
+impl Program {
+    // Every error the program has, in the order of the source
+    pub fn check(&self) -> Result<(), Vec<Error>> {
+        let mut errors = check::check(self);
+        // Moves and borrows are only worth checking once every name is right
+        if errors.is_empty() {
+            errors = ownership::check(self);
+            errors.sort_by_key(|error| error.span.start);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // What the program prints. Only errors the checker doesn't know about,
+    // such as overflows, can happen once it passed.
+    pub fn run(&self) -> Result<String, Error> {
+        run::run(self)
+    }
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, span: Span) -> Error {
+        Error { kind, span }
+    }
+
+    // The code rustc gives the same error
+    pub fn code(&self) -> Option<&'static str> {
+        match self.kind {
+            ErrorKind::NotInScope(_) | ErrorKind::UnknownFunction(_) => Some("E0425"),
+            ErrorKind::ArgumentCount(..) => Some("E0061"),
+            ErrorKind::Uninitialized { .. } => Some("E0381"),
+            ErrorKind::BreakOutsideLoop => Some("E0268"),
+            ErrorKind::NotABool(_) => Some("E0308"),
+            ErrorKind::Immutable { .. } | ErrorKind::Frozen { .. } => Some("E0384"),
+            ErrorKind::Moved { .. } => Some("E0382"),
+            ErrorKind::MutableTwice { .. } => Some("E0499"),
+            ErrorKind::Conflict { .. } => Some("E0502"),
+            ErrorKind::UsedWhileBorrowed { .. } => Some("E0503"),
+            ErrorKind::MovedWhileBorrowed { .. } => Some("E0505"),
+            ErrorKind::AssignedWhileBorrowed { .. } => Some("E0506"),
+            ErrorKind::BehindSharedReference { .. } => Some("E0594"),
+            ErrorKind::NotMutable { .. } => Some("E0596"),
+            ErrorKind::DoesNotLiveLongEnough { .. } => Some("E0597"),
+            ErrorKind::NotAReference { .. } => Some("E0614"),
+            _ => None,
+        }
+    }
+
+    // What the caret under `span` says, and the other places worth pointing
+    // at
+    fn labels(&self) -> (&'static str, Vec<(Span, String)>) {
+        match &self.kind {
+            ErrorKind::NotInScope(_) | ErrorKind::UnknownFunction(_) => {
+                ("not found in this scope", vec![])
+            }
+            ErrorKind::Uninitialized {
+                name,
+                declared,
+                path,
+            } => {
+                let mut others = vec![(*declared, format!("binding `{}` declared here", name))];
+                for (span, branch) in path {
+                    let when = match branch {
+                        Branch::If(taken) => format!("if this condition is `{}`", taken),
+                        Branch::While => "if this condition is `false` from the start".to_owned(),
+                        Branch::Break => "if the loop is left here".to_owned(),
+                    };
+                    others.push((*span, format!("{}, `{}` is not initialized", when, name)));
+                }
+                let label = if path.is_empty() {
+                    "used here but it isn't initialized"
+                } else {
+                    "used here but it is possibly-uninitialized"
+                };
+                (label, others)
+            }
+            ErrorKind::BreakOutsideLoop => ("cannot `break` outside of a loop", vec![]),
+            ErrorKind::Immutable { name, declared } => (
+                "cannot assign twice",
+                vec![(
+                    *declared,
+                    format!("help: make `{}` mutable: `mut {}`", name, name),
+                )],
+            ),
+            ErrorKind::Frozen {
+                name,
+                declared,
+                shadowed,
+            } => (
+                "frozen",
+                vec![
+                    (*shadowed, format!("this `{}` is mutable...", name)),
+                    (
+                        *declared,
+                        "...but shadowed by an immutable binding".to_owned(),
+                    ),
+                ],
+            ),
+            // Moved further down, in a loop
+            ErrorKind::Moved { moved, .. } if moved.start >= self.span.start => (
+                "value used here after move",
+                vec![(
+                    *moved,
+                    "value moved here, in previous iteration of loop".to_owned(),
+                )],
+            ),
+            ErrorKind::Moved { moved, .. } => (
+                "value used here after move",
+                vec![(*moved, "value moved here".to_owned())],
+            ),
+            ErrorKind::MutableTwice { first, used, .. } => (
+                "second mutable borrow occurs here",
+                vec![
+                    (*first, "first mutable borrow occurs here".to_owned()),
+                    (*used, "first borrow later used here".to_owned()),
+                ],
+            ),
+            ErrorKind::Conflict {
+                first,
+                first_mutable,
+                used,
+                ..
+            } => {
+                let (label, first_kind) = if *first_mutable {
+                    ("immutable borrow occurs here", "mutable")
+                } else {
+                    ("mutable borrow occurs here", "immutable")
+                };
+                (
+                    label,
+                    vec![
+                        (*first, format!("{} borrow occurs here", first_kind)),
+                        (*used, format!("{} borrow later used here", first_kind)),
+                    ],
+                )
+            }
+            ErrorKind::UsedWhileBorrowed { name, borrow, used } => (
+                "use of borrowed value",
+                vec![
+                    (*borrow, format!("`{}` is borrowed here", name)),
+                    (*used, "borrow later used here".to_owned()),
+                ],
+            ),
+            ErrorKind::AssignedWhileBorrowed { name, borrow, used } => (
+                "assignment to borrowed value occurs here",
+                vec![
+                    (*borrow, format!("`{}` is borrowed here", name)),
+                    (*used, "borrow later used here".to_owned()),
+                ],
+            ),
+            ErrorKind::MovedWhileBorrowed { name, borrow, used } => (
+                "move out of value occurs here",
+                vec![
+                    (*borrow, format!("borrow of `{}` occurs here", name)),
+                    (*used, "borrow later used here".to_owned()),
+                ],
+            ),
+            ErrorKind::NotMutable { name, declared } => (
+                "cannot borrow as mutable",
+                vec![(
+                    *declared,
+                    format!("help: make `{}` mutable: `mut {}`", name, name),
+                )],
+            ),
+            ErrorKind::BehindSharedReference { name, declared } => (
+                "cannot assign",
+                vec![(*declared, format!("`{}` is a `&` reference", name))],
+            ),
+            ErrorKind::NotAReference { name, declared } => (
+                "can't be dereferenced",
+                vec![(*declared, format!("`{}` isn't a reference", name))],
+            ),
+            ErrorKind::DoesNotLiveLongEnough {
+                name,
+                dropped,
+                used,
+            } => (
+                "borrowed value does not live long enough",
+                vec![
+                    (
+                        *dropped,
+                        format!("`{}` dropped here while still borrowed", name),
+                    ),
+                    (*used, "borrow later used here".to_owned()),
+                ],
+            ),
+            _ => ("", vec![]),
+        }
+    }
+
+    // The lines of `source` the error is about, in the style of rustc:
+    //
+    //     error[E0425]: cannot find value `short` in this scope
+    //      |
+    //     5 | println!("{}", short);
+    //      |                ^^^^^ not found in this scope
+    pub fn render(&self, source: &str) -> String {
+        let (label, others) = self.labels();
+        let mut labels: Vec<(Span, char, String)> = others
+            .into_iter()
+            .map(|(span, text)| (span, '-', text))
+            .collect();
+        labels.push((self.span, '^', label.to_owned()));
+        source::render(source, self.code(), &self.to_string(), labels)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character `{}`", c),
+            ErrorKind::InvalidNumber(text) => write!(f, "invalid integer `{}`", text),
+            ErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ErrorKind::UnexpectedToken(found, expected) => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            ErrorKind::UnexpectedEnd(expected) => {
+                write!(f, "expected {}, found the end of the input", expected)
+            }
+            ErrorKind::FormatArguments(placeholders, arguments) => write!(
+                f,
+                "{} positional arguments in format string, but there are {} arguments",
+                placeholders, arguments
+            ),
+            ErrorKind::NotInScope(name) => write!(f, "cannot find value `{}` in this scope", name),
+            ErrorKind::UnknownFunction(name) => {
+                write!(f, "cannot find function `{}` in this scope", name)
+            }
+            ErrorKind::ArgumentCount(expected, found) => write!(
+                f,
+                "this function takes {} argument(s) but {} were supplied",
+                expected, found
+            ),
+            ErrorKind::Uninitialized { name, path, .. } if path.is_empty() => {
+                write!(f, "used binding `{}` isn't initialized", name)
+            }
+            ErrorKind::Uninitialized { name, .. } => {
+                write!(f, "used binding `{}` is possibly-uninitialized", name)
+            }
+            ErrorKind::BreakOutsideLoop => write!(f, "`break` outside of a loop"),
+            ErrorKind::Immutable { name, .. } => {
+                write!(f, "cannot assign twice to immutable variable `{}`", name)
+            }
+            ErrorKind::Frozen { name, .. } => write!(
+                f,
+                "cannot assign to `{}`, frozen by an immutable shadowing binding",
+                name
+            ),
+            ErrorKind::Moved { name, .. } => write!(f, "use of moved value: `{}`", name),
+            ErrorKind::MutableTwice { name, .. } => write!(
+                f,
+                "cannot borrow `{}` as mutable more than once at a time",
+                name
+            ),
+            ErrorKind::Conflict {
+                name,
+                first_mutable,
+                ..
+            } => {
+                let (second, first) = if *first_mutable {
+                    ("immutable", "mutable")
+                } else {
+                    ("mutable", "immutable")
+                };
+                write!(
+                    f,
+                    "cannot borrow `{}` as {} because it is also borrowed as {}",
+                    name, second, first
+                )
+            }
+            ErrorKind::UsedWhileBorrowed { name, .. } => {
+                write!(f, "cannot use `{}` because it was mutably borrowed", name)
+            }
+            ErrorKind::MovedWhileBorrowed { name, .. } => {
+                write!(f, "cannot move out of `{}` because it is borrowed", name)
+            }
+            ErrorKind::AssignedWhileBorrowed { name, .. } => {
+                write!(f, "cannot assign to `{}` because it is borrowed", name)
+            }
+            ErrorKind::NotMutable { name, .. } => write!(
+                f,
+                "cannot borrow `{}` as mutable, as it is not declared as mutable",
+                name
+            ),
+            ErrorKind::BehindSharedReference { name, .. } => write!(
+                f,
+                "cannot assign to `*{}`, which is behind a `&` reference",
+                name
+            ),
+            ErrorKind::NotAReference { name, .. } => {
+                write!(f, "type of `{}` cannot be dereferenced", name)
+            }
+            ErrorKind::DoesNotLiveLongEnough { name, .. } => {
+                write!(f, "`{}` does not live long enough", name)
+            }
+            ErrorKind::UnsupportedOperation(op, left, Some(right)) => {
+                write!(f, "cannot apply `{}` to {} and {}", op, left, right)
+            }
+            ErrorKind::UnsupportedOperation(op, operand, None) => {
+                write!(f, "cannot apply unary `{}` to {}", op, operand)
+            }
+            ErrorKind::NotABool(found) => write!(f, "expected `bool`, found {}", found),
+            ErrorKind::Overflow => write!(f, "attempt to compute with overflow"),
+            ErrorKind::DivisionByZero => write!(f, "attempt to divide by zero"),
+            ErrorKind::TooManyIterations(limit) => {
+                write!(f, "loops ran more than {} times, stopped", limit)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The code of the first error of `source`, `None` if it runs
+    fn code(source: &str) -> Option<&'static str> {
+        run(source).err().and_then(|errors| errors[0].code())
+    }
+
+    #[test]
+    fn assigning_immutable() {
+        let source = "let _immutable_binding = 1;
+_immutable_binding += 1;";
+        assert_eq!(code(source), Some("E0384"));
+    }
+
+    #[test]
+    fn out_of_scope() {
+        let source = "{
+    let short_lived_binding = 2;
+}
+println!(\"outer short: {}\", short_lived_binding);";
+        assert_eq!(code(source), Some("E0425"));
+    }
+
+    #[test]
+    fn uninitialized() {
+        let source = "let another_binding;
+println!(\"another binding: {}\", another_binding);";
+        assert_eq!(code(source), Some("E0381"));
+    }
+
+    #[test]
+    fn assigning_frozen() {
+        let source = "let mut _mutable_integer = 7i32;
+{
+    let _mutable_integer = _mutable_integer;
+    _mutable_integer = 50;
+}";
+        assert_eq!(code(source), Some("E0384"));
+    }
+
+    #[test]
+    fn declared_first() {
+        let source = "let a_binding;
+{
+    let x = 2;
+    a_binding = x * x;
+}
+println!(\"a binding: {}\", a_binding);";
+        assert_eq!(run(source), Ok("a binding: 4\n".to_owned()));
+    }
+
+    #[test]
+    fn unfrozen() {
+        let source = "let mut _mutable_integer = 7i32;
+{
+    let _mutable_integer = _mutable_integer;
+}
+_mutable_integer = 3;
+println!(\"{}\", _mutable_integer);";
+        assert_eq!(run(source), Ok("3\n".to_owned()));
+    }
+
+    // The inner binding is dropped with its block, giving the outer one back
+    fn block_end(source: &str) -> String {
+        let timeline = timeline(source).unwrap();
+        let line = timeline.lines().find(|line| line.contains(" }"));
+        line.unwrap().to_owned()
+    }
+
+    #[test]
+    fn timeline_unshadowed() {
+        let source = "let shadowed_binding = 1;
+{
+    let shadowed_binding = \"abc\";
+}
+println!(\"outside inner block: {}\", shadowed_binding);";
+        let block_end = block_end(source);
+        assert!(block_end.contains("#2 dropped"));
+        assert!(block_end.contains("#1 visible again"));
+    }
+
+    #[test]
+    fn timeline_unfrozen() {
+        let source = "let mut _mutable_integer = 7i32;
+{
+    let _mutable_integer = _mutable_integer;
+}
+_mutable_integer = 3;";
+        let block_end = block_end(source);
+        assert!(block_end.contains("#2 dropped"));
+        assert!(block_end.contains("#1 mutable again"));
+    }
+
+    #[test]
+    fn use_after_move() {
+        let source = "let list = String::from(\"1, 2, 3\");
+let tail = list;
+println!(\"{}\", list);";
+        assert_eq!(code(source), Some("E0382"));
+    }
+
+    #[test]
+    fn move_out_of_borrowed() {
+        let source = "let list = String::from(\"1, 2, 3\");
+let this = &list;
+let tail = list;
+println!(\"{}\", this);";
+        assert_eq!(code(source), Some("E0505"));
+    }
+
+    #[test]
+    fn two_mutable_borrows() {
+        let source = "let mut list = String::from(\"1, 2, 3\");
+let first = &mut list;
+let second = &mut list;
+println!(\"{} {}\", first, second);";
+        assert_eq!(code(source), Some("E0499"));
+    }
+
+    #[test]
+    fn mutable_borrow_of_borrowed() {
+        let source = "let mut list = String::from(\"1, 2, 3\");
+let reader = &list;
+let writer = &mut list;
+println!(\"{}\", reader);";
+        assert_eq!(code(source), Some("E0502"));
+    }
+
+    #[test]
+    fn assigning_borrowed() {
+        let source = "let mut length = 3;
+let reader = &length;
+length = 4;
+println!(\"{}\", reader);";
+        assert_eq!(code(source), Some("E0506"));
+    }
+
+    #[test]
+    fn does_not_live_long_enough() {
+        let source = "let tail;
+{
+    let list = String::from(\"1, 2, 3\");
+    tail = &list;
+}
+println!(\"{}\", tail);";
+        assert_eq!(code(source), Some("E0597"));
+    }
+
+    #[test]
+    fn borrow_ends_at_last_use() {
+        let source = "let mut list = String::from(\"1, 2\");
+let reader = &list;
+println!(\"{}\", reader);
+let writer = &mut list;
+*writer = String::from(\"1, 2, 3\");
+println!(\"{}\", list);";
+        assert_eq!(run(source), Ok("1, 2\n1, 2, 3\n".to_owned()));
+    }
+
+    #[test]
+    fn deref_of_non_reference() {
+        let source = "let mut x = 1;
+*x = 2;";
+        assert_eq!(code(source), Some("E0614"));
+        let source = "let x = 1;
+let y = *x;";
+        assert_eq!(code(source), Some("E0614"));
+        let source = "let mut x = 1;
+let r = &mut x;
+*r = *r + 1;
+let s = &x;
+println!(\"{}\", *s);";
+        assert_eq!(run(source), Ok("2\n".to_owned()));
+    }
+
+    // The branches of the path on which the first error of `source` finds
+    // a binding uninitialized
+    fn uninitialized_path(source: &str) -> Option<Vec<Branch>> {
+        match run(source).err()?.remove(0).kind {
+            ErrorKind::Uninitialized { path, .. } => {
+                Some(path.iter().map(|(_, branch)| *branch).collect())
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn uninitialized_if_false() {
+        // rustc doesn't look at the condition, even when it can't be false
+        let source = "let another_binding;
+if true {
+    another_binding = 1;
+}
+println!(\"another binding: {}\", another_binding);";
+        assert_eq!(uninitialized_path(source), Some(vec![Branch::If(false)]));
+    }
+
+    #[test]
+    fn uninitialized_while_skipped() {
+        let source = "let mut another_binding;
+let mut x = 2;
+while x < 10 {
+    x = x * x;
+    another_binding = x;
+}
+println!(\"another binding: {}\", another_binding);";
+        assert_eq!(uninitialized_path(source), Some(vec![Branch::While]));
+    }
+
+    #[test]
+    fn uninitialized_at_break() {
+        let source = "let mut another_binding;
+let mut x = 2;
+loop {
+    if x > 10 {
+        break;
+    }
+    x = x * x;
+    another_binding = x;
+}
+println!(\"another binding: {}\", another_binding);";
+        assert_eq!(
+            uninitialized_path(source),
+            Some(vec![Branch::If(true), Branch::Break])
+        );
+    }
+
+    #[test]
+    fn initialized_on_every_path() {
+        let source = "let another_binding;
+let x = 2;
+if x > 1 {
+    another_binding = 1;
+} else if x > 0 {
+    another_binding = 2;
+} else {
+    return;
+}
+let mut squares;
+loop {
+    squares = x * x;
+    break;
+}
+println!(\"another binding: {}, {}\", another_binding, squares);";
+        assert_eq!(run(source), Ok("another binding: 1, 4\n".to_owned()));
+    }
+}
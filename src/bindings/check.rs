@@ -0,0 +1,432 @@
+use super::{Block, Branch, Error, ErrorKind, Expr, Program, Stmt};
+use crate::source::{Span, Spanned};
+
+// What the checker knows of a binding
+#[derive(Debug)]
+struct Binding {
+    // Its index among the bindings of the program, in the order of their
+    // `let`s
+    id: usize,
+    name: String,
+    mutable: bool,
+    // The name in its `let`
+    declared: Span,
+    // The binding with the same name it hides
+    shadows: Option<usize>,
+}
+
+// Something that happened to a binding, at `at` in the source, as the
+// checker went through the program
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub binding: usize,
+    pub at: Span,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventKind {
+    Introduced { name: String, mutable: bool },
+    // Hidden by the binding `by`, which is immutable while this one is
+    // mutable for `Frozen`
+    Shadowed { by: usize },
+    Frozen { by: usize },
+    // Visible, or mutable, again, its shadow being dropped
+    Unshadowed,
+    Unfrozen,
+    Read,
+    Assigned,
+    Dropped,
+}
+
+// Whether a binding is initialized, at some point of the program, on every
+// path leading there
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Init {
+    // The branches of a path on which it isn't, if there is one
+    missing: Option<Vec<(Span, Branch)>>,
+    // Whether there is a path on which it is
+    maybe: bool,
+}
+
+// Indexed by binding, `None` for the code no path reaches, after a `break`
+// or a `return`
+type State = Option<Vec<Init>>;
+
+// The bindings in scope, innermost block last, and latest binding last in
+// each block, so that the last one with a name is the one it refers to
+struct Checker {
+    scopes: Vec<Vec<Binding>>,
+    count: usize,
+    errors: Vec<Error>,
+    events: Vec<Event>,
+    state: State,
+    // The states at the `break`s of each loop, innermost loop last
+    breaks: Vec<Vec<State>>,
+}
+
+pub fn check(program: &Program) -> Vec<Error> {
+    walk(program).0
+}
+
+// The errors, and what happened to the bindings
+pub fn walk(program: &Program) -> (Vec<Error>, Vec<Event>) {
+    let mut checker = Checker {
+        scopes: Vec::new(),
+        count: 0,
+        errors: Vec::new(),
+        events: Vec::new(),
+        state: Some(Vec::new()),
+        breaks: Vec::new(),
+    };
+    checker.block(&program.body);
+    (checker.errors, checker.events)
+}
+
+impl Checker {
+    fn block(&mut self, block: &Block) {
+        self.scopes.push(Vec::new());
+        for statement in &block.statements {
+            self.statement(statement);
+        }
+
+        let dropped = self.scopes.pop().unwrap_or_default();
+        for binding in dropped.iter().rev() {
+            self.event(binding.id, block.close, EventKind::Dropped);
+            let shadowed = binding
+                .shadows
+                .and_then(|id| self.scopes.iter().flatten().find(|other| other.id == id));
+            if let Some(shadowed) = shadowed {
+                let kind = if shadowed.mutable && !binding.mutable {
+                    EventKind::Unfrozen
+                } else {
+                    EventKind::Unshadowed
+                };
+                self.event(shadowed.id, block.close, kind);
+            }
+        }
+    }
+
+    fn event(&mut self, binding: usize, at: Span, kind: EventKind) {
+        self.events.push(Event { binding, at, kind });
+    }
+
+    fn statement(&mut self, statement: &Spanned<Stmt>) {
+        match &statement.node {
+            Stmt::Let {
+                name,
+                mutable,
+                value,
+            } => {
+                // The value is checked first: in `let x = x;` it is the
+                // previous `x` that is read
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+                let id = self.count;
+                self.count += 1;
+                self.event(
+                    id,
+                    name.span,
+                    EventKind::Introduced {
+                        name: name.node.clone(),
+                        mutable: *mutable,
+                    },
+                );
+                let shadows = self
+                    .lookup(&name.node)
+                    .map(|shadowed| (shadowed.id, shadowed.mutable));
+                if let Some((shadowed, was_mutable)) = shadows {
+                    let kind = if was_mutable && !mutable {
+                        EventKind::Frozen { by: id }
+                    } else {
+                        EventKind::Shadowed { by: id }
+                    };
+                    self.event(shadowed, name.span, kind);
+                }
+                self.set(
+                    id,
+                    Init {
+                        missing: if value.is_some() {
+                            None
+                        } else {
+                            Some(Vec::new())
+                        },
+                        maybe: value.is_some(),
+                    },
+                );
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.push(Binding {
+                        id,
+                        name: name.node.clone(),
+                        mutable: *mutable,
+                        declared: name.span,
+                        shadows: shadows.map(|(shadowed, _)| shadowed),
+                    });
+                }
+            }
+            Stmt::Assign {
+                name,
+                deref,
+                op,
+                value,
+            } => {
+                self.expression(value);
+                // `x += 1` reads `x` first, and `*r = 1` only reads `r`
+                if op.is_some() || *deref {
+                    self.read(&name.node, name.span);
+                }
+                if !deref {
+                    self.assign(name, statement.span);
+                }
+            }
+            Stmt::Block(block) => self.block(block),
+            Stmt::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.expression(condition);
+                let entry = self.state.clone();
+                branch(&mut self.state, condition.span, Branch::If(true));
+                self.block(then);
+                let after_then = self.state.take();
+                self.state = entry;
+                branch(&mut self.state, condition.span, Branch::If(false));
+                if let Some(otherwise) = otherwise {
+                    self.block(otherwise);
+                }
+                self.state = join(after_then, self.state.take());
+            }
+            Stmt::While { condition, body } => {
+                self.expression(condition);
+                let mut skipped = self.state.clone();
+                branch(&mut skipped, condition.span, Branch::While);
+                // Left when the condition is false, after any number of
+                // iterations
+                let breaks = self.repeat(body);
+                let done = join(skipped, self.state.take());
+                self.state = breaks.into_iter().fold(done, join);
+            }
+            // Only left by a `break`
+            Stmt::Loop(body) => {
+                let breaks = self.repeat(body);
+                self.state = breaks.into_iter().fold(None, join);
+            }
+            Stmt::Break => match self.breaks.last_mut() {
+                Some(breaks) => {
+                    let mut state = self.state.take();
+                    branch(&mut state, statement.span, Branch::Break);
+                    breaks.push(state);
+                }
+                None => self
+                    .errors
+                    .push(Error::new(ErrorKind::BreakOutsideLoop, statement.span)),
+            },
+            Stmt::Return => self.state = None,
+            Stmt::Expr(expr) => self.expression(expr),
+            Stmt::Print {
+                format, arguments, ..
+            } => {
+                let placeholders = super::run::placeholders(&format.node);
+                if placeholders != arguments.len() {
+                    self.errors.push(Error::new(
+                        ErrorKind::FormatArguments(placeholders, arguments.len()),
+                        format.span,
+                    ));
+                }
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &Spanned<Expr>) {
+        match &expr.node {
+            Expr::Int(_) | Expr::Str(_) | Expr::Bool(_) => {}
+            Expr::Variable(name) => self.read(name, expr.span),
+            Expr::Negate(operand) => self.expression(operand),
+            Expr::Binary(_, left, right) | Expr::Compare(_, left, right) => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expr::Borrow { name, .. } | Expr::Deref(name) => self.read(&name.node, name.span),
+            Expr::Call {
+                function,
+                arguments,
+            } => {
+                let kind = match super::run::arity(&function.node) {
+                    None => Some(ErrorKind::UnknownFunction(function.node.clone())),
+                    Some(expected) if expected != arguments.len() => {
+                        Some(ErrorKind::ArgumentCount(expected, arguments.len()))
+                    }
+                    Some(_) => None,
+                };
+                if let Some(kind) = kind {
+                    self.errors.push(Error::new(kind, function.span));
+                }
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+        }
+    }
+
+    // Checks the body of a loop, returning the states at its `break`s.
+    //
+    // The body first runs with what is initialized before the loop, then
+    // again with what its first run may have initialized too, for the
+    // assignments of a later iteration to count. The errors of the first
+    // run are dropped, the second one finding them again.
+    fn repeat(&mut self, body: &Block) -> Vec<State> {
+        let (count, errors, events) = (self.count, self.errors.len(), self.events.len());
+        let entry = self.state.clone();
+        self.breaks.push(Vec::new());
+        self.block(body);
+        self.breaks.pop();
+        self.count = count;
+        self.errors.truncate(errors);
+        self.events.truncate(events);
+
+        // Only what may be initialized grows, a later iteration can't be
+        // missing more than the first one
+        let again = join(entry.clone(), self.state.take());
+        self.state = match (entry, again) {
+            (Some(mut entry), Some(again)) => {
+                for (init, later) in entry.iter_mut().zip(again) {
+                    init.maybe = later.maybe;
+                }
+                Some(entry)
+            }
+            (entry, _) => entry,
+        };
+        self.breaks.push(Vec::new());
+        self.block(body);
+        self.breaks.pop().unwrap_or_default()
+    }
+
+    fn set(&mut self, id: usize, init: Init) {
+        if let Some(state) = &mut self.state {
+            if state.len() <= id {
+                state.resize(id + 1, Init::default());
+            }
+            state[id] = init;
+        }
+    }
+
+    // What is known of the binding `id`, which is everything in code that
+    // can't be reached
+    fn init(&self, id: usize) -> Init {
+        match &self.state {
+            Some(state) => state.get(id).cloned().unwrap_or_default(),
+            None => Init {
+                missing: None,
+                maybe: false,
+            },
+        }
+    }
+
+    fn lookup(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|binding| binding.name == name)
+    }
+
+    fn read(&mut self, name: &str, span: Span) {
+        let (id, declared) = match self.lookup(name) {
+            Some(binding) => (binding.id, binding.declared),
+            None => {
+                let kind = ErrorKind::NotInScope(name.to_owned());
+                self.errors.push(Error::new(kind, span));
+                return;
+            }
+        };
+        match self.init(id).missing {
+            Some(path) => self.errors.push(Error::new(
+                ErrorKind::Uninitialized {
+                    name: name.to_owned(),
+                    declared,
+                    path,
+                },
+                span,
+            )),
+            None => self.event(id, span, EventKind::Read),
+        }
+    }
+
+    // `span` is the whole assignment
+    fn assign(&mut self, name: &Spanned<String>, span: Span) {
+        let (id, mutable, declared) = match self.lookup(&name.node) {
+            Some(binding) => (binding.id, binding.mutable, binding.declared),
+            None => {
+                self.errors.push(Error::new(
+                    ErrorKind::NotInScope(name.node.clone()),
+                    name.span,
+                ));
+                return;
+            }
+        };
+        // The first assignment of a deferred binding is its initialization,
+        // `mut` or not
+        if mutable || !self.init(id).maybe {
+            self.set(
+                id,
+                Init {
+                    missing: None,
+                    maybe: true,
+                },
+            );
+            self.event(id, span, EventKind::Assigned);
+            return;
+        }
+
+        // A mutable binding this one shadows makes it a frozen one
+        let shadowed = self
+            .scopes
+            .iter()
+            .flatten()
+            .filter(|binding| binding.name == name.node && binding.declared != declared)
+            .rfind(|binding| binding.mutable)
+            .map(|binding| binding.declared);
+        let kind = match shadowed {
+            Some(shadowed) => ErrorKind::Frozen {
+                name: name.node.clone(),
+                declared,
+                shadowed,
+            },
+            None => ErrorKind::Immutable {
+                name: name.node.clone(),
+                declared,
+            },
+        };
+        self.errors.push(Error::new(kind, span));
+    }
+}
+
+// Adds `branch` to the paths on which bindings aren't initialized
+fn branch(state: &mut State, span: Span, branch: Branch) {
+    for init in state.iter_mut().flatten() {
+        if let Some(path) = &mut init.missing {
+            path.push((span, branch));
+        }
+    }
+}
+
+// What is known where two paths meet
+fn join(a: State, b: State) -> State {
+    let (mut a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a, b),
+        (a, b) => return a.or(b),
+    };
+    if a.len() < b.len() {
+        a.resize(b.len(), Init::default());
+    }
+    for (init, other) in a.iter_mut().zip(b) {
+        init.missing = init.missing.take().or(other.missing);
+        init.maybe |= other.maybe;
+    }
+    Some(a)
+}
@@ -0,0 +1,567 @@
+use super::{Block, Error, ErrorKind, Expr, Program, Stmt};
+use crate::source::{Span, Spanned};
+
+// Moves and borrows, checked once the names are known to be right, as rustc
+// runs its borrow checker after name resolution.
+//
+// Integers, string literals and shared references are `Copy`, strings made
+// with `String::from` and mutable references move. A borrow lasts until the
+// last use of the bindings holding it rather than to the end of their
+// scope, as with rustc's non-lexical lifetimes: a first pass finds the last
+// uses, a second one reports the errors. A binding used in a loop stays in
+// use until the end of the loop, its next iteration using it again.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Copy,
+    Owned,
+    SharedRef,
+    MutRef,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Uninitialized,
+    Initialized,
+    // Where it was moved
+    Moved(Span),
+}
+
+#[derive(Debug)]
+struct Owner {
+    id: usize,
+    name: String,
+    mutable: bool,
+    kind: Kind,
+    state: State,
+    // The borrows whose references it holds
+    loans: Vec<usize>,
+    declared: Span,
+}
+
+// The state, kind and loans of the bindings in scope, outermost first, at a
+// point of the program. `None` where no path reaches, after a `break` or a
+// `return`.
+type Snapshot = Option<Vec<(State, Kind, Vec<usize>)>>;
+
+// A `&x` or a `&mut x`
+#[derive(Debug, Clone, Copy)]
+struct Loan {
+    of: usize,
+    mutable: bool,
+    span: Span,
+}
+
+struct Borrowck {
+    scopes: Vec<Vec<Owner>>,
+    count: usize,
+    loans: Vec<Loan>,
+    // The last use of each binding, found by the first pass, and until
+    // where it is in use
+    last_use: Vec<Option<(Span, usize)>>,
+    uses: Vec<Option<(Span, usize)>>,
+    errors: Vec<Error>,
+    reachable: bool,
+    // The snapshots at the `break`s of each loop, innermost loop last
+    breaks: Vec<Vec<Snapshot>>,
+}
+
+pub fn check(program: &Program) -> Vec<Error> {
+    let first = Borrowck::new(Vec::new()).run(program);
+    Borrowck::new(first.uses).run(program).errors
+}
+
+impl Kind {
+    fn is_copy(self) -> bool {
+        matches!(self, Kind::Copy | Kind::SharedRef)
+    }
+}
+
+impl Borrowck {
+    fn new(last_use: Vec<Option<(Span, usize)>>) -> Borrowck {
+        Borrowck {
+            scopes: Vec::new(),
+            count: 0,
+            loans: Vec::new(),
+            last_use,
+            uses: Vec::new(),
+            errors: Vec::new(),
+            reachable: true,
+            breaks: Vec::new(),
+        }
+    }
+
+    fn run(mut self, program: &Program) -> Borrowck {
+        self.block(&program.body);
+        self
+    }
+
+    fn block(&mut self, block: &Block) {
+        self.scopes.push(Vec::new());
+        for statement in &block.statements {
+            self.statement(statement);
+        }
+
+        // A reference still in use can't outlive what it refers to
+        let dropped = self.scopes.pop().unwrap_or_default();
+        for owner in dropped.iter().rev() {
+            if let Some((loan, used)) = self.live_loans(owner.id, block.close.start).first() {
+                self.error(
+                    ErrorKind::DoesNotLiveLongEnough {
+                        name: owner.name.clone(),
+                        dropped: block.close,
+                        used: *used,
+                    },
+                    loan.span,
+                );
+            }
+        }
+    }
+
+    fn statement(&mut self, statement: &Spanned<Stmt>) {
+        match &statement.node {
+            Stmt::Let {
+                name,
+                mutable,
+                value,
+            } => {
+                let (kind, loans) = match value {
+                    Some(value) => self.value(value),
+                    None => (Kind::Copy, Vec::new()),
+                };
+                let id = self.count;
+                self.count += 1;
+                let state = if value.is_some() {
+                    State::Initialized
+                } else {
+                    State::Uninitialized
+                };
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.push(Owner {
+                        id,
+                        name: name.node.clone(),
+                        mutable: *mutable,
+                        kind,
+                        state,
+                        loans,
+                        declared: name.span,
+                    });
+                }
+            }
+            Stmt::Assign {
+                name,
+                deref: true,
+                value,
+                ..
+            } => {
+                self.value(value);
+                self.dereferenced(name);
+                if let Some(owner) = self.used(name) {
+                    if owner.kind == Kind::SharedRef {
+                        let declared = owner.declared;
+                        self.error(
+                            ErrorKind::BehindSharedReference {
+                                name: name.node.clone(),
+                                declared,
+                            },
+                            statement.span,
+                        );
+                    }
+                }
+            }
+            Stmt::Assign {
+                name,
+                deref: false,
+                op,
+                value,
+            } => {
+                let (kind, loans) = self.value(value);
+                if op.is_some() {
+                    self.used(name);
+                }
+                let id = match self.owner(&name.node) {
+                    Some(owner) => owner.id,
+                    None => return,
+                };
+                if let Some((loan, used)) = self.live_loans(id, statement.span.start).first() {
+                    self.error(
+                        ErrorKind::AssignedWhileBorrowed {
+                            name: name.node.clone(),
+                            borrow: loan.span,
+                            used: *used,
+                        },
+                        statement.span,
+                    );
+                }
+                if let Some(owner) = self.owner(&name.node) {
+                    owner.state = State::Initialized;
+                    if op.is_none() {
+                        owner.kind = kind;
+                        owner.loans = loans;
+                    }
+                }
+            }
+            Stmt::Block(block) => self.block(block),
+            Stmt::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.read(condition);
+                let entry = self.snapshot();
+                self.block(then);
+                let after_then = self.snapshot();
+                self.restore(entry);
+                if let Some(otherwise) = otherwise {
+                    self.block(otherwise);
+                }
+                let after_otherwise = self.snapshot();
+                self.restore(merge(after_then, after_otherwise));
+            }
+            Stmt::While { condition, body } => {
+                self.read(condition);
+                let outer = self.in_scope();
+                let entry = self.snapshot();
+                let breaks = self.repeat(body);
+                let done = merge(entry, self.snapshot());
+                self.restore(breaks.into_iter().fold(done, merge));
+                self.in_use_throughout(&outer, statement.span);
+            }
+            Stmt::Loop(body) => {
+                let outer = self.in_scope();
+                let breaks = self.repeat(body);
+                self.restore(breaks.into_iter().fold(None, merge));
+                self.in_use_throughout(&outer, statement.span);
+            }
+            Stmt::Break => {
+                let snapshot = self.snapshot();
+                if let Some(breaks) = self.breaks.last_mut() {
+                    breaks.push(snapshot);
+                }
+                self.restore(None);
+            }
+            Stmt::Return => self.restore(None),
+            // `println!` only borrows its arguments
+            Stmt::Print { arguments, .. } => {
+                for argument in arguments {
+                    self.read(argument);
+                }
+            }
+            Stmt::Expr(expr) => {
+                self.value(expr);
+            }
+        }
+    }
+
+    // Checks the body of a loop, returning the snapshots at its `break`s.
+    //
+    // The body first runs from the state before the loop, then again from
+    // what its first run may have moved or borrowed too, for a move in an
+    // iteration to be seen by the next one. The errors of the first run are
+    // dropped, the second one finding them again.
+    fn repeat(&mut self, body: &Block) -> Vec<Snapshot> {
+        let (count, errors) = (self.count, self.errors.len());
+        let entry = self.snapshot();
+        self.breaks.push(Vec::new());
+        self.block(body);
+        self.breaks.pop();
+        self.count = count;
+        self.errors.truncate(errors);
+
+        let again = merge(entry, self.snapshot());
+        self.restore(again);
+        self.breaks.push(Vec::new());
+        self.block(body);
+        self.breaks.pop().unwrap_or_default()
+    }
+
+    fn in_scope(&self) -> Vec<usize> {
+        self.scopes.iter().flatten().map(|owner| owner.id).collect()
+    }
+
+    // Keeps the bindings of `ids` used in the loop at `span` in use until
+    // its end
+    fn in_use_throughout(&mut self, ids: &[usize], span: Span) {
+        for &id in ids {
+            if let Some(Some((_, until))) = self.uses.get_mut(id) {
+                if *until >= span.start {
+                    *until = (*until).max(span.end);
+                }
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        if !self.reachable {
+            return None;
+        }
+        let owners = self.scopes.iter().flatten();
+        Some(
+            owners
+                .map(|owner| (owner.state, owner.kind, owner.loans.clone()))
+                .collect(),
+        )
+    }
+
+    // Back to `snapshot`, which may have been taken with more bindings in
+    // scope
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.reachable = snapshot.is_some();
+        let owners = self.scopes.iter_mut().flatten();
+        for (owner, (state, kind, loans)) in owners.zip(snapshot.unwrap_or_default()) {
+            owner.state = state;
+            owner.kind = kind;
+            owner.loans = loans;
+        }
+    }
+
+    // Evaluates `expr` to look at it, which borrows a binding rather than
+    // moving it
+    fn read(&mut self, expr: &Spanned<Expr>) {
+        match &expr.node {
+            Expr::Variable(name) => {
+                let name = Spanned {
+                    node: name.clone(),
+                    span: expr.span,
+                };
+                self.borrow(&name, false);
+            }
+            _ => {
+                self.value(expr);
+            }
+        }
+    }
+
+    // Evaluates `expr` for its value, moving what isn't `Copy`. Returns the
+    // kind of the value, and the borrows it holds.
+    fn value(&mut self, expr: &Spanned<Expr>) -> (Kind, Vec<usize>) {
+        match &expr.node {
+            Expr::Int(_) | Expr::Str(_) | Expr::Bool(_) => (Kind::Copy, Vec::new()),
+            Expr::Variable(name) => {
+                let name = Spanned {
+                    node: name.clone(),
+                    span: expr.span,
+                };
+                let (id, kind, loans) = match self.used(&name) {
+                    Some(owner) if owner.state == State::Initialized => {
+                        (owner.id, owner.kind, owner.loans.clone())
+                    }
+                    _ => return (Kind::Copy, Vec::new()),
+                };
+                let live = self.live_loans(id, expr.span.start);
+                if kind.is_copy() {
+                    // Reading a copy needs no `&mut` in the way
+                    if let Some((loan, used)) = live.iter().find(|(loan, _)| loan.mutable) {
+                        self.error(
+                            ErrorKind::UsedWhileBorrowed {
+                                name: name.node.clone(),
+                                borrow: loan.span,
+                                used: *used,
+                            },
+                            expr.span,
+                        );
+                    }
+                } else {
+                    if let Some((loan, used)) = live.first() {
+                        self.error(
+                            ErrorKind::MovedWhileBorrowed {
+                                name: name.node.clone(),
+                                borrow: loan.span,
+                                used: *used,
+                            },
+                            expr.span,
+                        );
+                    }
+                    if let Some(owner) = self.owner(&name.node) {
+                        owner.state = State::Moved(expr.span);
+                    }
+                }
+                (kind, loans)
+            }
+            Expr::Borrow { mutable, name } => match self.borrow(name, *mutable) {
+                Some(loan) if *mutable => (Kind::MutRef, vec![loan]),
+                Some(loan) => (Kind::SharedRef, vec![loan]),
+                None => (Kind::Copy, Vec::new()),
+            },
+            Expr::Deref(name) => {
+                self.dereferenced(name);
+                self.used(name);
+                (Kind::Copy, Vec::new())
+            }
+            Expr::Negate(operand) => {
+                self.value(operand);
+                (Kind::Copy, Vec::new())
+            }
+            // `String + &str` gives a `String`, moving the left one
+            Expr::Binary(_, left, right) => {
+                let (kind, _) = self.value(left);
+                self.value(right);
+                match kind {
+                    Kind::Owned => (Kind::Owned, Vec::new()),
+                    _ => (Kind::Copy, Vec::new()),
+                }
+            }
+            Expr::Compare(_, left, right) => {
+                self.read(left);
+                self.read(right);
+                (Kind::Copy, Vec::new())
+            }
+            Expr::Call {
+                function,
+                arguments,
+            } => {
+                for argument in arguments {
+                    self.value(argument);
+                }
+                match function.node.as_str() {
+                    "String::from" => (Kind::Owned, Vec::new()),
+                    _ => (Kind::Copy, Vec::new()),
+                }
+            }
+        }
+    }
+
+    // Borrows `name`, returning the loan, if it can be borrowed
+    fn borrow(&mut self, name: &Spanned<String>, mutable: bool) -> Option<usize> {
+        let (id, declared, declared_mutable) = match self.used(name) {
+            Some(owner) if owner.state == State::Initialized => {
+                (owner.id, owner.declared, owner.mutable)
+            }
+            _ => return None,
+        };
+        if mutable && !declared_mutable {
+            self.error(
+                ErrorKind::NotMutable {
+                    name: name.node.clone(),
+                    declared,
+                },
+                name.span,
+            );
+        }
+
+        let live = self.live_loans(id, name.span.start);
+        let conflict = live
+            .iter()
+            .find(|(loan, _)| mutable || loan.mutable)
+            .copied();
+        if let Some((loan, used)) = conflict {
+            let kind = if mutable && loan.mutable {
+                ErrorKind::MutableTwice {
+                    name: name.node.clone(),
+                    first: loan.span,
+                    used,
+                }
+            } else {
+                ErrorKind::Conflict {
+                    name: name.node.clone(),
+                    first: loan.span,
+                    first_mutable: loan.mutable,
+                    used,
+                }
+            };
+            self.error(kind, name.span);
+        }
+
+        self.loans.push(Loan {
+            of: id,
+            mutable,
+            span: name.span,
+        });
+        Some(self.loans.len() - 1)
+    }
+
+    // The binding `name` refers to, after reporting a use after a move
+    // Only references can be dereferenced
+    fn dereferenced(&mut self, name: &Spanned<String>) {
+        let declared = match self.owner(&name.node) {
+            Some(owner) if !matches!(owner.kind, Kind::SharedRef | Kind::MutRef) => owner.declared,
+            _ => return,
+        };
+        let kind = ErrorKind::NotAReference {
+            name: name.node.clone(),
+            declared,
+        };
+        self.error(kind, name.span);
+    }
+
+    fn used(&mut self, name: &Spanned<String>) -> Option<&Owner> {
+        let (id, state) = match self.owner(&name.node) {
+            Some(owner) => (owner.id, owner.state),
+            None => return None,
+        };
+        if self.uses.len() <= id {
+            self.uses.resize(id + 1, None);
+        }
+        let until = match self.uses[id] {
+            Some((_, until)) => until.max(name.span.start),
+            None => name.span.start,
+        };
+        self.uses[id] = Some((name.span, until));
+        if let State::Moved(moved) = state {
+            self.error(
+                ErrorKind::Moved {
+                    name: name.node.clone(),
+                    moved,
+                },
+                name.span,
+            );
+        }
+        self.owner(&name.node).map(|owner| &*owner)
+    }
+
+    fn owner(&mut self, name: &str) -> Option<&mut Owner> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|owner| owner.name == name)
+    }
+
+    // The borrows of the binding `id` still to be used after `at`, with
+    // that use
+    fn live_loans(&self, id: usize, at: usize) -> Vec<(Loan, Span)> {
+        let holders = self.scopes.iter().flatten();
+        let mut live = Vec::new();
+        for holder in holders {
+            let last = match self.last_use.get(holder.id) {
+                Some(Some((last, until))) if *until > at => *last,
+                _ => continue,
+            };
+            for &loan in &holder.loans {
+                if self.loans[loan].of == id {
+                    live.push((self.loans[loan], last));
+                }
+            }
+        }
+        live
+    }
+
+    fn error(&mut self, kind: ErrorKind, span: Span) {
+        self.errors.push(Error::new(kind, span));
+    }
+}
+
+// What is known where two paths meet: a binding moved on either one is
+// moved, and holds the loans it holds on both
+fn merge(a: Snapshot, b: Snapshot) -> Snapshot {
+    let (mut a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a, b),
+        (a, b) => return a.or(b),
+    };
+    for ((state, kind, loans), (other_state, other_kind, other_loans)) in a.iter_mut().zip(b) {
+        match (*state, other_state) {
+            (State::Moved(_), _) => {}
+            (_, State::Moved(_)) | (State::Uninitialized, _) => *state = other_state,
+            _ => {}
+        }
+        if *kind == Kind::Copy {
+            *kind = other_kind;
+        }
+        for loan in other_loans {
+            if !loans.contains(&loan) {
+                loans.push(loan);
+            }
+        }
+    }
+    Some(a)
+}
@@ -0,0 +1,385 @@
+use std::fmt;
+
+use super::{Block, Error, ErrorKind, Expr, Program, Stmt};
+use crate::source::Spanned;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i64),
+    Str(String),
+    Bool(bool),
+    // The scope and the slot of the binding referred to
+    Ref(usize, usize),
+    Unit,
+}
+
+// Where a statement leaves the program
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flow {
+    Next,
+    Break,
+    Return,
+}
+
+// Only bindings that were checked are run, so every name is found and
+// initialized when it is read
+struct Interpreter {
+    scopes: Vec<Vec<(String, Option<Value>)>>,
+    output: String,
+    iterations: usize,
+}
+
+const MAX_ITERATIONS: usize = 100_000;
+
+pub fn run(program: &Program) -> Result<String, Error> {
+    let mut interpreter = Interpreter {
+        scopes: Vec::new(),
+        output: String::new(),
+        iterations: 0,
+    };
+    interpreter.block(&program.body)?;
+    Ok(interpreter.output)
+}
+
+// The number of arguments of a function, `None` for an unknown one
+pub fn arity(function: &str) -> Option<usize> {
+    match function {
+        "String::from" | "drop" => Some(1),
+        _ => None,
+    }
+}
+
+// The number of `{}` and `{:?}` in a format string
+pub fn placeholders(format: &str) -> usize {
+    format.replace("{{", "").matches('{').count()
+}
+
+impl Interpreter {
+    fn block(&mut self, block: &Block) -> Result<Flow, Error> {
+        self.scopes.push(Vec::new());
+        let mut flow = Flow::Next;
+        for statement in &block.statements {
+            flow = self.statement(statement)?;
+            if flow != Flow::Next {
+                break;
+            }
+        }
+        self.scopes.pop();
+        Ok(flow)
+    }
+
+    fn statement(&mut self, statement: &Spanned<Stmt>) -> Result<Flow, Error> {
+        match &statement.node {
+            Stmt::Let { name, value, .. } => {
+                let value = value
+                    .as_ref()
+                    .map(|value| self.evaluate(value))
+                    .transpose()?;
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.push((name.node.clone(), value));
+                }
+            }
+            Stmt::Assign {
+                name,
+                deref,
+                op,
+                value,
+            } => {
+                let mut value = self.evaluate(value)?;
+                let (mut scope, mut slot) = self.locate(&name.node);
+                if *deref {
+                    if let Some(Value::Ref(referent_scope, referent_slot)) =
+                        &self.scopes[scope][slot].1
+                    {
+                        (scope, slot) = (*referent_scope, *referent_slot);
+                    }
+                }
+                if let Some(op) = op {
+                    let current = self.scopes[scope][slot].1.clone().unwrap_or(Value::Unit);
+                    value = binary(*op, self.resolve(current), self.resolve(value)).map_err(
+                        |kind| Error {
+                            kind,
+                            span: statement.span,
+                        },
+                    )?;
+                }
+                self.scopes[scope][slot].1 = Some(value);
+            }
+            Stmt::Block(block) => return self.block(block),
+            Stmt::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if self.condition(condition)? {
+                    return self.block(then);
+                }
+                if let Some(otherwise) = otherwise {
+                    return self.block(otherwise);
+                }
+            }
+            Stmt::While { condition, body } => {
+                while self.condition(condition)? {
+                    self.iterate(statement)?;
+                    match self.block(body)? {
+                        Flow::Next => {}
+                        Flow::Break => break,
+                        Flow::Return => return Ok(Flow::Return),
+                    }
+                }
+            }
+            Stmt::Loop(body) => loop {
+                self.iterate(statement)?;
+                match self.block(body)? {
+                    Flow::Next => {}
+                    Flow::Break => break,
+                    Flow::Return => return Ok(Flow::Return),
+                }
+            },
+            Stmt::Break => return Ok(Flow::Break),
+            Stmt::Return => return Ok(Flow::Return),
+            Stmt::Expr(expr) => {
+                self.evaluate(expr)?;
+            }
+            Stmt::Print {
+                format,
+                arguments,
+                newline,
+            } => {
+                let mut values = Vec::new();
+                for argument in arguments {
+                    let value = self.evaluate(argument)?;
+                    values.push(self.resolve(value));
+                }
+                self.output += &interpolate(&format.node, &values);
+                if *newline {
+                    self.output.push('\n');
+                }
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn condition(&mut self, condition: &Spanned<Expr>) -> Result<bool, Error> {
+        match self.evaluate(condition).map(|value| self.resolve(value))? {
+            Value::Bool(value) => Ok(value),
+            value => Err(Error::new(
+                ErrorKind::NotABool(value.type_name()),
+                condition.span,
+            )),
+        }
+    }
+
+    // Counts an iteration of the loop `statement`
+    fn iterate(&mut self, statement: &Spanned<Stmt>) -> Result<(), Error> {
+        self.iterations += 1;
+        if self.iterations > MAX_ITERATIONS {
+            return Err(Error::new(
+                ErrorKind::TooManyIterations(MAX_ITERATIONS),
+                statement.span,
+            ));
+        }
+        Ok(())
+    }
+
+    // The scope and the slot of the binding `name` refers to
+    fn locate(&self, name: &str) -> (usize, usize) {
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(index, scope)| {
+                let slot = scope.iter().rposition(|(binding, _)| binding == name)?;
+                Some((index, slot))
+            })
+            .expect("the checker lets only bindings in scope through")
+    }
+
+    fn lookup(&self, name: &str) -> Value {
+        let (scope, slot) = self.locate(name);
+        self.scopes[scope][slot]
+            .1
+            .clone()
+            .expect("the checker lets only initialized bindings through")
+    }
+
+    // Follows references down to the value they refer to
+    fn resolve(&self, value: Value) -> Value {
+        match value {
+            Value::Ref(scope, slot) => {
+                let referent = self.scopes[scope][slot].1.clone().unwrap_or(Value::Unit);
+                self.resolve(referent)
+            }
+            value => value,
+        }
+    }
+
+    fn evaluate(&mut self, expr: &Spanned<Expr>) -> Result<Value, Error> {
+        let error = |kind| Error {
+            kind,
+            span: expr.span,
+        };
+        match &expr.node {
+            Expr::Int(value) => Ok(Value::Int(*value)),
+            Expr::Str(text) => Ok(Value::Str(text.clone())),
+            Expr::Bool(value) => Ok(Value::Bool(*value)),
+            Expr::Variable(name) => Ok(self.lookup(name)),
+            Expr::Borrow { name, .. } => {
+                let (scope, slot) = self.locate(&name.node);
+                Ok(Value::Ref(scope, slot))
+            }
+            Expr::Deref(name) => match self.lookup(&name.node) {
+                Value::Ref(scope, slot) => {
+                    Ok(self.scopes[scope][slot].1.clone().unwrap_or(Value::Unit))
+                }
+                value => Err(error(ErrorKind::UnsupportedOperation(
+                    "*".to_owned(),
+                    value.type_name(),
+                    None,
+                ))),
+            },
+            Expr::Call {
+                function,
+                arguments,
+            } => {
+                let argument = match arguments.first() {
+                    Some(argument) => self.evaluate(argument)?,
+                    None => Value::Unit,
+                };
+                match function.node.as_str() {
+                    "String::from" => match self.resolve(argument) {
+                        Value::Str(text) => Ok(Value::Str(text)),
+                        value => Err(error(ErrorKind::UnsupportedOperation(
+                            "(".to_owned(),
+                            value.type_name(),
+                            None,
+                        ))),
+                    },
+                    // The checker made sure the value isn't used anymore
+                    _ => Ok(Value::Unit),
+                }
+            }
+            Expr::Negate(operand) => {
+                match self.evaluate(operand).map(|value| self.resolve(value))? {
+                    Value::Int(value) => value
+                        .checked_neg()
+                        .map(Value::Int)
+                        .ok_or_else(|| error(ErrorKind::Overflow)),
+                    value => Err(error(ErrorKind::UnsupportedOperation(
+                        "-".to_owned(),
+                        value.type_name(),
+                        None,
+                    ))),
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                binary(*op, self.resolve(left), self.resolve(right)).map_err(error)
+            }
+            Expr::Compare(op, left, right) => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                compare(op, self.resolve(left), self.resolve(right)).map_err(error)
+            }
+        }
+    }
+}
+
+fn binary(op: char, left: Value, right: Value) -> Result<Value, ErrorKind> {
+    let (a, b) = match (left, right) {
+        (Value::Int(a), Value::Int(b)) => (a, b),
+        // `String + &str`, in Rust
+        (Value::Str(a), Value::Str(b)) if op == '+' => return Ok(Value::Str(a + &b)),
+        (left, right) => {
+            return Err(ErrorKind::UnsupportedOperation(
+                op.to_string(),
+                left.type_name(),
+                Some(right.type_name()),
+            ))
+        }
+    };
+    if b == 0 && (op == '/' || op == '%') {
+        return Err(ErrorKind::DivisionByZero);
+    }
+    let result = match op {
+        '+' => a.checked_add(b),
+        '-' => a.checked_sub(b),
+        '*' => a.checked_mul(b),
+        '/' => a.checked_div(b),
+        _ => a.checked_rem(b),
+    };
+    result.map(Value::Int).ok_or(ErrorKind::Overflow)
+}
+
+fn compare(op: &str, left: Value, right: Value) -> Result<Value, ErrorKind> {
+    let ordering = match (&left, &right) {
+        (Value::Int(a), Value::Int(b)) => a.cmp(b),
+        (Value::Str(a), Value::Str(b)) => a.cmp(b),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => {
+            return Err(ErrorKind::UnsupportedOperation(
+                op.to_owned(),
+                left.type_name(),
+                Some(right.type_name()),
+            ))
+        }
+    };
+    let result = match op {
+        "==" => ordering.is_eq(),
+        "!=" => ordering.is_ne(),
+        "<" => ordering.is_lt(),
+        "<=" => ordering.is_le(),
+        ">" => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    };
+    Ok(Value::Bool(result))
+}
+
+// `format` with its placeholders replaced by `values`, `{:?}` quoting strings
+fn interpolate(format: &str, values: &[Value]) -> String {
+    let mut out = String::new();
+    let mut values = values.iter();
+    let mut rest = format;
+    while let Some(index) = rest.find(['{', '}']) {
+        out += &rest[..index];
+        rest = &rest[index..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            out += &rest[..1];
+            rest = &rest[2..];
+            continue;
+        }
+        let end = rest.find('}').map_or(rest.len(), |end| end + 1);
+        match values.next() {
+            Some(Value::Str(text)) if rest[..end].contains('?') => out += &format!("{:?}", text),
+            Some(value) => out += &value.to_string(),
+            None => {}
+        }
+        rest = &rest[end..];
+    }
+    out + rest
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "an integer",
+            Value::Str(_) => "a string",
+            Value::Bool(_) => "a `bool`",
+            Value::Ref(..) => "a reference",
+            Value::Unit => "`()`",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Str(text) => write!(f, "{}", text),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Ref(..) => write!(f, "&_"),
+            Value::Unit => write!(f, "()"),
+        }
+    }
+}
@@ -0,0 +1,616 @@
+use std::fmt;
+
+use super::{Error, ErrorKind};
+use crate::source::{self, LexError, Lexeme, Span, Spanned};
+
+// The statements of the variable bindings example:
+//
+//     program    = statement*
+//     statement  = "let" "mut"? name ("=" expression)? ";"
+//                | "*"? name ("=" | "+=" | "-=" | "*=" | "/=" | "%=") expression ";"
+//                | "{" statement* "}"
+//                | "if" expression block ("else" ("if" ... | block))?
+//                | "while" expression block
+//                | "loop" block
+//                | "break" ";" | "return" ";"
+//                | ("println" | "print") "!" "(" string ("," expression)* ")" ";"
+//                | call ";"
+//     expression = sum (("==" | "!=" | "<" | "<=" | ">" | ">=") sum)?
+//     sum        = term (("+" | "-") term)*
+//     term       = unary (("*" | "/" | "%") unary)*
+//     unary      = "-" unary | "*" name | "&" "mut"? name | primary
+//     primary    = integer | string | "true" | "false" | call | name | "(" expression ")"
+//     call       = name ("::" name)? "(" (expression ("," expression)*)? ")"
+//
+// Integers may have a type suffix, `7i32`, which is checked and dropped.
+// `//` starts a comment. The functions are `String::from` and `drop`.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Spanned<Stmt>>,
+    // The closing `}`, or the end of the program
+    pub close: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Let {
+        name: Spanned<String>,
+        mutable: bool,
+        value: Option<Spanned<Expr>>,
+    },
+    // `op` is the `+` of `+=`, `None` for a plain `=`. With `deref`, it is
+    // `*name` that is assigned, `name` being a reference.
+    Assign {
+        name: Spanned<String>,
+        deref: bool,
+        op: Option<char>,
+        value: Spanned<Expr>,
+    },
+    Block(Block),
+    // An `else if` is an `else` block holding the `if`
+    If {
+        condition: Spanned<Expr>,
+        then: Block,
+        otherwise: Option<Block>,
+    },
+    While {
+        condition: Spanned<Expr>,
+        body: Block,
+    },
+    Loop(Block),
+    Break,
+    Return,
+    Print {
+        format: Spanned<String>,
+        arguments: Vec<Spanned<Expr>>,
+        newline: bool,
+    },
+    // A call, for its effect
+    Expr(Spanned<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Int(i64),
+    Str(String),
+    Bool(bool),
+    Variable(String),
+    Negate(Box<Spanned<Expr>>),
+    Binary(char, Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    // `==`, `<=`...
+    Compare(&'static str, Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    // `&name` or `&mut name`
+    Borrow {
+        mutable: bool,
+        name: Spanned<String>,
+    },
+    // `*name`
+    Deref(Spanned<String>),
+    Call {
+        function: Spanned<String>,
+        arguments: Vec<Spanned<Expr>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Str(String),
+    Identifier(String),
+    Symbol(&'static str),
+}
+
+// Longest first, for `+=` not to be read as `+` then `=`
+const SYMBOLS: [&str; 29] = [
+    "+=", "-=", "*=", "/=", "%=", "==", "!=", "<=", ">=", "&&", "||", "::", "{", "}", "(", ")",
+    ";", ",", "=", "+", "-", "*", "/", "%", "!", "&", ".", "<", ">",
+];
+
+const COMPARISONS: [&str; 6] = ["==", "!=", "<", "<=", ">", ">="];
+
+const SUFFIXES: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Spanned<Token>>, Error> {
+    let length = |rest: &str| {
+        rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len())
+    };
+    let lexemes = source::lex(source, &SYMBOLS, length).map_err(|error| {
+        let kind = match error.node {
+            LexError::UnexpectedCharacter(c) => ErrorKind::UnexpectedCharacter(c),
+            LexError::UnterminatedString => ErrorKind::UnterminatedString,
+        };
+        Error::new(kind, error.span)
+    })?;
+
+    let mut tokens = Vec::new();
+    for Spanned { node, span } in lexemes {
+        let token = match node {
+            Lexeme::Number => {
+                let text = &source[span.start..span.end];
+                let (digits, suffix) =
+                    text.split_at(text.find(char::is_alphabetic).unwrap_or(text.len()));
+                match digits.replace('_', "").parse() {
+                    Ok(value) if suffix.is_empty() || SUFFIXES.contains(&suffix) => {
+                        Token::Int(value)
+                    }
+                    _ => {
+                        let kind = ErrorKind::InvalidNumber(text.to_owned());
+                        return Err(Error::new(kind, span));
+                    }
+                }
+            }
+            Lexeme::Identifier(name) => Token::Identifier(name),
+            Lexeme::Str(text) => Token::Str(text),
+            Lexeme::Symbol(symbol) => Token::Symbol(symbol),
+        };
+        tokens.push(Spanned { node: token, span });
+    }
+    Ok(tokens)
+}
+
+pub fn parse(source: &str) -> Result<Program, Error> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        end: source.len(),
+    };
+    let mut statements = Vec::new();
+    while parser.peek().is_some() {
+        statements.push(parser.statement()?);
+    }
+    Ok(Program {
+        body: Block {
+            statements,
+            close: Span {
+                start: source.len(),
+                end: source.len(),
+            },
+        },
+    })
+}
+
+struct Parser<'a> {
+    tokens: &'a [Spanned<Token>],
+    position: usize,
+    // Where errors about a missing token point at
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Spanned<Token>> {
+        self.tokens.get(self.position)
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Spanned { node: Token::Symbol(s), .. }) if *s == symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Spanned { node: Token::Identifier(name), .. }) if name == keyword)
+    }
+
+    // The span of the token before the current one
+    fn previous(&self) -> Span {
+        self.tokens[self.position - 1].span
+    }
+
+    fn expect_symbol(&mut self, symbol: &'static str) -> Result<Span, Error> {
+        if self.is_symbol(symbol) {
+            self.position += 1;
+            return Ok(self.previous());
+        }
+        Err(self.unexpected(match symbol {
+            ";" => "`;`",
+            "(" => "`(`",
+            ")" => "`)`",
+            "!" => "`!`",
+            "=" => "`=`",
+            "," => "`,`",
+            "{" => "`{`",
+            _ => "a symbol",
+        }))
+    }
+
+    fn identifier(&mut self) -> Result<Spanned<String>, Error> {
+        match self.peek() {
+            Some(Spanned {
+                node: Token::Identifier(name),
+                span,
+            }) => {
+                self.position += 1;
+                Ok(Spanned {
+                    node: name.clone(),
+                    span: *span,
+                })
+            }
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> Error {
+        match self.peek() {
+            Some(token) => Error::new(
+                ErrorKind::UnexpectedToken(token.node.to_string(), expected),
+                token.span,
+            ),
+            None => Error::new(
+                ErrorKind::UnexpectedEnd(expected),
+                Span {
+                    start: self.end,
+                    end: self.end + 1,
+                },
+            ),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Spanned<Stmt>, Error> {
+        let start = match self.peek() {
+            Some(token) => token.span.start,
+            None => return Err(self.unexpected("a statement")),
+        };
+
+        let stmt = if self.is_symbol("{") {
+            self.position += 1;
+            Stmt::Block(self.block()?)
+        } else if self.is_keyword("if") {
+            self.if_else()?
+        } else if self.is_keyword("while") {
+            self.position += 1;
+            let condition = self.expression()?;
+            self.expect_symbol("{")?;
+            Stmt::While {
+                condition,
+                body: self.block()?,
+            }
+        } else if self.is_keyword("loop") {
+            self.position += 1;
+            self.expect_symbol("{")?;
+            Stmt::Loop(self.block()?)
+        } else if self.is_keyword("break") || self.is_keyword("return") {
+            let stmt = if self.is_keyword("break") {
+                Stmt::Break
+            } else {
+                Stmt::Return
+            };
+            self.position += 1;
+            stmt
+        } else if self.is_keyword("let") {
+            self.position += 1;
+            let mutable = self.is_keyword("mut");
+            if mutable {
+                self.position += 1;
+            }
+            let name = self.identifier()?;
+            let value = if self.is_symbol("=") {
+                self.position += 1;
+                Some(self.expression()?)
+            } else {
+                None
+            };
+            Stmt::Let {
+                name,
+                mutable,
+                value,
+            }
+        } else if self.is_keyword("println") || self.is_keyword("print") {
+            let newline = self.is_keyword("println");
+            self.position += 1;
+            self.expect_symbol("!")?;
+            self.expect_symbol("(")?;
+            let format = match self.peek() {
+                Some(Spanned {
+                    node: Token::Str(text),
+                    span,
+                }) => {
+                    self.position += 1;
+                    Spanned {
+                        node: text.clone(),
+                        span: *span,
+                    }
+                }
+                _ => return Err(self.unexpected("a format string")),
+            };
+            let mut arguments = Vec::new();
+            while self.is_symbol(",") {
+                self.position += 1;
+                arguments.push(self.expression()?);
+            }
+            self.expect_symbol(")")?;
+            Stmt::Print {
+                format,
+                arguments,
+                newline,
+            }
+        } else if self.is_call() {
+            Stmt::Expr(self.call()?)
+        } else {
+            let deref = self.is_symbol("*");
+            if deref {
+                self.position += 1;
+            }
+            let name = self.identifier()?;
+            let op = match self.peek() {
+                Some(Spanned {
+                    node: Token::Symbol(symbol),
+                    ..
+                }) if symbol.len() == 2 && symbol.ends_with('=') && *symbol != "==" => {
+                    symbol.chars().next()
+                }
+                _ => None,
+            };
+            if op.is_none() && !self.is_symbol("=") {
+                return Err(self.unexpected("`=` or a compound assignment"));
+            }
+            self.position += 1;
+            Stmt::Assign {
+                name,
+                deref,
+                op,
+                value: self.expression()?,
+            }
+        };
+
+        // Blocks end with their `}`, the other statements with a `;` left out
+        // of their span
+        let span = Span {
+            start,
+            end: self.previous().end,
+        };
+        if !matches!(
+            stmt,
+            Stmt::Block(_) | Stmt::If { .. } | Stmt::While { .. } | Stmt::Loop(_)
+        ) {
+            self.expect_symbol(";")?;
+        }
+        Ok(Spanned { node: stmt, span })
+    }
+
+    // An `if`, its `else` and the `if`s chained to it
+    fn if_else(&mut self) -> Result<Stmt, Error> {
+        self.position += 1;
+        let condition = self.expression()?;
+        self.expect_symbol("{")?;
+        let then = self.block()?;
+        if !self.is_keyword("else") {
+            return Ok(Stmt::If {
+                condition,
+                then,
+                otherwise: None,
+            });
+        }
+        self.position += 1;
+        let otherwise = if self.is_keyword("if") {
+            let start = self.peek().map_or(self.end, |token| token.span.start);
+            let stmt = self.if_else()?;
+            let span = Span {
+                start,
+                end: self.previous().end,
+            };
+            Block {
+                statements: vec![Spanned { node: stmt, span }],
+                close: self.previous(),
+            }
+        } else {
+            self.expect_symbol("{")?;
+            self.block()?
+        };
+        Ok(Stmt::If {
+            condition,
+            then,
+            otherwise: Some(otherwise),
+        })
+    }
+
+    // A name followed by `(` or `::`
+    fn is_call(&self) -> bool {
+        let next = self.tokens.get(self.position + 1).map(|token| &token.node);
+        matches!(
+            self.peek(),
+            Some(Spanned {
+                node: Token::Identifier(_),
+                ..
+            })
+        ) && matches!(next, Some(Token::Symbol("(")) | Some(Token::Symbol("::")))
+    }
+
+    fn call(&mut self) -> Result<Spanned<Expr>, Error> {
+        let mut function = self.identifier()?;
+        if self.is_symbol("::") {
+            self.position += 1;
+            let name = self.identifier()?;
+            function = Spanned {
+                node: format!("{}::{}", function.node, name.node),
+                span: Span {
+                    start: function.span.start,
+                    end: name.span.end,
+                },
+            };
+        }
+        self.expect_symbol("(")?;
+        let mut arguments = Vec::new();
+        while !self.is_symbol(")") {
+            if !arguments.is_empty() {
+                self.expect_symbol(",")?;
+            }
+            arguments.push(self.expression()?);
+        }
+        let close = self.expect_symbol(")")?;
+        Ok(Spanned {
+            span: Span {
+                start: function.span.start,
+                end: close.end,
+            },
+            node: Expr::Call {
+                function,
+                arguments,
+            },
+        })
+    }
+
+    // The statements up to the `}`, the `{` being already read
+    fn block(&mut self) -> Result<Block, Error> {
+        let mut statements = Vec::new();
+        loop {
+            if self.is_symbol("}") {
+                self.position += 1;
+                return Ok(Block {
+                    statements,
+                    close: self.previous(),
+                });
+            }
+            if self.peek().is_none() {
+                return Err(self.unexpected("`}`"));
+            }
+            statements.push(self.statement()?);
+        }
+    }
+
+    fn expression(&mut self) -> Result<Spanned<Expr>, Error> {
+        let left = self.sum()?;
+        let op = match self.peek() {
+            Some(Spanned {
+                node: Token::Symbol(op),
+                ..
+            }) if COMPARISONS.contains(op) => *op,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.sum()?;
+        Ok(Spanned {
+            span: Span {
+                start: left.span.start,
+                end: right.span.end,
+            },
+            node: Expr::Compare(op, Box::new(left), Box::new(right)),
+        })
+    }
+
+    fn sum(&mut self) -> Result<Spanned<Expr>, Error> {
+        self.binary(&["+", "-"], Parser::term)
+    }
+
+    fn term(&mut self) -> Result<Spanned<Expr>, Error> {
+        self.binary(&["*", "/", "%"], Parser::unary)
+    }
+
+    fn binary(
+        &mut self,
+        ops: &[&str],
+        operand: fn(&mut Self) -> Result<Spanned<Expr>, Error>,
+    ) -> Result<Spanned<Expr>, Error> {
+        let mut lhs = operand(self)?;
+        while let Some(Spanned {
+            node: Token::Symbol(op),
+            ..
+        }) = self.peek()
+        {
+            if !ops.contains(op) {
+                break;
+            }
+            self.position += 1;
+            let rhs = operand(self)?;
+            let span = Span {
+                start: lhs.span.start,
+                end: rhs.span.end,
+            };
+            let op = op.chars().next().unwrap_or('+');
+            lhs = Spanned {
+                node: Expr::Binary(op, Box::new(lhs), Box::new(rhs)),
+                span,
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Spanned<Expr>, Error> {
+        if self.is_symbol("*") || self.is_symbol("&") {
+            let deref = self.is_symbol("*");
+            self.position += 1;
+            let start = self.previous().start;
+            let mutable = !deref && self.is_keyword("mut");
+            if mutable {
+                self.position += 1;
+            }
+            let name = self.identifier()?;
+            let span = Span {
+                start,
+                end: name.span.end,
+            };
+            let node = if deref {
+                Expr::Deref(name)
+            } else {
+                Expr::Borrow { mutable, name }
+            };
+            return Ok(Spanned { node, span });
+        }
+        if self.is_symbol("-") {
+            self.position += 1;
+            let start = self.previous().start;
+            let operand = self.unary()?;
+            return Ok(Spanned {
+                span: Span {
+                    start,
+                    end: operand.span.end,
+                },
+                node: Expr::Negate(Box::new(operand)),
+            });
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Spanned<Expr>, Error> {
+        const EXPECTED: &str = "an integer, a string, a name or `(`";
+        let token = match self.peek() {
+            Some(token) => token,
+            None => return Err(self.unexpected(EXPECTED)),
+        };
+        let node = match &token.node {
+            Token::Int(value) => Expr::Int(*value),
+            Token::Str(text) => Expr::Str(text.clone()),
+            Token::Identifier(name) if name == "true" || name == "false" => {
+                Expr::Bool(name == "true")
+            }
+            Token::Identifier(_) if self.is_call() => return self.call(),
+            Token::Identifier(name) => Expr::Variable(name.clone()),
+            Token::Symbol("(") => {
+                self.position += 1;
+                let inner = self.expression()?;
+                let close = self.expect_symbol(")")?;
+                return Ok(Spanned {
+                    node: inner.node,
+                    span: Span {
+                        start: token.span.start,
+                        end: close.end,
+                    },
+                });
+            }
+            Token::Symbol(_) => return Err(self.unexpected(EXPECTED)),
+        };
+        self.position += 1;
+        Ok(Spanned {
+            node,
+            span: token.span,
+        })
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Int(value) => write!(f, "`{}`", value),
+            Token::Str(text) => write!(f, "`{:?}`", text),
+            Token::Identifier(name) => write!(f, "`{}`", name),
+            Token::Symbol(symbol) => write!(f, "`{}`", symbol),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use super::check::{self, Event, EventKind};
+use super::Program;
+use crate::source::line_of;
+
+// The life of every binding of a program, as a lane next to its source,
+// with what happens to them on each line:
+//
+//        1 2
+//     1 ┬     let x = 1;             x#1 introduced
+//     2 │     {
+//     3 ●         print!("{}", x);
+//     4 ┆ ┬       let x = "abc";     x#2 introduced, x#1 shadowed by #2
+//     5 │ ┴   }                      x#2 dropped, x#1 visible again
+//     6 ┴     (end)                  x#1 dropped
+//
+// `┬` introduces a binding, `●` reads it, `○` assigns it and `┴` drops it.
+// A binding is `│` while visible and `┆` while shadowed.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Unborn,
+    Visible,
+    Shadowed,
+    Dropped,
+}
+
+pub fn draw(source: &str, program: &Program) -> String {
+    let (errors, mut events) = check::walk(program);
+    // Reads in a multi-line `let` come before the binding it introduces
+    events.sort_by_key(|event| line_of(source, event.at.start).0);
+    let names: Vec<&str> = events
+        .iter()
+        .filter_map(|event| match &event.kind {
+            EventKind::Introduced { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    // `x#2` for the second binding named `x`, plain `x` for a name bound once
+    let labels: Vec<String> = names
+        .iter()
+        .enumerate()
+        .map(|(id, name)| {
+            let same: Vec<usize> = (0..names.len())
+                .filter(|&other| names[other] == *name)
+                .collect();
+            match same.iter().position(|&other| other == id) {
+                Some(index) if same.len() > 1 => format!("{}#{}", name, index + 1),
+                _ => name.to_string(),
+            }
+        })
+        .collect();
+
+    let mut lines: Vec<&str> = source.lines().collect();
+    let last = events
+        .iter()
+        .map(|event| line_of(source, event.at.start).0)
+        .max()
+        .unwrap_or(0);
+    // The drops at the end of the program, after its last line
+    while lines.len() <= last {
+        lines.push("");
+    }
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let gutter = lines.len().to_string().len();
+
+    let mut out = format!("{} ", " ".repeat(gutter));
+    for id in 0..names.len() {
+        out += &format!(" {}", lane(id));
+    }
+    out.push('\n');
+
+    let mut states = vec![State::Unborn; names.len()];
+    let mut events = events.iter().peekable();
+    for (index, line) in lines.iter().enumerate() {
+        let mut glyphs: Vec<Option<char>> = vec![None; names.len()];
+        let mut notes = Vec::new();
+        while let Some(Event { binding, kind, .. }) =
+            events.next_if(|event| line_of(source, event.at.start).0 == index)
+        {
+            let (glyph, note) = apply(&mut states[*binding], kind, *binding, &labels);
+            glyphs[*binding] = match (glyphs[*binding], glyph) {
+                // Introduced and dropped on the same line
+                (Some('┬'), Some('┴')) => Some('×'),
+                (Some('┬'), _) | (Some('┴'), _) => glyphs[*binding],
+                (previous, glyph) => glyph.or(previous),
+            };
+            notes.extend(note);
+        }
+        for error in &errors {
+            if line_of(source, error.span.start).0 == index {
+                notes.push(match error.code() {
+                    Some(code) => format!("error[{}]: {}", code, error),
+                    None => format!("error: {}", error),
+                });
+            }
+        }
+
+        let lanes: String = glyphs
+            .iter()
+            .zip(&states)
+            .map(|(glyph, state)| {
+                let glyph = glyph.unwrap_or(match state {
+                    State::Visible => '│',
+                    State::Shadowed => '┆',
+                    State::Unborn | State::Dropped => ' ',
+                });
+                format!(" {}", glyph)
+            })
+            .collect();
+        let text = if line.is_empty() && index >= source.lines().count() {
+            "(end)"
+        } else {
+            line
+        };
+        let row = format!(
+            "{:>gutter$}{}   {:<width$}   {}",
+            index + 1,
+            lanes,
+            text,
+            notes.join(", "),
+            gutter = gutter,
+            width = width
+        );
+        out += row.trim_end();
+        out.push('\n');
+    }
+    out
+}
+
+// Updates the state of a binding for an event, returning its glyph and what
+// to say about it
+fn apply(
+    state: &mut State,
+    kind: &EventKind,
+    binding: usize,
+    labels: &[String],
+) -> (Option<char>, Option<String>) {
+    let label = &labels[binding];
+    match kind {
+        EventKind::Introduced { mutable, .. } => {
+            *state = State::Visible;
+            let mutable = if *mutable { " (mut)" } else { "" };
+            (Some('┬'), Some(format!("{} introduced{}", label, mutable)))
+        }
+        // The shadow has the same name, its `#n` is enough
+        EventKind::Shadowed { by } => {
+            *state = State::Shadowed;
+            (
+                None,
+                Some(format!("{} shadowed by {}", label, number(&labels[*by]))),
+            )
+        }
+        EventKind::Frozen { by } => {
+            *state = State::Shadowed;
+            (
+                None,
+                Some(format!("{} frozen by {}", label, number(&labels[*by]))),
+            )
+        }
+        EventKind::Unshadowed => {
+            *state = State::Visible;
+            (None, Some(format!("{} visible again", label)))
+        }
+        EventKind::Unfrozen => {
+            *state = State::Visible;
+            (None, Some(format!("{} mutable again", label)))
+        }
+        EventKind::Read => (Some('●'), None),
+        EventKind::Assigned => (Some('○'), Some(format!("{} assigned", label))),
+        EventKind::Dropped => {
+            *state = State::Dropped;
+            (Some('┴'), Some(format!("{} dropped", label)))
+        }
+    }
+}
+
+fn number(label: &str) -> &str {
+    label.rfind('#').map_or(label, |index| &label[index..])
+}
+
+// The column header of a binding's lane
+fn lane(id: usize) -> char {
+    const LANES: &str = "123456789abcdefghijklmnopqrstuvwxyz";
+    LANES.chars().nth(id).unwrap_or('+')
+}
@@ -0,0 +1,539 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+
+use crate::source::{Span, Spanned};
+
+// Evaluates arithmetic expressions with integers, floats and variables:
+//
+//     let mut calculator = Calculator::new();
+//     calculator.evaluate("width = 1_000")?;
+//     calculator.evaluate("-(width + 5) * 2.5")?; // Float(-2512.5)
+//
+// Integers are `i64`, floats `f64`, and mixing them gives a float, like an
+// `as f64` on the integer side. Integer arithmetic is checked: overflows and
+// divisions by zero are errors, not wrapped values or panics. Floats follow
+// IEEE 754, so `1.0 / 0` is infinity.
+//
+// Errors carry the span of the input they are about, `Error::render` puts a
+// caret under it.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnexpectedCharacter(char),
+    InvalidNumber(String),
+    // What was found, and what was expected instead
+    UnexpectedToken(String, &'static str),
+    UnexpectedEnd(&'static str),
+    UnknownVariable(String),
+    // The operator, and its operands
+    Overflow(char, i64, i64),
+    NegationOverflow(i64),
+    DivisionByZero,
+}
+
+#[derive(Debug, Default)]
+pub struct Calculator {
+    variables: HashMap<String, Number>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Float(f64),
+    Identifier(String),
+    // `+ - * / % ( ) =`
+    Symbol(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(Number),
+    Variable(String),
+    Negate(Box<Spanned<Expr>>),
+    // The operator's span is the one of the `Spanned` holding the `Binary`
+    Binary(char, Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+}
+
+impl Calculator {
+    pub fn new() -> Calculator {
+        Calculator::default()
+    }
+
+    // Evaluate an expression, or assign one to a variable with `name = ...`,
+    // in which case the assigned value is returned.
+    pub fn evaluate(&mut self, line: &str) -> Result<Number, Error> {
+        let tokens = tokenize(line)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+            end: line.len(),
+        };
+
+        let assigned = match tokens.as_slice() {
+            [Spanned {
+                node: Token::Identifier(name),
+                ..
+            }, Spanned {
+                node: Token::Symbol('='),
+                ..
+            }, ..] => {
+                parser.position = 2;
+                Some(name.clone())
+            }
+            _ => None,
+        };
+
+        let expr = parser.expression()?;
+        if let Some(token) = parser.tokens.get(parser.position) {
+            return Err(unexpected(token, "an operator"));
+        }
+        let value = self.eval(&expr)?;
+        if let Some(name) = assigned {
+            self.variables.insert(name, value);
+        }
+        Ok(value)
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = (&str, Number)> {
+        self.variables
+            .iter()
+            .map(|(name, &value)| (name.as_str(), value))
+    }
+
+    fn eval(&self, expr: &Spanned<Expr>) -> Result<Number, Error> {
+        match &expr.node {
+            Expr::Number(number) => Ok(*number),
+            Expr::Variable(name) => self.variables.get(name).copied().ok_or_else(|| Error {
+                kind: ErrorKind::UnknownVariable(name.clone()),
+                span: expr.span,
+            }),
+            Expr::Negate(operand) => match self.eval(operand)? {
+                Number::Int(value) => value.checked_neg().map(Number::Int).ok_or(Error {
+                    kind: ErrorKind::NegationOverflow(value),
+                    span: expr.span,
+                }),
+                Number::Float(value) => Ok(Number::Float(-value)),
+            },
+            Expr::Binary(op, lhs, rhs) => {
+                let (left, right) = (self.eval(lhs)?, self.eval(rhs)?);
+                binary(*op, left, right).map_err(|kind| Error {
+                    span: match kind {
+                        ErrorKind::DivisionByZero => rhs.span,
+                        _ => expr.span,
+                    },
+                    kind,
+                })
+            }
+        }
+    }
+}
+
+fn binary(op: char, left: Number, right: Number) -> Result<Number, ErrorKind> {
+    let (a, b) = match (left, right) {
+        (Number::Int(a), Number::Int(b)) => {
+            if b == 0 && (op == '/' || op == '%') {
+                return Err(ErrorKind::DivisionByZero);
+            }
+            let result = match op {
+                '+' => a.checked_add(b),
+                '-' => a.checked_sub(b),
+                '*' => a.checked_mul(b),
+                '/' => a.checked_div(b),
+                _ => a.checked_rem(b),
+            };
+            return result.map(Number::Int).ok_or(ErrorKind::Overflow(op, a, b));
+        }
+        (a, b) => (a.as_f64(), b.as_f64()),
+    };
+    Ok(Number::Float(match op {
+        '+' => a + b,
+        '-' => a - b,
+        '*' => a * b,
+        '/' => a / b,
+        _ => a % b,
+    }))
+}
+
+fn tokenize(line: &str) -> Result<Vec<Spanned<Token>>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut end = start + c.len_utf8();
+        let token = if c.is_ascii_digit() || c == '.' {
+            // Digits, `_` separators, a fraction and an exponent: anything a
+            // Rust float literal may contain, validated by `parse` below
+            let mut previous = c;
+            chars.next();
+            while let Some(&(index, c)) = chars.peek() {
+                let exponent_sign = (c == '+' || c == '-') && (previous == 'e' || previous == 'E');
+                if !(c.is_ascii_alphanumeric() || c == '_' || c == '.' || exponent_sign) {
+                    break;
+                }
+                previous = c;
+                end = index + c.len_utf8();
+                chars.next();
+            }
+            number(&line[start..end]).ok_or_else(|| Error {
+                kind: ErrorKind::InvalidNumber(line[start..end].to_owned()),
+                span: Span { start, end },
+            })?
+        } else if c.is_alphabetic() || c == '_' {
+            chars.next();
+            while let Some(&(index, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = index + c.len_utf8();
+                chars.next();
+            }
+            Token::Identifier(line[start..end].to_owned())
+        } else if "+-*/%()=".contains(c) {
+            chars.next();
+            Token::Symbol(c)
+        } else {
+            return Err(Error {
+                kind: ErrorKind::UnexpectedCharacter(c),
+                span: Span { start, end },
+            });
+        };
+        tokens.push(Spanned {
+            node: token,
+            span: Span { start, end },
+        });
+    }
+    Ok(tokens)
+}
+
+fn number(text: &str) -> Option<Token> {
+    let digits = text.replace('_', "");
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    if digits.contains(['.', 'e', 'E']) {
+        digits.parse().ok().map(Token::Float)
+    } else {
+        digits.parse().ok().map(Token::Int)
+    }
+}
+
+// Recursive descent, one method per precedence level:
+//
+//     expression = term (("+" | "-") term)*
+//     term       = unary (("*" | "/" | "%") unary)*
+//     unary      = "-" unary | primary
+//     primary    = number | variable | "(" expression ")"
+struct Parser<'a> {
+    tokens: &'a [Spanned<Token>],
+    position: usize,
+    // Where errors about a missing token point at
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn expression(&mut self) -> Result<Spanned<Expr>, Error> {
+        self.binary(&['+', '-'], Parser::term)
+    }
+
+    fn term(&mut self) -> Result<Spanned<Expr>, Error> {
+        self.binary(&['*', '/', '%'], Parser::unary)
+    }
+
+    // Left-associative operators among `ops`, between operands parsed by
+    // `operand`
+    fn binary(
+        &mut self,
+        ops: &[char],
+        operand: fn(&mut Self) -> Result<Spanned<Expr>, Error>,
+    ) -> Result<Spanned<Expr>, Error> {
+        let mut lhs = operand(self)?;
+        while let Some(Spanned {
+            node: Token::Symbol(op),
+            span,
+        }) = self.tokens.get(self.position)
+        {
+            if !ops.contains(op) {
+                break;
+            }
+            self.position += 1;
+            let rhs = operand(self)?;
+            lhs = Spanned {
+                node: Expr::Binary(*op, Box::new(lhs), Box::new(rhs)),
+                span: *span,
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Spanned<Expr>, Error> {
+        if let Some(Spanned {
+            node: Token::Symbol('-'),
+            span,
+        }) = self.tokens.get(self.position)
+        {
+            self.position += 1;
+            let operand = self.unary()?;
+            return Ok(Spanned {
+                span: Span {
+                    start: span.start,
+                    end: operand.span.end.max(span.end),
+                },
+                node: Expr::Negate(Box::new(operand)),
+            });
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Spanned<Expr>, Error> {
+        const EXPECTED: &str = "a number, a variable or `(`";
+        let token = match self.tokens.get(self.position) {
+            Some(token) => token,
+            None => return Err(self.end_error(EXPECTED)),
+        };
+        self.position += 1;
+
+        let node = match &token.node {
+            Token::Int(value) => Expr::Number(Number::Int(*value)),
+            Token::Float(value) => Expr::Number(Number::Float(*value)),
+            Token::Identifier(name) => Expr::Variable(name.clone()),
+            Token::Symbol('(') => {
+                let inner = self.expression()?;
+                return match self.tokens.get(self.position) {
+                    Some(Spanned {
+                        node: Token::Symbol(')'),
+                        span,
+                    }) => {
+                        self.position += 1;
+                        Ok(Spanned {
+                            node: inner.node,
+                            span: Span {
+                                start: token.span.start,
+                                end: span.end,
+                            },
+                        })
+                    }
+                    Some(other) => Err(unexpected(other, "`)`")),
+                    None => Err(self.end_error("`)`")),
+                };
+            }
+            Token::Symbol(_) => return Err(unexpected(token, EXPECTED)),
+        };
+        Ok(Spanned {
+            node,
+            span: token.span,
+        })
+    }
+
+    fn end_error(&self, expected: &'static str) -> Error {
+        Error {
+            kind: ErrorKind::UnexpectedEnd(expected),
+            span: Span {
+                start: self.end,
+                end: self.end + 1,
+            },
+        }
+    }
+}
+
+fn unexpected(token: &Spanned<Token>, expected: &'static str) -> Error {
+    Error {
+        kind: ErrorKind::UnexpectedToken(token.node.to_string(), expected),
+        span: token.span,
+    }
+}
+
+impl Number {
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(value) => value as f64,
+            Number::Float(value) => value,
+        }
+    }
+}
+
+impl Error {
+    // The input, with a caret under the part the error is about, and the
+    // message:
+    //
+    //     1 + (2 * 3
+    //               ^ expected `)`, found the end of the input
+    pub fn render(&self, input: &str) -> String {
+        format!("{}\n{}", input, self.caret(input, 0))
+    }
+
+    // The caret line alone, for inputs that are already on screen after a
+    // prompt of `indent` characters
+    pub fn caret(&self, input: &str, indent: usize) -> String {
+        let start = input[..self.span.start.min(input.len())].chars().count();
+        let width = input
+            .get(self.span.start..self.span.end)
+            .map_or(1, |text| text.chars().count().max(1));
+        format!(
+            "{}{} {}",
+            " ".repeat(indent + start),
+            "^".repeat(width),
+            self
+        )
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Number::Int(value) => write!(f, "{}", value),
+            // Always with a decimal point, to tell floats from integers
+            Number::Float(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Int(value) => write!(f, "`{}`", value),
+            Token::Float(value) => write!(f, "`{:?}`", value),
+            Token::Identifier(name) => write!(f, "`{}`", name),
+            Token::Symbol(c) => write!(f, "`{}`", c),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character `{}`", c),
+            ErrorKind::InvalidNumber(text) => write!(f, "invalid number `{}`", text),
+            ErrorKind::UnexpectedToken(found, expected) => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            ErrorKind::UnexpectedEnd(expected) => {
+                write!(f, "expected {}, found the end of the input", expected)
+            }
+            ErrorKind::UnknownVariable(name) => write!(f, "unknown variable `{}`", name),
+            ErrorKind::NegationOverflow(value) => {
+                write!(f, "attempt to negate `{}` with overflow", value)
+            }
+            ErrorKind::Overflow(op, a, b) => {
+                write!(f, "`{} {} {}` overflows an i64", a, op, b)
+            }
+            ErrorKind::DivisionByZero => write!(f, "attempt to divide by zero"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(line: &str) -> Result<Number, Error> {
+        Calculator::new().evaluate(line)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(Number::Int(7)));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(Number::Int(9)));
+        assert_eq!(evaluate("10 - 4 - 3"), Ok(Number::Int(3)));
+        assert_eq!(evaluate("2 * 7 % 4"), Ok(Number::Int(2)));
+        assert_eq!(evaluate("-2 * -3"), Ok(Number::Int(6)));
+        assert_eq!(evaluate("--1_000"), Ok(Number::Int(1000)));
+        assert_eq!(evaluate("7 / 2"), Ok(Number::Int(3)));
+        assert_eq!(evaluate("7 / 2.0"), Ok(Number::Float(3.5)));
+        assert_eq!(evaluate("1.0 / 0"), Ok(Number::Float(f64::INFINITY)));
+    }
+
+    #[test]
+    fn variables() {
+        let mut calculator = Calculator::new();
+        assert_eq!(calculator.evaluate("width = 1_000"), Ok(Number::Int(1000)));
+        assert_eq!(
+            calculator.evaluate("-(width + 5) * 2.5"),
+            Ok(Number::Float(-2512.5))
+        );
+        let variables: Vec<_> = calculator.variables().collect();
+        assert_eq!(variables, vec![("width", Number::Int(1000))]);
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let error = evaluate("1 + 6 / (3 - 3)").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::DivisionByZero);
+        assert_eq!(
+            error.render("1 + 6 / (3 - 3)"),
+            "1 + 6 / (3 - 3)\n        ^^^^^^^ attempt to divide by zero"
+        );
+        assert_eq!(
+            evaluate("5 % 0").unwrap_err().span,
+            Span { start: 4, end: 5 }
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let error = evaluate("9223372036854775807 + 1").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Overflow('+', i64::MAX, 1));
+        assert_eq!(error.span, Span { start: 20, end: 21 });
+        assert_eq!(
+            error.to_string(),
+            "`9223372036854775807 + 1` overflows an i64"
+        );
+
+        let mut calculator = Calculator::new();
+        calculator
+            .evaluate("min = -9223372036854775807 - 1")
+            .unwrap();
+        let error = calculator.evaluate("-min").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::NegationOverflow(i64::MIN));
+        assert_eq!(error.span, Span { start: 0, end: 4 });
+        assert_eq!(
+            calculator.evaluate("min / -1").unwrap_err().kind,
+            ErrorKind::Overflow('/', i64::MIN, -1)
+        );
+        assert_eq!(
+            evaluate("99999999999999999999").unwrap_err().kind,
+            ErrorKind::InvalidNumber("99999999999999999999".to_owned())
+        );
+    }
+
+    #[test]
+    fn carets() {
+        let render = |input: &str| evaluate(input).unwrap_err().render(input);
+        assert_eq!(
+            render("1 + (2 * 3"),
+            "1 + (2 * 3\n          ^ expected `)`, found the end of the input"
+        );
+        assert_eq!(render("5 + ten"), "5 + ten\n    ^^^ unknown variable `ten`");
+        assert_eq!(render("2 $ 3"), "2 $ 3\n  ^ unexpected character `$`");
+        assert_eq!(render("1 2"), "1 2\n  ^ expected an operator, found `2`");
+        assert_eq!(
+            render("3 * * 4"),
+            "3 * * 4\n    ^ expected a number, a variable or `(`, found `*`"
+        );
+        assert_eq!(render("1.2.3"), "1.2.3\n^^^^^ invalid number `1.2.3`");
+        // After a prompt, with characters wider than a byte before the error
+        let error = evaluate("€").unwrap_err();
+        assert_eq!(error.caret("€", 2), "  ^ unexpected character `€`");
+        let error = evaluate("x = (€").unwrap_err();
+        assert_eq!(error.caret("x = (€", 0), "     ^ unexpected character `€`");
+    }
+}
@@ -0,0 +1,408 @@
+use std::fmt;
+
+use crate::shapes::{Outline, Point, Rectangle, Shape};
+
+// Draws points, lines and shapes on a grid of characters, to print them:
+//
+//     let mut canvas = Canvas::fitting(&bounds, 40, Resolution::Braille);
+//     canvas.line(Point::new(0.0, 0.0), Point::new(10.0, 5.0));
+//     canvas.shape(&Rectangle::square(Point::new(3.0, 3.0), 2.0));
+//     println!("{}", canvas);
+//
+// As for the shapes, y goes up. Anything outside the canvas is clipped.
+//
+// Terminal cells are about twice as tall as they are wide, so a pixel is a
+// whole cell with `Resolution::Cells`, and one of the 2 × 4 dots of a braille
+// character with `Resolution::Braille`, which gives square pixels either way
+// once `scale` is set: the width of a pixel in the shapes' units.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Cells,
+    Braille,
+}
+
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    columns: usize,
+    rows: usize,
+    resolution: Resolution,
+    scale: f64,
+    // Where the bottom left corner of the canvas is
+    origin: Point,
+    // Row by row, from the top
+    pixels: Vec<bool>,
+}
+
+impl Resolution {
+    // Pixels per cell, across and down
+    fn pixels(self) -> (usize, usize) {
+        match self {
+            Resolution::Cells => (1, 1),
+            Resolution::Braille => (2, 4),
+        }
+    }
+
+    // How much taller than wide a pixel is
+    fn aspect(self) -> f64 {
+        match self {
+            Resolution::Cells => 2.0,
+            Resolution::Braille => 1.0,
+        }
+    }
+}
+
+impl Canvas {
+    // With a pixel per unit and the origin in the bottom left corner
+    pub fn new(columns: usize, rows: usize, resolution: Resolution) -> Canvas {
+        let (across, down) = resolution.pixels();
+        Canvas {
+            columns,
+            rows,
+            resolution,
+            scale: 1.0,
+            origin: Point::new(0.0, 0.0),
+            pixels: vec![false; columns * across * rows * down],
+        }
+    }
+
+    // `columns` wide, and as many rows as `bounds` needs
+    pub fn fitting(bounds: &Rectangle, columns: usize, resolution: Resolution) -> Canvas {
+        let (across, down) = resolution.pixels();
+        let columns = columns.max(1);
+        // The right and top edges fall in the last pixels, not past them
+        let scale = bounds.width() / (columns * across - 1).max(1) as f64;
+        // A rectangle with no width still needs pixels of some size
+        let scale = if scale > 0.0 && scale.is_finite() {
+            scale
+        } else {
+            1.0
+        };
+        let pixel_height = scale * resolution.aspect();
+        let pixels = (bounds.height() / pixel_height).floor() as usize + 1;
+        let rows = pixels.div_ceil(down);
+        Canvas::new(columns, rows.max(1), resolution)
+            .scale(scale)
+            .origin(Point::new(bounds.top_left.x, bounds.bottom_right.y))
+    }
+
+    pub fn scale(mut self, scale: f64) -> Canvas {
+        self.scale = scale;
+        self
+    }
+
+    pub fn origin(mut self, origin: Point) -> Canvas {
+        self.origin = origin;
+        self
+    }
+
+    fn width(&self) -> i64 {
+        (self.columns * self.resolution.pixels().0) as i64
+    }
+
+    fn height(&self) -> i64 {
+        (self.rows * self.resolution.pixels().1) as i64
+    }
+
+    // Where `point` is, in pixels from the bottom left corner
+    fn position(&self, point: Point) -> (f64, f64) {
+        let pixel_height = self.scale * self.resolution.aspect();
+        (
+            (point.x - self.origin.x) / self.scale,
+            (point.y - self.origin.y) / pixel_height,
+        )
+    }
+
+    // The pixel `point` falls in, counting rows from the bottom
+    fn pixel(&self, point: Point) -> (i64, i64) {
+        let (x, y) = self.position(point);
+        (x.floor() as i64, y.floor() as i64)
+    }
+
+    // The center of a pixel, back in the shapes' units
+    fn center(&self, (x, y): (i64, i64)) -> Point {
+        let pixel_height = self.scale * self.resolution.aspect();
+        Point::new(
+            self.origin.x + (x as f64 + 0.5) * self.scale,
+            self.origin.y + (y as f64 + 0.5) * pixel_height,
+        )
+    }
+
+    fn set(&mut self, (x, y): (i64, i64)) {
+        if (0..self.width()).contains(&x) && (0..self.height()).contains(&y) {
+            let index = (self.height() - 1 - y) * self.width() + x;
+            self.pixels[index as usize] = true;
+        }
+    }
+
+    fn is_set(&self, x: usize, row: usize) -> bool {
+        self.pixels[row * self.width() as usize + x]
+    }
+
+    pub fn point(&mut self, point: Point) {
+        let (x, y) = self.position(point);
+        if x.is_finite() && y.is_finite() {
+            self.set(self.pixel(point));
+        }
+    }
+
+    // Bresenham's algorithm, between the pixels of both ends once the line
+    // is clipped to the canvas, so that only pixels on it are walked through
+    pub fn line(&mut self, from: Point, to: Point) {
+        let area = (self.width() as f64, self.height() as f64);
+        let ((x0, y0), (x1, y1)) = match clip(self.position(from), self.position(to), area) {
+            Some(ends) => ends,
+            None => return,
+        };
+        let (mut x, mut y) = (x0.floor() as i64, y0.floor() as i64);
+        let (x1, y1) = (x1.floor() as i64, y1.floor() as i64);
+        let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+        let (step_x, step_y) = ((x1 - x).signum(), (y1 - y).signum());
+        let mut error = dx + dy;
+        loop {
+            self.set((x, y));
+            if (x, y) == (x1, y1) {
+                break;
+            }
+            let twice = 2 * error;
+            if twice >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if twice <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    // The outline of a shape
+    pub fn shape(&mut self, shape: &dyn Shape) {
+        match shape.outline() {
+            Outline::Rectangle(rectangle) => {
+                let Rectangle {
+                    top_left,
+                    bottom_right,
+                } = rectangle;
+                let top_right = Point::new(bottom_right.x, top_left.y);
+                let bottom_left = Point::new(top_left.x, bottom_right.y);
+                self.polygon(&[top_left, top_right, bottom_right, bottom_left]);
+            }
+            Outline::Ellipse {
+                center,
+                semi_x,
+                semi_y,
+            } => {
+                // Enough segments for each to be about a pixel long, within
+                // reason for an ellipse much larger than the canvas
+                let segments = (shape.perimeter() / self.scale)
+                    .ceil()
+                    .clamp(8.0, MAX_SEGMENTS) as usize;
+                let vertices: Vec<Point> = (0..segments)
+                    .map(|i| {
+                        let angle = i as f64 / segments as f64 * std::f64::consts::TAU;
+                        Point::new(
+                            center.x + semi_x * angle.cos(),
+                            center.y + semi_y * angle.sin(),
+                        )
+                    })
+                    .collect();
+                self.polygon(&vertices);
+            }
+            Outline::Polygon(vertices) => self.polygon(&vertices),
+        }
+    }
+
+    fn polygon(&mut self, vertices: &[Point]) {
+        for (i, &vertex) in vertices.iter().enumerate() {
+            self.line(vertex, vertices[(i + 1) % vertices.len()]);
+        }
+    }
+
+    // Every pixel whose center is in the shape
+    pub fn fill(&mut self, shape: &dyn Shape) {
+        let bounds = shape.bounding_box();
+        let (left, bottom) = self.pixel(Point::new(bounds.top_left.x, bounds.bottom_right.y));
+        let (right, top) = self.pixel(Point::new(bounds.bottom_right.x, bounds.top_left.y));
+        for y in bottom.max(0)..=top.min(self.height() - 1) {
+            for x in left.max(0)..=right.min(self.width() - 1) {
+                if shape.contains(self.center((x, y))) {
+                    self.set((x, y));
+                }
+            }
+        }
+    }
+}
+
+const MAX_SEGMENTS: f64 = 100_000.0;
+
+// The part of the segment between `from` and `to` within `0..=width` and
+// `0..=height`, if any, with the Cohen–Sutherland algorithm: an end outside
+// the area is moved along the segment onto the edge it is past, until both
+// ends are inside or past the same edge
+fn clip(
+    mut from: (f64, f64),
+    mut to: (f64, f64),
+    (width, height): (f64, f64),
+) -> Option<((f64, f64), (f64, f64))> {
+    let ends = [from.0, from.1, to.0, to.1];
+    if !ends.iter().all(|end| end.is_finite()) {
+        return None;
+    }
+    // The edges a point is past, as bits
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 2;
+    const BOTTOM: u8 = 4;
+    const TOP: u8 = 8;
+    let outside = |(x, y): (f64, f64)| {
+        let mut code = 0;
+        if x < 0.0 {
+            code |= LEFT;
+        } else if x > width {
+            code |= RIGHT;
+        }
+        if y < 0.0 {
+            code |= BOTTOM;
+        } else if y > height {
+            code |= TOP;
+        }
+        code
+    };
+    // Each move puts an end on an edge, so four of them per end at most
+    for _ in 0..8 {
+        let (a, b) = (outside(from), outside(to));
+        if a | b == 0 {
+            return Some((from, to));
+        }
+        if a & b != 0 {
+            return None;
+        }
+        let (end, other) = if a != 0 {
+            (&mut from, to)
+        } else {
+            (&mut to, from)
+        };
+        let code = outside(*end);
+        // The edge is set exactly, and the other coordinate found from how
+        // far along the segment it is
+        let (x, y) = *end;
+        *end = if code & (LEFT | RIGHT) != 0 {
+            let edge = if code & LEFT != 0 { 0.0 } else { width };
+            (edge, y + (other.1 - y) * ((edge - x) / (other.0 - x)))
+        } else {
+            let edge = if code & BOTTOM != 0 { 0.0 } else { height };
+            (x + (other.0 - x) * ((edge - y) / (other.1 - y)), edge)
+        };
+    }
+    None
+}
+
+// A braille character has its dots numbered down the left column, down the
+// right one, then the bottom row, each dot being a bit of the code point
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (across, down) = self.resolution.pixels();
+        for row in 0..self.rows {
+            let line: String = (0..self.columns)
+                .map(|column| match self.resolution {
+                    Resolution::Cells if self.is_set(column, row) => '#',
+                    Resolution::Cells => ' ',
+                    Resolution::Braille => {
+                        let mut bits = 0;
+                        for (dy, dots) in BRAILLE_DOTS.iter().enumerate().take(down) {
+                            for (dx, dot) in dots.iter().enumerate().take(across) {
+                                if self.is_set(column * across + dx, row * down + dy) {
+                                    bits |= dot;
+                                }
+                            }
+                        }
+                        match bits {
+                            0 => ' ',
+                            bits => char::from_u32(0x2800 + bits).unwrap_or('?'),
+                        }
+                    }
+                })
+                .collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::Circle;
+
+    #[test]
+    fn cells() {
+        // Cells are twice as tall as they are wide
+        let mut canvas = Canvas::new(4, 2, Resolution::Cells);
+        canvas.line(Point::new(0.0, 0.0), Point::new(3.0, 3.0));
+        assert_eq!(canvas.to_string(), "  ##\n##\n");
+    }
+
+    #[test]
+    fn braille_bits() {
+        let mut canvas = Canvas::new(2, 1, Resolution::Braille);
+        // The top left dot of the first character, and the bottom right one
+        // of the second
+        canvas.point(Point::new(0.0, 3.0));
+        canvas.point(Point::new(3.0, 0.0));
+        assert_eq!(canvas.to_string(), "\u{2801}\u{2880}\n");
+
+        let mut full = Canvas::new(1, 1, Resolution::Braille);
+        full.fill(&Rectangle::new(Point::new(0.0, 4.0), Point::new(2.0, 0.0)));
+        assert_eq!(full.to_string(), "\u{28ff}\n");
+    }
+
+    #[test]
+    fn fill() {
+        let mut canvas = Canvas::new(6, 3, Resolution::Cells);
+        canvas.fill(&Rectangle::new(Point::new(1.0, 4.0), Point::new(4.0, 2.0)));
+        assert_eq!(canvas.to_string(), "\n ###\n\n");
+
+        // Pixels are filled when their center is in the shape
+        let mut canvas = Canvas::new(5, 3, Resolution::Cells);
+        canvas.fill(&Circle::new(Point::new(2.5, 3.0), 0.8));
+        assert_eq!(canvas.to_string(), "\n  #\n\n");
+    }
+
+    #[test]
+    fn clipping() {
+        // Only the part of the line on the canvas is drawn
+        let mut canvas = Canvas::new(4, 1, Resolution::Cells);
+        canvas.line(Point::new(-100.0, 1.0), Point::new(100.0, 1.0));
+        assert_eq!(canvas.to_string(), "####\n");
+
+        // However far the ends are
+        let mut canvas = Canvas::new(4, 1, Resolution::Cells);
+        canvas.line(Point::new(-1e300, 1.0), Point::new(1e300, 1.0));
+        canvas.line(Point::new(10.0, 10.0), Point::new(20.0, 0.0));
+        assert_eq!(canvas.to_string(), "####\n");
+
+        let mut canvas = Canvas::new(4, 1, Resolution::Cells);
+        canvas.line(Point::new(-1e300, -1e300), Point::new(1e300, 1e300));
+        assert!(canvas.to_string().starts_with('#'));
+
+        // Nothing for ends that aren't numbers
+        let mut canvas = Canvas::new(4, 1, Resolution::Cells);
+        canvas.line(Point::new(f64::NAN, 0.0), Point::new(1.0, 1.0));
+        canvas.point(Point::new(f64::INFINITY, 0.0));
+        assert_eq!(canvas.to_string(), "\n");
+    }
+
+    #[test]
+    fn degenerate_bounds() {
+        let line = Rectangle::new(Point::new(2.0, 5.0), Point::new(2.0, 1.0));
+        let mut canvas = Canvas::fitting(&line, 4, Resolution::Cells);
+        canvas.line(Point::new(2.0, 5.0), Point::new(2.0, 1.0));
+        canvas.shape(&Circle::new(Point::new(2.0, 3.0), 1e12));
+        assert_eq!(canvas.to_string(), "#\n#\n#\n");
+
+        let canvas = Canvas::fitting(&line, 0, Resolution::Braille);
+        assert!(canvas.to_string().lines().count() > 0);
+    }
+}
@@ -0,0 +1,121 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::human::{ByteSize, Duration};
+use crate::refined::{Even, NonZero, Percentage, Positive};
+use crate::words::{Number, Numero, Words};
+
+mod values;
+
+pub use values::{Color, Complex, Coordinates};
+
+// A registry of the types that go through `FromStr` and back out through
+// `Display` and `Debug`, the round trip the conversion example demonstrates
+// on one type at a time:
+//
+//     let color = convert::find("color").unwrap().convert("#ff8000")?;
+//     println!("{} / {}", color.display, color.debug);
+//
+// Registering a type only takes a `FromStr` impl whose error is `Display`.
+
+// A type that can be converted from a string
+pub struct Conversion {
+    pub name: &'static str,
+    // What the strings look like
+    pub example: &'static str,
+    parse: fn(&str) -> Result<Converted, String>,
+}
+
+// A value that was parsed, as its `Display` and `Debug` impls print it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Converted {
+    pub display: String,
+    pub debug: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertError {
+    pub type_name: &'static str,
+    pub message: String,
+}
+
+impl Conversion {
+    fn of<T>(name: &'static str, example: &'static str) -> Conversion
+    where
+        T: FromStr + fmt::Display + fmt::Debug,
+        T::Err: fmt::Display,
+    {
+        Conversion {
+            name,
+            example,
+            parse: parse::<T>,
+        }
+    }
+
+    pub fn convert(&self, input: &str) -> Result<Converted, ConvertError> {
+        (self.parse)(input).map_err(|message| ConvertError {
+            type_name: self.name,
+            message,
+        })
+    }
+}
+
+fn parse<T>(input: &str) -> Result<Converted, String>
+where
+    T: FromStr + fmt::Display + fmt::Debug,
+    T::Err: fmt::Display,
+{
+    let value: T = input.parse().map_err(|error: T::Err| error.to_string())?;
+    Ok(Converted {
+        display: value.to_string(),
+        debug: format!("{:?}", value),
+    })
+}
+
+pub fn registry() -> Vec<Conversion> {
+    vec![
+        Conversion::of::<Even>("even", "42"),
+        Conversion::of::<Positive>("positive", "7"),
+        Conversion::of::<NonZero>("nonzero", "-3"),
+        Conversion::of::<Percentage>("percentage", "99.5"),
+        Conversion::of::<Number>("number", "thirty"),
+        Conversion::of::<Numero>("numero", "trente"),
+        Conversion::of::<Words>("words", "quatre-vingt-dix-sept"),
+        Conversion::of::<Color>("color", "#80ff5a"),
+        Conversion::of::<Complex>("complex", "3.3 + 7.2i"),
+        Conversion::of::<Coordinates>("coordinates", "53.347778°N 6.259722°W"),
+        Conversion::of::<Duration>("duration", "1h30m"),
+        Conversion::of::<ByteSize>("size", "1.5GiB"),
+        Conversion::of::<u8>("u8", "255"),
+        Conversion::of::<u16>("u16", "65535"),
+        Conversion::of::<u32>("u32", "4294967295"),
+        Conversion::of::<u64>("u64", "18446744073709551615"),
+        Conversion::of::<u128>("u128", "340282366920938463463374607431768211455"),
+        Conversion::of::<usize>("usize", "18446744073709551615"),
+        Conversion::of::<i8>("i8", "-128"),
+        Conversion::of::<i16>("i16", "-32768"),
+        Conversion::of::<i32>("i32", "-2147483648"),
+        Conversion::of::<i64>("i64", "-9223372036854775808"),
+        Conversion::of::<i128>("i128", "-170141183460469231731687303715884105728"),
+        Conversion::of::<isize>("isize", "-9223372036854775808"),
+        Conversion::of::<f32>("f32", "65.4321"),
+        Conversion::of::<f64>("f64", "1e-3"),
+        Conversion::of::<bool>("bool", "true"),
+        Conversion::of::<char>("char", "é"),
+    ]
+}
+
+pub fn find(name: &str) -> Option<Conversion> {
+    registry()
+        .into_iter()
+        .find(|conversion| conversion.name == name)
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {}: {}", self.type_name, self.message)
+    }
+}
+
+impl error::Error for ConvertError {}
@@ -0,0 +1,292 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+// The `Color`, `Complex` and `City` coordinates of the println example, made
+// parseable so that they can be converted from strings.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complex {
+    pub real: f64,
+    pub imag: f64,
+}
+
+// In degrees, north and east are positive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+// What was wrong with the value, naming the part at fault
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueError(pub String);
+
+fn error<T>(message: String) -> Result<T, ValueError> {
+    Err(ValueError(message))
+}
+
+// `#80ff5a`, `#8f5`, `0x80FF5A` or `rgb(128, 255, 90)`. What `Display`
+// prints, `RGB (128, 255, 90) 0x80FF5A`, parses too.
+impl FromStr for Color {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Color, ValueError> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            return match hex.len() {
+                6 => Color::from_hex(hex),
+                // `#8f5` is `#88ff55`
+                3 => Color::from_hex(&hex.chars().flat_map(|c| [c, c]).collect::<String>()),
+                n => error(format!(
+                    "expected 3 or 6 hexadecimal digits after `#`, found {}",
+                    n
+                )),
+            };
+        }
+        if let Some(hex) = s.strip_prefix("0x") {
+            return Color::from_hex(hex);
+        }
+
+        let lower = s.to_lowercase();
+        let rest = match lower.strip_prefix("rgb") {
+            Some(rest) => rest.trim_start(),
+            None => {
+                return error(format!(
+                    "`{}` is not `#rrggbb`, `0xRRGGBB` or `rgb(r, g, b)`",
+                    s
+                ))
+            }
+        };
+        let (inside, after) = match rest.strip_prefix('(').and_then(|rest| rest.split_once(')')) {
+            Some(parts) => parts,
+            None => return error("expected `(r, g, b)` after `rgb`".to_owned()),
+        };
+
+        let components: Vec<&str> = inside.split(',').map(str::trim).collect();
+        if components.len() != 3 {
+            return error(format!("expected 3 components, found {}", components.len()));
+        }
+        let mut values = [0u8; 3];
+        for (value, (component, name)) in values
+            .iter_mut()
+            .zip(components.iter().zip(["red", "green", "blue"]))
+        {
+            *value = component.parse().map_err(|_| {
+                ValueError(format!(
+                    "{} component `{}` is not between 0 and 255",
+                    name, component
+                ))
+            })?;
+        }
+        let color = Color {
+            red: values[0],
+            green: values[1],
+            blue: values[2],
+        };
+
+        // The hexadecimal part `Display` adds must agree with the rest
+        let after = after.trim();
+        if !after.is_empty() && after.parse::<Color>() != Ok(color) {
+            return error(format!(
+                "`{}` is not the same color as rgb({})",
+                after, inside
+            ));
+        }
+        Ok(color)
+    }
+}
+
+impl Color {
+    fn from_hex(hex: &str) -> Result<Color, ValueError> {
+        if hex.len() != 6 {
+            return error(format!(
+                "expected 6 hexadecimal digits, found {}",
+                hex.len()
+            ));
+        }
+        let component = |range: std::ops::Range<usize>, name: &str| {
+            let digits = hex.get(range).unwrap_or("");
+            u8::from_str_radix(digits, 16).map_err(|_| {
+                ValueError(format!(
+                    "{} component `{}` is not hexadecimal",
+                    name, digits
+                ))
+            })
+        };
+        Ok(Color {
+            red: component(0..2, "red")?,
+            green: component(2..4, "green")?,
+            blue: component(4..6, "blue")?,
+        })
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "RGB ({red}, {green}, {blue}) 0x{red:02X}{green:02X}{blue:02X}",
+            red = self.red,
+            green = self.green,
+            blue = self.blue,
+        )
+    }
+}
+
+// `3.3 + 7.2i`, `-2i`, `5`, `1e3-i`...
+impl FromStr for Complex {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Complex, ValueError> {
+        let compact: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if compact.is_empty() {
+            return error("expected a complex number like `3.3 + 7.2i`".to_owned());
+        }
+        let number = |text: &str, part: &str| {
+            text.parse::<f64>()
+                .map_err(|_| ValueError(format!("{} part `{}` is not a number", part, text)))
+        };
+
+        let imaginary = match compact.strip_suffix('i') {
+            Some(imaginary) => imaginary,
+            None => {
+                return Ok(Complex {
+                    real: number(&compact, "real")?,
+                    imag: 0.0,
+                })
+            }
+        };
+        // The sign between the parts, which isn't the one of an exponent
+        let bytes = imaginary.as_bytes();
+        let split = (1..bytes.len())
+            .rev()
+            .find(|&i| {
+                (bytes[i] == b'+' || bytes[i] == b'-') && !matches!(bytes[i - 1], b'e' | b'E')
+            })
+            .unwrap_or(0);
+        let (real, imag) = imaginary.split_at(split);
+        let imag = match imag {
+            "" | "+" => 1.0,
+            "-" => -1.0,
+            imag => number(imag, "imaginary")?,
+        };
+        let real = if real.is_empty() {
+            0.0
+        } else {
+            number(real, "real")?
+        };
+        Ok(Complex { real, imag })
+    }
+}
+
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.imag.is_sign_negative() {
+            '-'
+        } else {
+            '+'
+        };
+        write!(f, "{} {} {}i", self.real, sign, self.imag.abs())
+    }
+}
+
+// `53.347778°N 6.259722°W`, the degree signs being optional, or signed
+// decimal degrees: `53.347778, -6.259722`
+impl FromStr for Coordinates {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Coordinates, ValueError> {
+        let parts: Vec<&str> = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .collect();
+        if parts.len() != 2 {
+            return error(format!(
+                "expected a latitude and a longitude, found {} parts",
+                parts.len()
+            ));
+        }
+        let lat = degrees(parts[0], 'N', 'S', "latitude")?;
+        let lon = degrees(parts[1], 'E', 'W', "longitude")?;
+        if !(-90.0..=90.0).contains(&lat) {
+            return error(format!("latitude {} is not between 90°S and 90°N", lat));
+        }
+        if !(-180.0..=180.0).contains(&lon) {
+            return error(format!("longitude {} is not between 180°W and 180°E", lon));
+        }
+        Ok(Coordinates { lat, lon })
+    }
+}
+
+// `53.3°N` or `-6.2`, for the given positive and negative directions
+fn degrees(text: &str, positive: char, negative: char, name: &str) -> Result<f64, ValueError> {
+    let last = text.chars().last();
+    let before_last = last.map_or(text, |c| &text[..text.len() - c.len_utf8()]);
+    let (number, sign) = match last.map(|c| c.to_ascii_uppercase()) {
+        Some(c) if c == positive => (before_last, 1.0),
+        Some(c) if c == negative => (before_last, -1.0),
+        Some(c) if c.is_alphabetic() => {
+            return error(format!(
+                "the {} `{}` should end with {} or {}",
+                name, text, positive, negative
+            ))
+        }
+        _ => (text, 1.0),
+    };
+    let number = number.trim_end_matches('°');
+    match number.parse::<f64>() {
+        Ok(value) if sign < 0.0 && value < 0.0 => error(format!(
+            "the {} `{}` has both a sign and a direction",
+            name, text
+        )),
+        Ok(value) => Ok(sign * value),
+        Err(_) => error(format!("the {} `{}` is not a number", name, text)),
+    }
+}
+
+impl fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
+        let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
+        let precision = f.precision().unwrap_or(3);
+        write!(
+            f,
+            "{:.*}°{} {:.*}°{}",
+            precision,
+            self.lat.abs(),
+            lat_c,
+            precision,
+            self.lon.abs(),
+            lon_c
+        )
+    }
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl error::Error for ValueError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates() {
+        let coordinates: Coordinates = "53n 6.5°w".parse().unwrap();
+        assert_eq!((coordinates.lat, coordinates.lon), (53.0, -6.5));
+        assert!("53ß 6E".parse::<Coordinates>().is_err());
+        assert!("-53S 6E".parse::<Coordinates>().is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt;
+use std::fmt::Write as _;
+
+// A display adapter for anything that can be iterated over several times
+// (`&Vec<T>`, slices, ranges, ...) and whose items implement `Display`.
+//
+// By default it renders like the `List` of the println example:
+// `[0: 1, 1: 2, 2: 3]` when indexed, `[1, 2, 3]` otherwise. The `{:#}`
+// alternate flag switches to a multi-line layout, one item per line. Either
+// way, the caller's width, fill, alignment and precision apply to each item.
+#[derive(Clone)]
+pub struct DisplayList<'a, I> {
+    items: I,
+    open: &'a str,
+    close: &'a str,
+    separator: &'a str,
+    indexed: bool,
+    limit: Option<usize>,
+}
+
+impl<'a, I> DisplayList<'a, I>
+where
+    I: IntoIterator + Clone,
+    I::Item: fmt::Display,
+{
+    pub fn new(items: I) -> DisplayList<'a, I> {
+        DisplayList {
+            items,
+            open: "[",
+            close: "]",
+            separator: ", ",
+            indexed: false,
+            limit: None,
+        }
+    }
+
+    pub fn open(mut self, open: &'a str) -> Self {
+        self.open = open;
+        self
+    }
+
+    pub fn close(mut self, close: &'a str) -> Self {
+        self.close = close;
+        self
+    }
+
+    pub fn separator(mut self, separator: &'a str) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    // Prefix every item with its position: `0: 1`
+    pub fn indexed(mut self, indexed: bool) -> Self {
+        self.indexed = indexed;
+        self
+    }
+
+    // Only show the first `limit` items, followed by `… (k more)`
+    pub fn truncate(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn write_inline(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.open)?;
+        let mut iter = self.items.clone().into_iter();
+        let limit = self.limit.unwrap_or(usize::MAX);
+
+        for (count, item) in iter.by_ref().take(limit).enumerate() {
+            if count != 0 {
+                f.write_str(self.separator)?;
+            }
+            if self.indexed {
+                write!(f, "{}: ", count)?;
+            }
+            // Forward the caller's flags (precision, width, ...) to the item.
+            fmt::Display::fmt(&item, f)?;
+        }
+
+        let remaining = iter.count();
+        if remaining > 0 {
+            if limit > 0 {
+                f.write_str(self.separator)?;
+            }
+            write!(f, "… ({} more)", remaining)?;
+        }
+        f.write_str(self.close)
+    }
+
+    fn write_pretty(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The separator ends each line, so trailing spaces are dropped.
+        let separator = self.separator.trim_end();
+        let options = Options {
+            width: f.width(),
+            precision: f.precision(),
+            fill: f.fill(),
+            align: f.align(),
+        };
+        let mut iter = self.items.clone().into_iter();
+        let limit = self.limit.unwrap_or(usize::MAX);
+
+        f.write_str(self.open)?;
+        let mut empty = true;
+        for (count, item) in iter.by_ref().take(limit).enumerate() {
+            if empty {
+                f.write_str("\n")?;
+                empty = false;
+            }
+            let mut pad = PadAdapter::new(f);
+            if self.indexed {
+                write!(pad, "{}: ", count)?;
+            }
+            // Nested lists keep the alternate layout and get indented.
+            pad.write_str(&pretty(&item, options))?;
+            writeln!(f, "{}", separator)?;
+        }
+
+        let remaining = iter.count();
+        if remaining > 0 {
+            if empty {
+                f.write_str("\n")?;
+            }
+            writeln!(PadAdapter::new(f), "… ({} more)", remaining)?;
+        }
+        f.write_str(self.close)
+    }
+}
+
+impl<'a, I> fmt::Display for DisplayList<'a, I>
+where
+    I: IntoIterator + Clone,
+    I::Item: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            self.write_pretty(f)
+        } else {
+            self.write_inline(f)
+        }
+    }
+}
+
+// The caller's flags, kept while the formatter is borrowed by a `PadAdapter`
+#[derive(Clone, Copy)]
+struct Options {
+    width: Option<usize>,
+    precision: Option<usize>,
+    fill: char,
+    align: Option<fmt::Alignment>,
+}
+
+// `item` in the alternate layout, with the caller's width, fill, alignment
+// and precision, the way `Display::fmt` would have written it with them.
+fn pretty(item: &impl fmt::Display, options: Options) -> String {
+    // Without an alignment, the fill is a space and the item picks its own
+    // side, numbers right and text left, which a runtime width keeps
+    let width = match options.align {
+        None => options.width.unwrap_or(0),
+        Some(_) => 0,
+    };
+    let text = match options.precision {
+        Some(precision) => format!("{:#width$.precision$}", item),
+        None => format!("{:#width$}", item),
+    };
+    let (align, width) = match (options.align, options.width) {
+        (Some(align), Some(width)) => (align, width),
+        _ => return text,
+    };
+    let padding = width.saturating_sub(text.chars().count());
+    let (before, after) = match align {
+        fmt::Alignment::Left => (0, padding),
+        fmt::Alignment::Right => (padding, 0),
+        fmt::Alignment::Center => (padding / 2, padding - padding / 2),
+    };
+    let fill = |count| options.fill.to_string().repeat(count);
+    format!("{}{}{}", fill(before), text, fill(after))
+}
+
+// Indents everything written through it by four spaces, the same way
+// `{:#?}` indents nested structures.
+struct PadAdapter<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    on_newline: bool,
+}
+
+impl<'a, 'b> PadAdapter<'a, 'b> {
+    fn new(f: &'a mut fmt::Formatter<'b>) -> PadAdapter<'a, 'b> {
+        PadAdapter {
+            f,
+            on_newline: true,
+        }
+    }
+}
+
+impl<'a, 'b> fmt::Write for PadAdapter<'a, 'b> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for line in s.split_inclusive('\n') {
+            if self.on_newline {
+                self.f.write_str("    ")?;
+            }
+            self.on_newline = line.ends_with('\n');
+            self.f.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline() {
+        assert_eq!(DisplayList::new(1..=3).to_string(), "[1, 2, 3]");
+        let list = DisplayList::new(&["a", "b"]).indexed(true);
+        assert_eq!(list.to_string(), "[0: a, 1: b]");
+        assert_eq!(
+            format!("{:.1}", DisplayList::new(&[1.25, 2.0])),
+            "[1.2, 2.0]"
+        );
+        assert_eq!(format!("{:>3}", DisplayList::new(&[1, 22])), "[  1,  22]");
+    }
+
+    #[test]
+    fn separators() {
+        let list = DisplayList::new(1..=3)
+            .open("{")
+            .close("}")
+            .separator(" | ");
+        assert_eq!(list.to_string(), "{1 | 2 | 3}");
+        assert_eq!(format!("{:#}", list), "{\n    1 |\n    2 |\n    3 |\n}");
+    }
+
+    #[test]
+    fn truncation() {
+        let list = DisplayList::new(1..=100).truncate(2);
+        assert_eq!(list.to_string(), "[1, 2, … (98 more)]");
+        assert_eq!(
+            DisplayList::new(1..=3).truncate(0).to_string(),
+            "[… (3 more)]"
+        );
+        assert_eq!(DisplayList::new(1..=2).truncate(5).to_string(), "[1, 2]");
+        assert_eq!(
+            format!("{:#}", DisplayList::new(1..=3).truncate(1)),
+            "[\n    1,\n    … (2 more)\n]"
+        );
+    }
+
+    #[test]
+    fn pretty() {
+        assert_eq!(format!("{:#}", DisplayList::new(0..0)), "[]");
+        let rows = vec![DisplayList::new(&[1.0, 2.5]), DisplayList::new(&[3.0, 4.0])];
+        assert_eq!(
+            format!("{:#.1}", DisplayList::new(&rows)),
+            "[\n    [\n        1.0,\n        2.5,\n    ],\n    [\n        3.0,\n        4.0,\n    ],\n]"
+        );
+    }
+
+    #[test]
+    fn pretty_flags() {
+        let numbers = DisplayList::new(&[1, 22]);
+        assert_eq!(format!("{:#3}", numbers), "[\n      1,\n     22,\n]");
+        assert_eq!(format!("{:*<#4}", numbers), "[\n    1***,\n    22**,\n]");
+        assert_eq!(
+            format!("{:-^#5.1}", DisplayList::new(&[0.25])),
+            "[\n    -0.2-,\n]"
+        );
+        assert_eq!(format!("{:#3}", DisplayList::new(&["a"])), "[\n    a  ,\n]");
+    }
+}
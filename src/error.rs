@@ -0,0 +1,102 @@
+use std::env;
+use std::error;
+use std::fmt;
+use std::io;
+
+use crate::matrix::MatrixError;
+use crate::refined::RefinementError;
+
+// Everything that can make a command fail, as opposed to the panics the
+// examples trigger on purpose. The runner prints the error and its causes,
+// and exits with `exit_code`.
+#[derive(Debug)]
+pub enum Error {
+    // $BINARY isn't set, or isn't valid unicode
+    MissingBinary(env::VarError),
+    UnknownBinary(String),
+    // Invalid command-line arguments
+    Usage(String),
+    Io {
+        // What was being done, "reading the guess" for instance
+        context: &'static str,
+        source: io::Error,
+    },
+    Parse {
+        input: String,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    Refinement(RefinementError),
+    Matrix(MatrixError),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn io(context: &'static str, source: io::Error) -> Error {
+        Error::Io { context, source }
+    }
+
+    pub fn parse<E>(input: &str, source: E) -> Error
+    where
+        E: error::Error + Send + Sync + 'static,
+    {
+        Error::Parse {
+            input: input.to_owned(),
+            source: Box::new(source),
+        }
+    }
+
+    // Following the BSD `sysexits.h` conventions, 101 is left to panics
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            // EX_USAGE
+            Error::MissingBinary(_) | Error::UnknownBinary(_) | Error::Usage(_) => 64,
+            // EX_DATAERR
+            Error::Parse { .. } | Error::Refinement(_) => 65,
+            // EX_SOFTWARE
+            Error::Matrix(_) => 70,
+            // EX_IOERR
+            Error::Io { .. } => 74,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::MissingBinary(_) => write!(f, "environment variable $BINARY must be present"),
+            Error::UnknownBinary(binary) => write!(f, "no such binary configuration '{}'", binary),
+            Error::Usage(message) => write!(f, "{}", message),
+            Error::Io { context, .. } => write!(f, "i/o error while {}", context),
+            Error::Parse { input, .. } => write!(f, "could not parse `{}`", input),
+            Error::Refinement(error) => write!(f, "{}", error),
+            Error::Matrix(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::MissingBinary(source) => Some(source),
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { source, .. } => Some(source.as_ref()),
+            // Their message already is the whole story
+            Error::UnknownBinary(_) | Error::Usage(_) | Error::Refinement(_) | Error::Matrix(_) => {
+                None
+            }
+        }
+    }
+}
+
+impl From<RefinementError> for Error {
+    fn from(error: RefinementError) -> Error {
+        Error::Refinement(error)
+    }
+}
+
+impl From<MatrixError> for Error {
+    fn from(error: MatrixError) -> Error {
+        Error::Matrix(error)
+    }
+}
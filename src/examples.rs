@@ -1,6 +1,17 @@
+pub mod bindings;
+pub mod calculator;
+pub mod cast_rules;
+pub mod casting_explorer;
 pub mod conversion;
+pub mod convert;
 pub mod custom_types;
+pub mod float_inspector;
 pub mod guess_number;
+pub mod infer;
+pub mod layout;
+pub mod literals;
+pub mod matrix_bench;
+pub mod overflow;
 pub mod primitives;
 pub mod println;
 pub mod types;
@@ -0,0 +1,56 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+
+use crate::bindings;
+use crate::error::Error;
+
+// Checks and runs a program of the `bindings` language, read from the file
+// given as argument or from the standard input:
+//
+//     BINARY=bindings cargo run -- snippet.rs
+//     echo 'let x = 1; x += 1;' | BINARY=bindings cargo run
+//
+// Errors are printed the way rustc prints them, the program only runs
+// without any. With `--timeline`, it draws the life of each binding instead:
+//
+//     BINARY=bindings cargo run -- --timeline snippet.rs
+pub fn main() -> Result<(), Error> {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let timeline = args.first().map(String::as_str) == Some("--timeline");
+    if timeline {
+        args.remove(0);
+    }
+
+    let source = match args.first() {
+        Some(path) => {
+            fs::read_to_string(path).map_err(|error| Error::io("reading the program", error))?
+        }
+        None => {
+            let mut source = String::new();
+            io::stdin()
+                .read_to_string(&mut source)
+                .map_err(|error| Error::io("reading the program", error))?;
+            source
+        }
+    };
+
+    if timeline {
+        match bindings::timeline(&source) {
+            Ok(timeline) => print!("{}", timeline),
+            Err(error) => println!("{}", error.render(&source)),
+        }
+        return Ok(());
+    }
+
+    match bindings::run(&source) {
+        Ok(output) => print!("{}", output),
+        Err(errors) => {
+            for error in &errors {
+                println!("{}\n", error.render(&source));
+            }
+            println!("{} error(s)", errors.len());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+
+use crate::calculator::{Calculator, Number};
+use crate::error::Error;
+
+// `parse_string` in the conversion example adds two parsed numbers, this
+// evaluates whole expressions. With arguments it evaluates them as a single
+// expression, without it reads one per line until the end of the input:
+//
+//     BINARY=calc cargo run -- "(1 + 2) * 3.5"
+//     BINARY=calc cargo run
+//     > speed = 42
+//     > speed * 2 / (1 - 1)
+//                   ^^^^^^^ attempt to divide by zero
+//
+// `vars` lists the variables, `quit` leaves.
+pub fn main() -> Result<(), Error> {
+    let mut calculator = Calculator::new();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        let line = args.join(" ");
+        match calculator.evaluate(&line) {
+            Ok(value) => println!("{}", value),
+            Err(error) => println!("{}", error.render(&line)),
+        }
+        return Ok(());
+    }
+
+    const PROMPT: &str = "> ";
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("{}", PROMPT);
+        io::stdout()
+            .flush()
+            .map_err(|error| Error::io("writing the prompt", error))?;
+
+        let line = match lines.next() {
+            Some(line) => line.map_err(|error| Error::io("reading an expression", error))?,
+            None => break,
+        };
+        match line.trim() {
+            "" => continue,
+            "quit" | "exit" => break,
+            "vars" => {
+                let mut variables: Vec<(&str, Number)> = calculator.variables().collect();
+                variables.sort_by_key(|&(name, _)| name);
+                for (name, value) in variables {
+                    println!("{} = {}", name, value);
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        match calculator.evaluate(&line) {
+            Ok(value) => println!("{}", value),
+            Err(error) => println!("{}", error.caret(&line, PROMPT.len())),
+        }
+    }
+    println!();
+    Ok(())
+}
@@ -0,0 +1,439 @@
+use std::fmt;
+
+use crate::error::Error;
+use crate::literal::Value;
+
+// The comments of `types::casting` describe what `as` does. This section
+// checks them: every primitive numeric type is cast into every other one for
+// a set of boundary values, and each result is compared with the one
+// computed from the documented rule alone, without using `as` on the value
+// under test.
+pub fn main() -> Result<(), Error> {
+    let casts = casts();
+    for (rule, description) in RULES.iter().enumerate() {
+        let checked: Vec<&Cast> = casts.iter().filter(|cast| cast.rule == rule).collect();
+        let ok = checked.iter().filter(|cast| cast.agrees()).count();
+        println!("{}: {}/{} casts ok", description, ok, checked.len());
+        for cast in checked.iter().filter(|cast| !cast.agrees()) {
+            println!("  {}", cast);
+        }
+    }
+    Ok(())
+}
+
+const RULES: [&str; 4] = [
+    "int -> int, T::MAX + 1 added or subtracted until it fits",
+    "float -> int, saturating, NaN is 0",
+    "int -> float, rounded to nearest, ties to even",
+    "float -> float, rounded to nearest, ties to even",
+];
+
+// A value cast with `as`, and what the rule at `rule` in `RULES` says it
+// should have given
+struct Cast {
+    rule: usize,
+    source: Value,
+    actual: Value,
+    expected: Value,
+}
+
+impl Cast {
+    fn agrees(&self) -> bool {
+        same(self.actual, self.expected)
+    }
+}
+
+impl fmt::Display for Cast {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{} as {}: got {}, the rule says {}",
+            self.source,
+            self.source.type_name(),
+            self.actual.type_name(),
+            self.actual,
+            self.expected
+        )
+    }
+}
+
+// Every boundary value of every type, cast into every type
+fn casts() -> Vec<Cast> {
+    let mut casts = Vec::new();
+    for source_type in types() {
+        for source in boundary_values(source_type) {
+            for actual in source.cast_all().iter().copied() {
+                let (rule, expected) = match (source.is_float(), actual.is_float()) {
+                    (false, false) => (0, integer_to_integer(source, actual)),
+                    (true, false) => (1, float_to_integer(source, actual)),
+                    (false, true) => (2, integer_to_float(source, actual)),
+                    (true, true) => (3, float_to_float(source, actual)),
+                };
+                casts.push(Cast {
+                    rule,
+                    source,
+                    actual,
+                    expected,
+                });
+            }
+        }
+    }
+    casts
+}
+
+// One value of every primitive type, as a template for `Value::with_bits`
+fn types() -> [Value; 14] {
+    Value::U8(0).cast_all()
+}
+
+// Two values are the same if they have the same bits, except for NaN whose
+// payload isn't specified
+fn same(a: Value, b: Value) -> bool {
+    match (a, b) {
+        (Value::F32(a), Value::F32(b)) if a.is_nan() => b.is_nan(),
+        (Value::F64(a), Value::F64(b)) if a.is_nan() => b.is_nan(),
+        _ => a.type_name() == b.type_name() && a.to_bits() == b.to_bits(),
+    }
+}
+
+// Sign and magnitude of an integer value
+fn integer_parts(value: Value) -> (bool, u128) {
+    let bits = value.bits();
+    let raw = value.to_bits();
+    if value.is_signed() && raw >> (bits - 1) == 1 {
+        // Sign-extend to 128 bits, then negate to get the magnitude
+        let extended = if bits == 128 { raw } else { raw | (!0 << bits) };
+        (true, extended.wrapping_neg())
+    } else {
+        (false, raw)
+    }
+}
+
+// The exact value of a float, decoded from its fields
+fn float_value(value: Value) -> f64 {
+    match value {
+        Value::F64(v) => v,
+        Value::F32(v) => f32_to_f64(v),
+        _ => unreachable!(),
+    }
+}
+
+fn f32_to_f64(v: f32) -> f64 {
+    let bits = v.to_bits();
+    let sign = if bits >> 31 == 1 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = (bits & 0x7f_ffff) as u64;
+    match exponent {
+        0xff if mantissa != 0 => f64::NAN,
+        0xff => sign * f64::INFINITY,
+        // Subnormal: no implicit leading 1, fixed exponent
+        0 => sign * mantissa as f64 * 2f64.powi(-149),
+        _ => {
+            let bits = ((bits as u64 >> 31) << 63)
+                | (((exponent - 127 + 1023) as u64) << 52)
+                | (mantissa << 29);
+            f64::from_bits(bits)
+        }
+    }
+}
+
+// The value modulo 2^bits: "T::MAX + 1 is added or subtracted until the
+// value fits into the new type". For signed types those are also the two's
+// complement bits.
+fn wrap(negative: bool, magnitude: u128, bits: u32) -> u128 {
+    let residue = if bits == 128 {
+        magnitude
+    } else {
+        magnitude % (1 << bits)
+    };
+    if negative {
+        let negated = residue.wrapping_neg();
+        if bits == 128 {
+            negated
+        } else {
+            negated & ((1 << bits) - 1)
+        }
+    } else {
+        residue
+    }
+}
+
+fn integer_to_integer(source: Value, target: Value) -> Value {
+    let (negative, magnitude) = integer_parts(source);
+    target.with_bits(wrap(negative, magnitude, target.bits()))
+}
+
+// "The `as` keyword performs a saturating cast when casting from float to
+// int", the fraction is dropped and NaN becomes 0.
+fn float_to_integer(source: Value, target: Value) -> Value {
+    let v = float_value(source);
+    let bits = target.bits() as i32;
+    let (min, max) = if target.is_signed() {
+        (-(2f64.powi(bits - 1)), 2f64.powi(bits - 1))
+    } else {
+        (0.0, 2f64.powi(bits))
+    };
+    // `max` itself is just out of range
+    let all_ones = if bits == 128 { !0 } else { (1u128 << bits) - 1 };
+
+    if v.is_nan() {
+        target.with_bits(0)
+    } else if v >= max {
+        // MAX is 0111...1 for signed types, 1111...1 for unsigned ones
+        target.with_bits(if target.is_signed() {
+            all_ones >> 1
+        } else {
+            all_ones
+        })
+    } else if v.trunc() < min {
+        // MIN is 1000...0 for signed types, 0 for unsigned ones
+        target.with_bits(if target.is_signed() {
+            1 << (bits - 1)
+        } else {
+            0
+        })
+    } else {
+        let t = v.trunc();
+        // `t` is an integer that fits in 128 bits, so it can be rebuilt
+        // exactly from its digits in base 2^32.
+        let mut magnitude = 0u128;
+        let mut rest = t.abs();
+        for shift in (0..4).rev() {
+            let unit = 2f64.powi(32 * shift);
+            let digit = (rest / unit).floor();
+            rest -= digit * unit;
+            magnitude |= (digit as u32 as u128) << (32 * shift);
+        }
+        target.with_bits(wrap(t < 0.0, magnitude, bits as u32))
+    }
+}
+
+// Round `magnitude` to `precision` significant bits, to nearest with ties to
+// even, returning the rounded value as a mantissa and a power of two.
+fn round_to_precision(magnitude: u128, precision: u32) -> (u128, i32) {
+    let length = 128 - magnitude.leading_zeros();
+    if length <= precision {
+        return (magnitude, 0);
+    }
+    let shift = length - precision;
+    let quotient = magnitude >> shift;
+    let remainder = magnitude & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    let round_up = remainder > half || (remainder == half && quotient % 2 == 1);
+    (quotient + round_up as u128, shift as i32)
+}
+
+fn integer_to_float(source: Value, target: Value) -> Value {
+    let (negative, magnitude) = integer_parts(source);
+    let sign = if negative { -1.0 } else { 1.0 };
+    let (mantissa, exponent) = match target {
+        Value::F32(_) => round_to_precision(magnitude, 24),
+        _ => round_to_precision(magnitude, 53),
+    };
+    // The mantissa has at most 54 bits and the multiplication is by a power
+    // of two: both are exact in `f64`.
+    let v = sign * mantissa as u64 as f64 * 2f64.powi(exponent);
+    match target {
+        // Only `u128` can exceed `f32::MAX`, which rounds to infinity
+        Value::F32(_) if v.abs() >= 2f64.powi(128) => target.with_bits(
+            (if negative {
+                f32::NEG_INFINITY
+            } else {
+                f32::INFINITY
+            })
+            .to_bits() as u128,
+        ),
+        Value::F32(_) => target.with_bits(f32_from_exact(v).to_bits() as u128),
+        _ => target.with_bits(v.to_bits() as u128),
+    }
+}
+
+// Build the `f32` holding `v`, which must be exactly representable
+fn f32_from_exact(v: f64) -> f32 {
+    // Decode `v` into fields and re-encode them with the `f32` layout
+    if v == 0.0 {
+        return f32::from_bits(if v.is_sign_negative() { 1 << 31 } else { 0 });
+    }
+    let bits = v.to_bits();
+    let sign = ((bits >> 63) as u32) << 31;
+    let exponent = ((bits >> 52) & 0x7ff) as i32 - 1023;
+    let mantissa = bits & ((1 << 52) - 1);
+    if exponent < -126 {
+        // Subnormal `f32`: the value is a multiple of 2^-149
+        let units = v.abs() * 2f64.powi(149);
+        f32::from_bits(sign | units as u32)
+    } else {
+        f32::from_bits(sign | (((exponent + 127) as u32) << 23) | (mantissa >> 29) as u32)
+    }
+}
+
+fn float_to_float(source: Value, target: Value) -> Value {
+    let v = float_value(source);
+    match target {
+        // Every `f32` is exactly an `f64`
+        Value::F64(_) => target.with_bits(v.to_bits() as u128),
+        _ if v.is_nan() => target.with_bits(f32::NAN.to_bits() as u128),
+        _ => {
+            // f32::MAX + half an ulp and above round to infinity
+            let overflow = 2f64.powi(128) - 2f64.powi(103);
+            if v.abs() >= overflow {
+                let infinity = if v < 0.0 {
+                    f32::NEG_INFINITY
+                } else {
+                    f32::INFINITY
+                };
+                return target.with_bits(infinity.to_bits() as u128);
+            }
+            // Distance between consecutive `f32`s around `v`
+            let exponent = ((v.to_bits() >> 52) & 0x7ff) as i32 - 1023;
+            let quantum = 2f64.powi(exponent.max(-126) - 23);
+            let units = v.abs() / quantum;
+            let floor = units.floor();
+            let fraction = units - floor;
+            let round_up = fraction > 0.5 || (fraction == 0.5 && floor % 2.0 == 1.0);
+            let rounded = (floor + if round_up { 1.0 } else { 0.0 }) * quantum;
+            let rounded = if v.is_sign_negative() {
+                -rounded
+            } else {
+                rounded
+            };
+            target.with_bits(f32_from_exact(rounded).to_bits() as u128)
+        }
+    }
+}
+
+// MIN, MAX, 0, ±1 and the bounds of every other integer type for integers;
+// for floats also NaN, ±inf, subnormals, halves (to see the rounding) and
+// the neighbours of every integer bound.
+fn boundary_values(template: Value) -> Vec<Value> {
+    let mut integers: Vec<(bool, u128)> = vec![(false, 0), (false, 1), (true, 1), (false, 2)];
+    for t in types().iter().filter(|t| !t.is_float()) {
+        let bits = t.bits();
+        let (min, max) = if t.is_signed() {
+            (
+                (true, 1u128 << (bits - 1)),
+                (false, (1u128 << (bits - 1)) - 1),
+            )
+        } else {
+            (
+                (false, 0),
+                (false, if bits == 128 { !0 } else { (1 << bits) - 1 }),
+            )
+        };
+        for &(negative, magnitude) in &[min, max] {
+            integers.push((negative, magnitude));
+            integers.push((negative, magnitude.saturating_add(1)));
+            integers.push((negative, magnitude.saturating_sub(1)));
+        }
+    }
+    // Where integers stop being exact in `f32` and `f64`
+    for &precision in &[24, 53] {
+        for delta in 0..4 {
+            integers.push((false, (1 << precision) + delta));
+            integers.push((true, (1 << precision) + delta));
+        }
+    }
+
+    match template {
+        Value::F32(_) | Value::F64(_) => {
+            let mut floats = vec![
+                0.0,
+                -0.0,
+                0.5,
+                -0.5,
+                1.5,
+                2.5,
+                -2.5,
+                0.99,
+                -0.99,
+                f64::NAN,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::MAX,
+                f64::MIN,
+                f64::MIN_POSITIVE,
+                f64::MIN_POSITIVE / 2.0,
+                f64::from_bits(1),
+                f64::EPSILON,
+                // f32 rounding: ties at 1 + 2^-24, overflow and underflow
+                1.0 + 2f64.powi(-24),
+                1.0 + 3.0 * 2f64.powi(-24),
+                2f64.powi(128) - 2f64.powi(103),
+                2f64.powi(128) - 2f64.powi(104),
+                2f64.powi(-149),
+                2f64.powi(-150),
+                3.0 * 2f64.powi(-150),
+                2f64.powi(-127),
+                f32::MAX as f64,
+                f32::MIN_POSITIVE as f64,
+            ];
+            for &(negative, magnitude) in &integers {
+                let v = magnitude as f64;
+                let v = if negative { -v } else { v };
+                floats.extend_from_slice(&[v, v.next_up(), v.next_down()]);
+            }
+            floats
+                .into_iter()
+                .map(|v| match template {
+                    Value::F32(_) => Value::F32(v as f32),
+                    _ => Value::F64(v),
+                })
+                .collect()
+        }
+        _ => integers
+            .into_iter()
+            .filter_map(|(negative, magnitude)| fitting(template, negative, magnitude))
+            .collect(),
+    }
+}
+
+// The value of `template`'s type holding ±`magnitude`, if it fits
+fn fitting(template: Value, negative: bool, magnitude: u128) -> Option<Value> {
+    let negative = negative && magnitude != 0;
+    let candidate = template.with_bits(wrap(negative, magnitude, template.bits()));
+    if integer_parts(candidate) == (negative, magnitude) {
+        Some(candidate)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every cast the rule at `rule` covers agrees with it, and there are some
+    fn check(rule: usize) {
+        let casts: Vec<Cast> = casts()
+            .into_iter()
+            .filter(|cast| cast.rule == rule)
+            .collect();
+        assert!(!casts.is_empty());
+        let wrong: Vec<String> = casts
+            .iter()
+            .filter(|cast| !cast.agrees())
+            .map(Cast::to_string)
+            .collect();
+        assert!(wrong.is_empty(), "{}", wrong.join("\n"));
+    }
+
+    #[test]
+    fn integer_to_integer_wraps() {
+        check(0);
+    }
+
+    #[test]
+    fn float_to_integer_saturates() {
+        check(1);
+    }
+
+    #[test]
+    fn integer_to_float_rounds_to_nearest() {
+        check(2);
+    }
+
+    #[test]
+    fn float_to_float_rounds_to_nearest() {
+        check(3);
+    }
+}
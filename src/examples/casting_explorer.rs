@@ -0,0 +1,289 @@
+use std::convert::TryFrom;
+use std::env;
+use std::fmt;
+
+use crate::error::Error;
+use crate::literal::{self, Value};
+
+// Shows what `as` does to a literal for every primitive numeric type, and
+// what `TryFrom` would have said about the same conversion.
+//
+//     BINARY=casting_explorer cargo run -- 1000
+//     BINARY=casting_explorer cargo run -- -1i8 0x80u8
+//     BINARY=casting_explorer cargo run -- 300.0f32 f32::NAN
+//
+// Any Rust numeric literal works, as well as the `MIN`, `MAX`, `NAN`,
+// `INFINITY` and `NEG_INFINITY` constants of the primitive types.
+pub fn main() -> Result<(), Error> {
+    let literals: Vec<String> = env::args().skip(1).collect();
+    let literals = if literals.is_empty() {
+        // The values used by the casting section of the types example
+        vec!["1000".to_owned(), "-1i8".to_owned(), "300.0f32".to_owned()]
+    } else {
+        literals
+    };
+
+    for literal in literals {
+        match parse(&literal) {
+            Ok(value) => explore(value),
+            Err(error) => println!("{}: {}", literal, error),
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn explore(value: Value) {
+    println!(
+        "{}{} has the bits {}",
+        value,
+        value.type_name(),
+        value.bit_pattern()
+    );
+    println!(
+        "{:>5} | {:>40} | {:>34} | {:<40} | TryFrom",
+        "as", "value", "bits", "what happened"
+    );
+
+    for (result, try_from) in value.cast_all().iter().zip(try_from_all(value).iter()) {
+        let try_from = match try_from {
+            TryFromResult::Ok(value) => format!("Ok({})", value),
+            TryFromResult::Err(error) => format!("Err({})", error),
+            TryFromResult::NotImplemented => "not implemented".to_owned(),
+        };
+        println!(
+            "{:>5} | {:>40} | {:>34} | {:<40} | {}",
+            result.type_name(),
+            result.to_string(),
+            result.bit_pattern(),
+            explain(value, *result),
+            try_from
+        );
+    }
+}
+
+// What `TryFrom` returns for a conversion, if the conversion exists at all
+#[derive(Debug, Clone, PartialEq)]
+pub enum TryFromResult {
+    Ok(Value),
+    Err(String),
+    NotImplemented,
+}
+
+// The mathematical value held by a `Value`, so that values of different
+// types can be compared without going through yet another cast.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Exact {
+    Integer { negative: bool, magnitude: u128 },
+    // Non-integral, or too large for a `u128`
+    Float(f64),
+    Infinity { negative: bool },
+    NaN,
+}
+
+// `T::try_from($v)` for every integer `T`, in the order of `Value`
+macro_rules! try_from_integers {
+    ($v:expr) => {
+        [
+            try_from_integer(u8::try_from($v).map(Value::U8)),
+            try_from_integer(u16::try_from($v).map(Value::U16)),
+            try_from_integer(u32::try_from($v).map(Value::U32)),
+            try_from_integer(u64::try_from($v).map(Value::U64)),
+            try_from_integer(u128::try_from($v).map(Value::U128)),
+            try_from_integer(usize::try_from($v).map(Value::Usize)),
+            try_from_integer(i8::try_from($v).map(Value::I8)),
+            try_from_integer(i16::try_from($v).map(Value::I16)),
+            try_from_integer(i32::try_from($v).map(Value::I32)),
+            try_from_integer(i64::try_from($v).map(Value::I64)),
+            try_from_integer(i128::try_from($v).map(Value::I128)),
+            try_from_integer(isize::try_from($v).map(Value::Isize)),
+        ]
+    };
+}
+
+fn try_from_integer<E: fmt::Display>(result: Result<Value, E>) -> TryFromResult {
+    match result {
+        Ok(value) => TryFromResult::Ok(value),
+        Err(error) => TryFromResult::Err(error.to_string()),
+    }
+}
+
+// `T::try_from(value)` for every primitive type `T`
+pub fn try_from_all(value: Value) -> Vec<TryFromResult> {
+    let casts = value.cast_all();
+    let mut results: Vec<TryFromResult> = match value {
+        Value::F32(_) | Value::F64(_) => vec![TryFromResult::NotImplemented; 12],
+        Value::U8(v) => try_from_integers!(v).to_vec(),
+        Value::U16(v) => try_from_integers!(v).to_vec(),
+        Value::U32(v) => try_from_integers!(v).to_vec(),
+        Value::U64(v) => try_from_integers!(v).to_vec(),
+        Value::U128(v) => try_from_integers!(v).to_vec(),
+        Value::Usize(v) => try_from_integers!(v).to_vec(),
+        Value::I8(v) => try_from_integers!(v).to_vec(),
+        Value::I16(v) => try_from_integers!(v).to_vec(),
+        Value::I32(v) => try_from_integers!(v).to_vec(),
+        Value::I64(v) => try_from_integers!(v).to_vec(),
+        Value::I128(v) => try_from_integers!(v).to_vec(),
+        Value::Isize(v) => try_from_integers!(v).to_vec(),
+    };
+    // Conversions into floats only exist when they are lossless, in which
+    // case they come from the blanket `TryFrom` impl over `From` and
+    // always succeed with the same value as `as`.
+    for &target in &casts[12..] {
+        results.push(if has_lossless_from(value, target) {
+            TryFromResult::Ok(target)
+        } else {
+            TryFromResult::NotImplemented
+        });
+    }
+    results
+}
+
+fn exact(value: Value) -> Exact {
+    match value {
+        Value::F32(v) => Exact::from_float(v as f64),
+        Value::F64(v) => Exact::from_float(v),
+        _ => {
+            let (negative, magnitude) = value.sign_and_magnitude();
+            Exact::Integer {
+                negative,
+                magnitude,
+            }
+        }
+    }
+}
+
+fn truncated(value: Value) -> Value {
+    match value {
+        Value::F32(v) => Value::F32(v.trunc()),
+        Value::F64(v) => Value::F64(v.trunc()),
+        _ => value,
+    }
+}
+
+impl Exact {
+    fn from_float(v: f64) -> Exact {
+        if v.is_nan() {
+            Exact::NaN
+        } else if v.is_infinite() {
+            Exact::Infinity { negative: v < 0.0 }
+        } else if v.fract() == 0.0 && v.abs() < 2f64.powi(128) {
+            Exact::Integer {
+                negative: v < 0.0 && v != 0.0,
+                magnitude: v.abs() as u128,
+            }
+        } else {
+            Exact::Float(v)
+        }
+    }
+
+    fn is_negative(self) -> bool {
+        match self {
+            Exact::Integer { negative, .. } | Exact::Infinity { negative } => negative,
+            Exact::Float(v) => v < 0.0,
+            Exact::NaN => false,
+        }
+    }
+}
+
+// `From` impls between numeric types exist only for lossless conversions
+fn has_lossless_from(source: Value, target: Value) -> bool {
+    let (from, to) = (source.type_name(), target.type_name());
+    match to {
+        "f32" => matches!(from, "u8" | "u16" | "i8" | "i16" | "f32"),
+        "f64" => matches!(
+            from,
+            "u8" | "u16" | "u32" | "i8" | "i16" | "i32" | "f32" | "f64"
+        ),
+        _ => false,
+    }
+}
+
+// Describe what happened to `source` when it was cast into `result`
+pub fn explain(source: Value, result: Value) -> String {
+    let (before, after) = (exact(source), exact(result));
+
+    match (source.is_float(), result.is_float()) {
+        // int -> int
+        (false, false) => {
+            if before == after {
+                if source.is_signed() && before.is_negative() && result.bits() > source.bits() {
+                    "value preserved (sign-extended)".to_owned()
+                } else {
+                    "value preserved".to_owned()
+                }
+            } else {
+                let mut notes = Vec::new();
+                if result.bits() < source.bits() {
+                    notes.push(format!("truncated to the low {} bits", result.bits()));
+                } else if result.bits() > source.bits() {
+                    notes.push("sign-extended".to_owned());
+                }
+                if before.is_negative() != after.is_negative() {
+                    notes.push("sign reinterpreted".to_owned());
+                }
+                notes.join(", ")
+            }
+        }
+        // float -> int
+        (true, false) => {
+            if before == Exact::NaN {
+                "NaN becomes 0".to_owned()
+            } else if exact(truncated(source)) != after {
+                let bound = if before.is_negative() { "MIN" } else { "MAX" };
+                format!("saturated to {}::{}", result.type_name(), bound)
+            } else if before != after {
+                "fraction discarded (rounded toward zero)".to_owned()
+            } else {
+                "value preserved".to_owned()
+            }
+        }
+        // int -> float, float -> float
+        (_, true) => match after {
+            Exact::NaN => "NaN stays NaN".to_owned(),
+            _ if before == after => "value preserved".to_owned(),
+            Exact::Infinity { .. } => "overflowed to infinity".to_owned(),
+            Exact::Integer { magnitude: 0, .. } => "underflowed to zero".to_owned(),
+            _ => format!("rounded to the nearest {}", result.type_name()),
+        },
+    }
+}
+
+// A numeric literal, or one of the constants of a primitive type
+fn parse(input: &str) -> Result<Value, String> {
+    if let Some(index) = input.find("::") {
+        let (ty, constant) = (&input[..index], &input[index + 2..]);
+        return constant_value(ty, constant).ok_or_else(|| format!("unknown constant `{}`", input));
+    }
+    literal::lex(input)
+        .map(|literal| literal.value)
+        .map_err(|error| match error.note() {
+            Some(note) => format!("{} ({})", error, note),
+            None => error.to_string(),
+        })
+}
+
+fn constant_value(ty: &str, constant: &str) -> Option<Value> {
+    macro_rules! constants {
+        ($($name:ident => $variant:ident),*) => {
+            match (ty, constant) {
+                $(
+                    (stringify!($name), "MIN") => Some(Value::$variant($name::MIN)),
+                    (stringify!($name), "MAX") => Some(Value::$variant($name::MAX)),
+                )*
+                ("f32", "NAN") => Some(Value::F32(f32::NAN)),
+                ("f32", "INFINITY") => Some(Value::F32(f32::INFINITY)),
+                ("f32", "NEG_INFINITY") => Some(Value::F32(f32::NEG_INFINITY)),
+                ("f64", "NAN") => Some(Value::F64(f64::NAN)),
+                ("f64", "INFINITY") => Some(Value::F64(f64::INFINITY)),
+                ("f64", "NEG_INFINITY") => Some(Value::F64(f64::NEG_INFINITY)),
+                _ => None,
+            }
+        };
+    }
+    constants!(
+        u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128, usize => Usize,
+        i8 => I8, i16 => I16, i32 => I32, i64 => I64, i128 => I128, isize => Isize,
+        f32 => F32, f64 => F64
+    )
+}
@@ -1,10 +1,17 @@
-pub fn main() -> () {
-    from();
-    into_using_from();
-    into();
-    try_from();
-    to_string();
-    parse_string();
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::allocations;
+use crate::error::Error;
+
+pub fn main() -> Result<(), Error> {
+    allocations::section("from", from);
+    allocations::section("into_using_from", into_using_from);
+    allocations::section("into", into);
+    allocations::section("try_from", try_from)?;
+    allocations::section("to_string", to_string);
+    allocations::section("parse_string", parse_string)?;
+    Ok(())
 }
 
 fn from() -> () {
@@ -44,85 +51,168 @@ fn into_using_from() -> () {
     println!("My number is {:?}", num);
 }
 
-fn into() -> () {
-    #[derive(Debug)]
-    struct Number {
-        value: i32,
-    }
+#[derive(Debug)]
+pub(crate) struct Number {
+    pub(crate) value: i32,
+}
 
-    impl From<i32> for Number {
-        fn from(item: i32) -> Self {
-            Number { value: item }
-        }
-    }
+#[derive(Debug)]
+pub(crate) struct Numero {
+    pub(crate) valeur: i32,
+}
 
-    #[derive(Debug)]
-    struct Numero {
-        valeur: i32,
+impl From<i32> for Number {
+    fn from(item: i32) -> Self {
+        Number { value: item }
     }
+}
 
-    impl Into<Numero> for Number {
-        fn into(self) -> Numero {
-            return Numero { valeur: self.value };
-        }
+impl Into<Numero> for Number {
+    fn into(self) -> Numero {
+        return Numero { valeur: self.value };
     }
+}
 
+fn into() -> () {
     let num = Number::from(30);
     let numero: Numero = num.into();
     println!("My numero is {:?}", numero);
+
+    // `crate::words` translates for real: the value stays the same, the
+    // words change
+    use crate::words::{self, Words};
+    let number = words::Number { value: 30 };
+    let numero = words::Numero::from(number);
+    println!("{} is {}", Words::from(number), Words::from(numero));
+    for &value in &[71, 80, 81, 97, 200, 221, 80_000, 2_000_000, -1_234_567] {
+        println!(
+            "{}: {} / {}",
+            value,
+            Words::english(value),
+            Words::french(value)
+        );
+    }
+    if let Ok(numero) = words::Numero::try_from("quatre-vingt-dix-sept") {
+        println!("{}", numero);
+    }
+    for text in ["ninety-seven", "vingt-et-un", "quatre-vingt", "one two"] {
+        match text.parse::<Words>() {
+            Ok(words) => println!("{:?} is {}", text, words.value),
+            Err(error) => println!("{:?}: {}", text, error),
+        }
+    }
 }
 
-fn try_from() -> () {
-    use std::convert::TryFrom;
-    use std::convert::TryInto;
+#[derive(Debug, PartialEq)]
+pub(crate) struct EvenNumber(pub(crate) i32);
+
+// The error says which number was rejected, and why
+#[derive(Debug, PartialEq)]
+pub(crate) struct OddNumber(pub(crate) i32);
 
-    #[derive(Debug, PartialEq)]
-    struct EvenNumber(i32);
-    impl TryFrom<i32> for EvenNumber {
-        type Error = ();
-        fn try_from(value: i32) -> Result<Self, Self::Error> {
-            if value % 2 == 0 {
-                Ok(EvenNumber(value))
-            } else {
-                Err(())
-            }
+impl fmt::Display for OddNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is odd", self.0)
+    }
+}
+
+impl std::error::Error for OddNumber {}
+
+impl TryFrom<i32> for EvenNumber {
+    type Error = OddNumber;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        if value % 2 == 0 {
+            Ok(EvenNumber(value))
+        } else {
+            Err(OddNumber(value))
         }
     }
+}
+
+fn try_from() -> Result<(), Error> {
+    use std::convert::TryInto;
 
     // TryFrom
 
     assert_eq!(EvenNumber::try_from(8), Ok(EvenNumber(8)));
-    assert_eq!(EvenNumber::try_from(5), Err(()));
+    assert_eq!(EvenNumber::try_from(5), Err(OddNumber(5)));
 
     // TryInto
 
-    let result: Result<EvenNumber, ()> = 8i32.try_into();
+    let result: Result<EvenNumber, OddNumber> = 8i32.try_into();
     assert_eq!(result, Ok(EvenNumber(8)));
-    let result: Result<EvenNumber, ()> = 5i32.try_into();
-    assert_eq!(result, Err(()));
-}
-
-fn to_string() -> () {
-    use std::fmt;
-
-    struct Circle {
-        radius: i32,
+    let result: Result<EvenNumber, OddNumber> = 5i32.try_into();
+    assert_eq!(result, Err(OddNumber(5)));
+
+    // `crate::refined` generalizes `EvenNumber` to any constraint, with
+    // errors that explain what went wrong.
+    use crate::refined::{Bounded, Even, NonZero, Percentage, Positive, Refinement};
+
+    let even = Even::try_from(8)?;
+    // `Deref` gives access to the inner `i32`
+    println!("{} is even, and so is {}", even, *even + 2);
+    let n: i32 = even.into_inner();
+    println!("{} unwrapped", n);
+    let result: Result<Even, _> = 5i32.try_into();
+    if let Err(error) = result {
+        println!("error: {}", error);
     }
 
-    impl fmt::Display for Circle {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "Circle of radius {}", self.radius)
+    for text in ["42", "-3", "0", "many"] {
+        match text.parse::<Positive>() {
+            Ok(n) => println!("{} is positive", n),
+            Err(error) => println!("error: {}", error),
         }
     }
+    if let Err(error) = NonZero::try_from(0) {
+        println!("error: {}", error);
+    }
+    match "250".parse::<Bounded<1, 100>>() {
+        Ok(n) => println!("{} is in range", n),
+        Err(error) => println!("error: {}", error),
+    }
+    let progress: Percentage = "99.5".parse()?;
+    println!("{}% done", progress);
+    if let Err(error) = Percentage::try_from(f64::NAN) {
+        println!("error: {}", error);
+    }
+    Ok(())
+}
+
+pub(crate) struct Circle {
+    pub(crate) radius: i32,
+}
+
+impl fmt::Display for Circle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Circle of radius {}", self.radius)
+    }
+}
 
+fn to_string() -> () {
     let circle = Circle { radius: 6 };
     println!("{}", circle.to_string());
 }
 
-fn parse_string() -> () {
-    let parsed: i32 = "5".parse().unwrap();
-    let turbo_parsed = "10".parse::<i32>().unwrap();
+fn parse_string() -> Result<(), Error> {
+    use crate::calculator::Calculator;
+
+    let parsed: i32 = "5".parse().map_err(|error| Error::parse("5", error))?;
+    let turbo_parsed = "10"
+        .parse::<i32>()
+        .map_err(|error| Error::parse("10", error))?;
 
     let sum = parsed + turbo_parsed;
     println!("Sum: {:?}", sum);
+
+    // `crate::calculator` parses whole expressions, see `BINARY=calc`
+    let mut calculator = Calculator::new();
+    let sum = calculator
+        .evaluate("5 + 10")
+        .map_err(|error| Error::parse("5 + 10", error))?;
+    println!("5 + 10 = {}", sum);
+    if let Err(error) = calculator.evaluate("5 + ten") {
+        println!("{}", error.render("5 + ten"));
+    }
+    Ok(())
 }
@@ -0,0 +1,55 @@
+use std::env;
+
+use crate::convert::{self, Conversion};
+use crate::error::Error;
+
+// Parses a value of any type of the `convert` registry and prints it back
+// through `Display` and `Debug`:
+//
+//     BINARY=convert cargo run -- color "rgb(128, 255, 90)"
+//     BINARY=convert cargo run -- numero 80
+//
+// Without arguments, it converts the example of every type.
+pub fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (name, input) = match args.split_first() {
+        None => {
+            for conversion in convert::registry() {
+                show(&conversion, conversion.example)?;
+            }
+            return Ok(());
+        }
+        Some((name, rest)) if !rest.is_empty() => (name, rest.join(" ")),
+        Some(_) => return Err(Error::Usage(usage())),
+    };
+
+    let conversion = match convert::find(name) {
+        Some(conversion) => conversion,
+        None => {
+            return Err(Error::Usage(format!(
+                "unknown type `{}`\n{}",
+                name,
+                usage()
+            )))
+        }
+    };
+    show(&conversion, &input)
+}
+
+fn show(conversion: &Conversion, input: &str) -> Result<(), Error> {
+    let converted = conversion
+        .convert(input)
+        .map_err(|error| Error::parse(input, error))?;
+    println!("{} {:?}", conversion.name, input);
+    println!("    Display: {}", converted.display);
+    println!("    Debug:   {}", converted.debug);
+    Ok(())
+}
+
+fn usage() -> String {
+    let names: Vec<&str> = convert::registry()
+        .iter()
+        .map(|conversion| conversion.name)
+        .collect();
+    format!("usage: convert <type> <value>, types: {}", names.join(", "))
+}
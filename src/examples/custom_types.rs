@@ -1,72 +1,80 @@
-pub fn main() -> () {
-    structures();
-    enums();
-    enums_linked_lists();
-    constants();
+use crate::allocations;
+use crate::error::Error;
+
+pub fn main() -> Result<(), Error> {
+    allocations::section("structures", structures);
+    allocations::section("shapes", shapes);
+    allocations::section("svg", svg);
+    allocations::section("canvas", canvas);
+    allocations::section("enums", enums);
+    allocations::section("enums_linked_lists", enums_linked_lists);
+    allocations::section("constants", constants);
+    Ok(())
 }
 
-fn structures() -> () {
-    #[derive(Debug)]
-    struct Person {
-        name: String,
-        age: u8,
-    }
+#[derive(Debug)]
+pub(crate) struct Person {
+    pub(crate) name: String,
+    pub(crate) age: u8,
+}
 
-    // A unit struct
-    #[derive(Debug)]
-    struct Unit;
+// A unit struct
+#[derive(Debug)]
+pub(crate) struct Unit;
 
-    // A tuple struct
-    struct Pair(i32, f32);
+// A tuple struct
+pub(crate) struct Pair(pub(crate) i32, pub(crate) f32);
 
-    // A struct with two fields
-    #[derive(Debug)]
-    struct Point {
-        x: f32,
-        y: f32,
-    }
+// A struct with two fields
+#[derive(Debug)]
+pub(crate) struct Point {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+}
 
-    // Structs can be reused as fields of another struct
-    #[derive(Debug)]
-    struct Rectangle {
-        // A rectangle can be specified by where the top left and bottom right
-        // corners are in space.
-        top_left: Point,
-        bottom_right: Point,
-    }
-    impl Rectangle {
-        fn area(&self) -> f32 {
-            let Rectangle {
-                top_left:
-                    Point {
-                        x: top_left_x,
-                        y: top_left_y,
-                    },
-                bottom_right:
-                    Point {
-                        x: bottom_right_x,
-                        y: bottom_right_y,
-                    },
-            } = self;
-            let width = bottom_right_x - top_left_x;
-            let height = top_left_y - bottom_right_y;
-
-            width * height
-        }
-        fn square(point: Point, size: f32) -> Rectangle {
-            Rectangle {
-                top_left: Point {
-                    x: point.x,
-                    y: point.y + size,
+// Structs can be reused as fields of another struct
+#[derive(Debug)]
+pub(crate) struct Rectangle {
+    // A rectangle can be specified by where the top left and bottom right
+    // corners are in space.
+    pub(crate) top_left: Point,
+    pub(crate) bottom_right: Point,
+}
+
+impl Rectangle {
+    fn area(&self) -> f32 {
+        let Rectangle {
+            top_left:
+                Point {
+                    x: top_left_x,
+                    y: top_left_y,
                 },
-                bottom_right: Point {
-                    x: point.x + size,
-                    y: point.y,
+            bottom_right:
+                Point {
+                    x: bottom_right_x,
+                    y: bottom_right_y,
                 },
-            }
+        } = self;
+        let width = bottom_right_x - top_left_x;
+        let height = top_left_y - bottom_right_y;
+
+        width * height
+    }
+    fn square(point: Point, size: f32) -> Rectangle {
+        Rectangle {
+            top_left: Point {
+                x: point.x,
+                y: point.y + size,
+            },
+            bottom_right: Point {
+                x: point.x + size,
+                y: point.y,
+            },
         }
     }
+}
 
+fn structures() -> () {
     // Create struct with field init shorthand
     let name = String::from("Peter");
     let age = 27;
@@ -128,22 +136,179 @@ fn structures() -> () {
     );
 }
 
-fn enums() -> () {
-    // Create an `enum` to classify a web event. Note how both
-    // names and type information together specify the variant:
-    // `PageLoad != PageUnload` and `KeyPress(char) != Paste(String)`.
-    // Each is different and independent.
-    enum WebEvent {
-        // An `enum` may either be `unit-like`,
-        PageLoad,
-        PageUnload,
-        // like tuple structs,
-        KeyPress(char),
-        Paste(String),
-        // or c-like structures.
-        Click { x: i64, y: i64 },
+// The rectangle above and the circle of the conversion example, along with
+// other shapes, behind the `Shape` trait
+fn shapes() {
+    use crate::shapes::{Circle, Ellipse, Point, Polygon, Rectangle, Shape, Triangle};
+
+    let mut shapes: Vec<Box<dyn Shape>> = vec![
+        Box::new(Rectangle::new(Point::new(1.0, 10.4), Point::new(10.3, 0.4))),
+        Box::new(Rectangle::square(Point::new(3.0, 3.0), 2.0)),
+        Box::new(Circle::new(Point::new(0.0, 0.0), 6.0)),
+        Box::new(Triangle::new(
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(0.0, 3.0),
+        )),
+        Box::new(Ellipse::new(Point::new(-2.0, 1.0), 3.0, 1.5)),
+        // An L made of three unit squares
+        Box::new(Polygon::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 0.0),
+            Point::new(2.0, 1.0),
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 2.0),
+            Point::new(0.0, 2.0),
+        ])),
+    ];
+
+    shapes.sort_by(|a, b| a.area().total_cmp(&b.area()));
+    for shape in &shapes {
+        let bounds = shape.bounding_box();
+        println!(
+            "{}: area {:.2}, perimeter {:.2}, {} x {} box",
+            shape,
+            shape.area(),
+            shape.perimeter(),
+            bounds.width(),
+            bounds.height()
+        );
     }
 
+    let origin = Point::new(0.0, 0.0);
+    let around_origin: Vec<String> = shapes
+        .iter()
+        .filter(|shape| shape.contains(origin))
+        .map(|shape| shape.to_string())
+        .collect();
+    println!("Containing {}: {:?}", origin, around_origin);
+
+    let large = shapes.iter().filter(|shape| shape.area() > 10.0).count();
+    println!("{} of {} shapes have an area over 10", large, shapes.len());
+    let total: f64 = shapes.iter().map(|shape| shape.area()).sum();
+    println!("Total area {:.2}", total);
+}
+
+// A few of the shapes above as an SVG image
+fn svg() {
+    print!("{}", scene());
+}
+
+// The image of `svg`, the one the tests of `crate::svg` compare with
+// `shapes.svg`
+pub(crate) fn scene() -> crate::svg::Scene {
+    use crate::convert::Color;
+    use crate::shapes::{Circle, Point, Rectangle, Triangle};
+    use crate::svg::{Scene, Style};
+
+    let orange = Color {
+        red: 255,
+        green: 128,
+        blue: 0,
+    };
+    let mut scene = Scene::new();
+    scene
+        .add(
+            Rectangle::new(Point::new(1.0, 10.4), Point::new(10.3, 0.4)),
+            Style::default(),
+        )
+        .add(
+            Rectangle::square(Point::new(3.0, 3.0), 2.0),
+            Style::fill(orange),
+        )
+        .add(
+            Circle::new(Point::new(0.0, 0.0), 6.0),
+            Style {
+                stroke_width: 0.5,
+                ..Style::stroke(orange)
+            },
+        )
+        .add(
+            Triangle::new(
+                Point::new(0.0, 0.0),
+                Point::new(4.0, 0.0),
+                Point::new(0.0, 3.0),
+            ),
+            Style::fill(Color {
+                red: 128,
+                green: 255,
+                blue: 90,
+            }),
+        );
+    scene
+}
+
+// The rectangle and the square of `structures`, drawn in the terminal
+fn canvas() {
+    use crate::canvas::{Canvas, Resolution};
+    use crate::shapes::{Circle, Point, Rectangle, Shape};
+
+    // Cells are twice as tall as they are wide, so this diagonal climbs a
+    // row every two columns
+    let mut small = Canvas::new(4, 2, Resolution::Cells);
+    small.line(Point::new(0.0, 0.0), Point::new(3.0, 3.0));
+    print!("{}", small);
+
+    let rect = Rectangle::new(Point::new(1.0, 10.4), Point::new(10.3, 0.4));
+    let square = Rectangle::square(Point::new(3.0, 3.0), 2.0);
+    let circle = Circle::new(Point::new(7.0, 7.0), 2.0);
+    let bounds = rect.bounding_box();
+
+    for resolution in [Resolution::Cells, Resolution::Braille] {
+        let mut canvas = Canvas::fitting(&bounds, 24, resolution);
+        canvas.shape(&rect);
+        canvas.fill(&square);
+        canvas.shape(&circle);
+        canvas.point(Point::new(2.0, 9.0));
+        println!("{:?}:", resolution);
+        print!("{}", canvas);
+    }
+}
+
+// Create an `enum` to classify a web event. Note how both
+// names and type information together specify the variant:
+// `PageLoad != PageUnload` and `KeyPress(char) != Paste(String)`.
+// Each is different and independent.
+pub(crate) enum WebEvent {
+    // An `enum` may either be `unit-like`,
+    PageLoad,
+    PageUnload,
+    // like tuple structs,
+    KeyPress(char),
+    Paste(String),
+    // or c-like structures.
+    Click { x: i64, y: i64 },
+}
+
+pub(crate) enum Status {
+    #[allow(dead_code)]
+    Rich,
+    Poor,
+}
+
+pub(crate) enum Work {
+    Civilian,
+    #[allow(dead_code)]
+    Soldier,
+}
+
+// enum with implicit discriminator (starts at 0)
+pub(crate) enum Number {
+    Zero,
+    One,
+    #[allow(dead_code)]
+    Two,
+}
+
+// enum with explicit discriminator
+pub(crate) enum Color {
+    Red = 0xff0000,
+    #[allow(dead_code)]
+    Green = 0x00ff00,
+    Blue = 0x0000ff,
+}
+
+fn enums() -> () {
     // A function which takes a `WebEvent` enum as an argument and
     // returns nothing.
     fn inspect(event: WebEvent) {
@@ -173,17 +338,6 @@ fn enums() -> () {
     inspect(load);
     inspect(unload);
 
-    enum Status {
-        #[allow(dead_code)]
-        Rich,
-        Poor,
-    }
-    enum Work {
-        Civilian,
-        #[allow(dead_code)]
-        Soldier,
-    }
-
     // Explicitly `use` each name so they are available without
     // manual scoping.
     use Status::{Poor, Rich};
@@ -207,22 +361,6 @@ fn enums() -> () {
         Soldier => println!("Soldiers fight!"),
     }
 
-    // enum with implicit discriminator (starts at 0)
-    enum Number {
-        Zero,
-        One,
-        #[allow(dead_code)]
-        Two,
-    }
-
-    // enum with explicit discriminator
-    enum Color {
-        Red = 0xff0000,
-        #[allow(dead_code)]
-        Green = 0x00ff00,
-        Blue = 0x0000ff,
-    }
-
     // `enums` can be cast as integers.
     println!("zero is {}", Number::Zero as i32);
     println!("one is {}", Number::One as i32);
@@ -231,84 +369,89 @@ fn enums() -> () {
     println!("violets are #{:06x}", Color::Blue as i32);
 }
 
-fn enums_linked_lists() -> () {
-    enum List {
-        // Cons: Tuple struct that wraps an element and a pointer to the next node
-        Cons(u32, Box<List>),
-        // Nil: A node that signifies the end of the linked list
-        Nil,
+pub(crate) enum List {
+    // Cons: Tuple struct that wraps an element and a pointer to the next node
+    Cons(u32, Box<List>),
+    // Nil: A node that signifies the end of the linked list
+    Nil,
+}
+use List::*;
+
+impl List {
+    // Create an empty list
+    fn new() -> List {
+        // `Nil` has type `List`
+        Nil
     }
-    use List::*;
-    impl List {
-        // Create an empty list
-        fn new() -> List {
-            // `Nil` has type `List`
-            Nil
-        }
 
-        // Consume a list, and return the same list with a new element at its front
-        fn prepend(self, elem: u32) -> List {
-            // `Cons` also has type List
-            Cons(elem, Box::new(self))
-        }
+    // Consume a list, and return the same list with a new element at its front
+    fn prepend(self, elem: u32) -> List {
+        // `Cons` also has type List
+        Cons(elem, Box::new(self))
+    }
 
-        // Consume a list, and return the same list with a new element at its end
-        fn append(self, element: u32) -> List {
-            match self {
-                Cons(e, tail) => Cons(e, Box::new(tail.append(element))),
-                Nil => Cons(element, Box::new(Nil)),
-            }
+    // Consume a list, and return the same list with a new element at its end
+    fn append(self, element: u32) -> List {
+        match self {
+            Cons(e, tail) => Cons(e, Box::new(tail.append(element))),
+            Nil => Cons(element, Box::new(Nil)),
         }
+    }
 
-        // Return the length of the list
-        fn len(&self) -> u32 {
-            // `self` has to be matched, because the behavior of this method
-            // depends on the variant of `self`
-            // `self` has type `&List`, and `*self` has type `List`, matching on a
-            // concrete type `T` is preferred over a match on a reference `&T`
-            match self {
-                // Can't take ownership of the tail, because `self` is borrowed;
-                // instead take a reference to the tail
-                Cons(_, ref tail) => 1 + tail.len(),
-                // Base Case: An empty list has zero length
-                Nil => 0,
-            }
+    // Return the length of the list
+    fn len(&self) -> u32 {
+        // `self` has to be matched, because the behavior of this method
+        // depends on the variant of `self`
+        // `self` has type `&List`, and `*self` has type `List`, matching on a
+        // concrete type `T` is preferred over a match on a reference `&T`
+        match self {
+            // Can't take ownership of the tail, because `self` is borrowed;
+            // instead take a reference to the tail
+            Cons(_, ref tail) => 1 + tail.len(),
+            // Base Case: An empty list has zero length
+            Nil => 0,
         }
+    }
 
-        // Return representation of the list as a (heap allocated) string
-        fn stringify(&self) -> String {
-            match *self {
-                Cons(head, ref tail) => {
-                    // `format!` is similar to `print!`, but returns a heap
-                    // allocated string instead of printing to the console
-                    format!("{}, {}", head, tail.stringify())
-                }
-                Nil => {
-                    format!("Nil")
-                }
+    // Return representation of the list as a (heap allocated) string
+    fn stringify(&self) -> String {
+        match *self {
+            Cons(head, ref tail) => {
+                // `format!` is similar to `print!`, but returns a heap
+                // allocated string instead of printing to the console
+                format!("{}, {}", head, tail.stringify())
+            }
+            Nil => {
+                format!("Nil")
             }
         }
     }
+}
 
-    // Create an empty linked list
-    let mut list = List::new();
-
-    // Prepend some elements
-    list = list.prepend(1);
-    list = list.prepend(2);
-    list = list.prepend(3);
+fn enums_linked_lists() -> () {
+    // Create an empty linked list, and prepend some elements. Each `prepend`
+    // allocates a single node.
+    let list = allocations::section("prepend", || {
+        let mut list = List::new();
+        list = list.prepend(1);
+        list = list.prepend(2);
+        list = list.prepend(3);
+        list
+    });
 
     // Show the final state of the list
     println!("linked list has length: {}", list.len());
     println!("{}", list.stringify());
 
-    // Create an empty linked list
-    let mut list = List::new();
-
-    // Append some elements
-    list = list.append(1);
-    list = list.append(2);
-    list = list.append(3);
+    // Create an empty linked list, and append some elements. Each `append`
+    // moves every node out of its box and into a new one on the way down.
+    let list = allocations::section("append", || {
+        let mut list = List::new();
+        list = list.append(1);
+        list = list.append(2);
+        list = list.append(3);
+        list
+    });
 
     // Show the final state of the list
     println!("linked list has length: {}", list.len());
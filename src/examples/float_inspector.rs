@@ -0,0 +1,643 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::env;
+use std::fmt;
+
+use crate::error::Error;
+use crate::literal::{self, Literal, Radix, Value};
+
+// Takes `f32`/`f64` values apart: the bits of the sign, exponent and
+// mantissa, what kind of value they encode, the exact decimal value stored
+// and its closest neighbours.
+//
+//     BINARY=float_inspector cargo run -- 65.4321_f32 1_000.05 NaN
+//     BINARY=float_inspector cargo run -- f32:0x7fc00001
+//
+// Unsuffixed values are `f64`, like in Rust. `f32:<hex>` and `f64:<hex>`
+// inspect raw bits, to look at NaN payloads for instance.
+pub fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args = if args.is_empty() {
+        // The floats used by the types and primitives examples
+        vec![
+            "65.4321_f32".to_owned(),
+            "3f32".to_owned(),
+            "1_000.05f64".to_owned(),
+            "0.1".to_owned(),
+            "NaN".to_owned(),
+            "f32:0x00000001".to_owned(),
+        ]
+    } else {
+        args
+    };
+
+    for arg in args {
+        match parse(&arg) {
+            Ok(Input::F32(value, literal)) => inspect(value, literal.as_ref()),
+            Ok(Input::F64(value, literal)) => inspect(value, literal.as_ref()),
+            Err(error) => println!("{}: {}", arg, error),
+        }
+        println!();
+    }
+    Ok(())
+}
+
+enum Input {
+    // The value, and the literal it was written as if any
+    F32(f32, Option<Literal>),
+    F64(f64, Option<Literal>),
+}
+
+fn parse(arg: &str) -> Result<Input, String> {
+    if let Some(hex) = arg.strip_prefix("f32:0x") {
+        let bits = u32::from_str_radix(hex, 16).map_err(|e| e.to_string())?;
+        return Ok(Input::F32(f32::from_bits(bits), None));
+    }
+    if let Some(hex) = arg.strip_prefix("f64:0x") {
+        let bits = u64::from_str_radix(hex, 16).map_err(|e| e.to_string())?;
+        return Ok(Input::F64(f64::from_bits(bits), None));
+    }
+    // `NaN` and `inf` aren't literals, but are worth a look
+    if arg.trim_start_matches('-').starts_with(char::is_alphabetic) {
+        let value = arg.parse::<f64>().map_err(|e| e.to_string())?;
+        return Ok(Input::F64(value, None));
+    }
+
+    let literal = literal::lex(arg).map_err(|error| match error.note() {
+        Some(note) => format!("{}: {}", error, note),
+        None => error.to_string(),
+    })?;
+    match literal.value {
+        Value::F32(value) => Ok(Input::F32(value, Some(literal))),
+        Value::F64(value) => Ok(Input::F64(value, Some(literal))),
+        // `3` would be an `i32` to rustc, but here it is the `f64` it
+        // becomes where a float is expected
+        _ if !literal.suffixed && literal.radix == Radix::Decimal => {
+            let value = literal.digits.parse().map_err(|_| "not a float")?;
+            Ok(Input::F64(value, Some(literal)))
+        }
+        value => Err(format!("expected a float, found `{}`", value.type_name())),
+    }
+}
+
+// What the inspector needs to know about `f32` and `f64`
+pub trait Float: Copy + PartialEq + fmt::Debug {
+    const NAME: &'static str;
+    const EXPONENT_BITS: u32;
+    const MANTISSA_BITS: u32;
+
+    fn bits(self) -> u64;
+    fn next_up(self) -> Self;
+    fn next_down(self) -> Self;
+    fn is_finite(self) -> bool;
+
+    fn bias() -> i32 {
+        (1 << (Self::EXPONENT_BITS - 1)) - 1
+    }
+}
+
+impl Float for f32 {
+    const NAME: &'static str = "f32";
+    const EXPONENT_BITS: u32 = 8;
+    const MANTISSA_BITS: u32 = 23;
+
+    fn bits(self) -> u64 {
+        self.to_bits() as u64
+    }
+    fn next_up(self) -> Self {
+        f32::next_up(self)
+    }
+    fn next_down(self) -> Self {
+        f32::next_down(self)
+    }
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+}
+
+impl Float for f64 {
+    const NAME: &'static str = "f64";
+    const EXPONENT_BITS: u32 = 11;
+    const MANTISSA_BITS: u32 = 52;
+
+    fn bits(self) -> u64 {
+        self.to_bits()
+    }
+    fn next_up(self) -> Self {
+        f64::next_up(self)
+    }
+    fn next_down(self) -> Self {
+        f64::next_down(self)
+    }
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+}
+
+// The three fields of an IEEE-754 binary float
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fields {
+    pub negative: bool,
+    pub exponent: u64,
+    pub mantissa: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    Zero,
+    Subnormal,
+    Normal,
+    Infinite,
+    // Quiet NaNs have the top mantissa bit set; the rest is the payload
+    NaN { quiet: bool, payload: u64 },
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Class::Zero => write!(f, "zero"),
+            Class::Subnormal => write!(f, "subnormal (no implicit leading 1)"),
+            Class::Normal => write!(f, "normal"),
+            Class::Infinite => write!(f, "infinite"),
+            Class::NaN { quiet, payload } => write!(
+                f,
+                "NaN ({}, payload {:#x})",
+                if *quiet { "quiet" } else { "signaling" },
+                payload
+            ),
+        }
+    }
+}
+
+pub fn fields<F: Float>(value: F) -> Fields {
+    let bits = value.bits();
+    Fields {
+        negative: bits >> (F::EXPONENT_BITS + F::MANTISSA_BITS) == 1,
+        exponent: (bits >> F::MANTISSA_BITS) & ((1 << F::EXPONENT_BITS) - 1),
+        mantissa: bits & ((1 << F::MANTISSA_BITS) - 1),
+    }
+}
+
+pub fn classify<F: Float>(value: F) -> Class {
+    let Fields {
+        exponent, mantissa, ..
+    } = fields(value);
+    let max_exponent = (1 << F::EXPONENT_BITS) - 1;
+    match (exponent, mantissa) {
+        (0, 0) => Class::Zero,
+        (0, _) => Class::Subnormal,
+        (e, 0) if e == max_exponent => Class::Infinite,
+        (e, m) if e == max_exponent => {
+            let quiet_bit = 1 << (F::MANTISSA_BITS - 1);
+            Class::NaN {
+                quiet: m & quiet_bit != 0,
+                payload: m & (quiet_bit - 1),
+            }
+        }
+        _ => Class::Normal,
+    }
+}
+
+// A finite float is exactly `significand × 2^exponent`
+pub fn significand_and_exponent<F: Float>(value: F) -> (u64, i32) {
+    let Fields {
+        exponent, mantissa, ..
+    } = fields(value);
+    let shift = F::bias() + F::MANTISSA_BITS as i32;
+    if exponent == 0 {
+        (mantissa, 1 - shift)
+    } else {
+        (mantissa | 1 << F::MANTISSA_BITS, exponent as i32 - shift)
+    }
+}
+
+// The exact value of a finite float, without rounding
+pub fn exact_value<F: Float>(value: F) -> Decimal {
+    let (significand, exponent) = significand_and_exponent(value);
+    let mut digits = BigUint::from_u64(significand);
+    if exponent >= 0 {
+        for _ in 0..exponent {
+            digits.mul_small(2);
+        }
+        Decimal {
+            negative: fields(value).negative,
+            digits,
+            scale: 0,
+        }
+    } else {
+        // m × 2^-k = m × 5^k / 10^k
+        for _ in 0..-exponent {
+            digits.mul_small(5);
+        }
+        Decimal {
+            negative: fields(value).negative,
+            digits,
+            scale: -exponent as u32,
+        }
+        .normalized()
+    }
+}
+
+fn inspect<F: Float>(value: F, literal: Option<&Literal>) {
+    let Fields {
+        negative,
+        exponent,
+        mantissa,
+    } = fields(value);
+    let class = classify(value);
+
+    println!("{:?}_{}", value, F::NAME);
+    println!(
+        "  bits        {} {:0e$b} {:0m$b}",
+        negative as u8,
+        exponent,
+        mantissa,
+        e = F::EXPONENT_BITS as usize,
+        m = F::MANTISSA_BITS as usize
+    );
+    println!("  sign        {}", if negative { "-" } else { "+" });
+    match class {
+        Class::Normal => println!(
+            "  exponent    {} - {} = {}",
+            exponent,
+            F::bias(),
+            exponent as i32 - F::bias()
+        ),
+        Class::Subnormal => println!(
+            "  exponent    0, read as 1 - {} = {}",
+            F::bias(),
+            1 - F::bias()
+        ),
+        _ => println!("  exponent    {}", exponent),
+    }
+    let leading = if class == Class::Normal { "1" } else { "0" };
+    println!(
+        "  mantissa    {}.{:0m$b}",
+        leading,
+        mantissa,
+        m = F::MANTISSA_BITS as usize
+    );
+    println!("  class       {}", class);
+
+    if !value.is_finite() {
+        return;
+    }
+    let (significand, power) = significand_and_exponent(value);
+    let exact = exact_value(value);
+    println!("  exactly     {} × 2^{} = {}", significand, power, exact);
+
+    let (down, up) = (value.next_down(), value.next_up());
+    println!("  next down   {:?} ({})", down, describe_neighbour(down));
+    println!("  next up     {:?} ({})", up, describe_neighbour(up));
+
+    if let Some(literal) = literal {
+        explain(literal, &exact, F::MANTISSA_BITS);
+    }
+}
+
+fn describe_neighbour<F: Float>(value: F) -> String {
+    if value.is_finite() {
+        format!("exactly {}", exact_value(value))
+    } else {
+        "not finite".to_owned()
+    }
+}
+
+// Why the float doesn't hold the literal it was written as
+fn explain(literal: &Literal, exact: &Decimal, mantissa_bits: u32) {
+    let written = match Decimal::from_literal(literal) {
+        Some(decimal) => decimal.normalized(),
+        None => return,
+    };
+    if &written == exact {
+        println!("  {} is stored exactly", literal);
+        return;
+    }
+
+    // A decimal fraction `n / 10^k` has a finite binary expansion only if,
+    // once reduced, its denominator is a power of two: all the factors 5 of
+    // 10^k must cancel out with the numerator.
+    let mut numerator = written.digits.clone();
+    let mut fives = written.scale;
+    while fives > 0 && numerator.div_small(5).1 == 0 {
+        numerator = numerator.div_small(5).0;
+        fives -= 1;
+    }
+    if fives > 0 {
+        println!(
+            "  {} has no finite binary expansion: as a fraction its denominator keeps a factor 5^{},",
+            literal, fives
+        );
+        println!(
+            "              so it is rounded to the nearest value with {} significant bits",
+            mantissa_bits + 1
+        );
+    } else {
+        println!(
+            "  {} needs more than {} significant bits, it is rounded to the nearest float",
+            literal,
+            mantissa_bits + 1
+        );
+    }
+    println!("  error       {}", exact.sub(&written));
+}
+
+// An arbitrary precision unsigned integer, stored in base 10^9 with the least
+// significant digit first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint(Vec<u32>);
+
+const BASE: u64 = 1_000_000_000;
+
+impl BigUint {
+    fn from_u64(mut value: u64) -> BigUint {
+        let mut digits = Vec::new();
+        while value > 0 {
+            digits.push((value % BASE) as u32);
+            value /= BASE;
+        }
+        BigUint(digits)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn mul_small(&mut self, factor: u32) {
+        let mut carry = 0u64;
+        for digit in self.0.iter_mut() {
+            let product = *digit as u64 * factor as u64 + carry;
+            *digit = (product % BASE) as u32;
+            carry = product / BASE;
+        }
+        if carry > 0 {
+            self.0.push(carry as u32);
+        }
+        self.trim();
+    }
+
+    // Quotient and remainder
+    fn div_small(&self, divisor: u32) -> (BigUint, u32) {
+        let mut quotient = vec![0; self.0.len()];
+        let mut remainder = 0u64;
+        for (i, &digit) in self.0.iter().enumerate().rev() {
+            let current = remainder * BASE + digit as u64;
+            quotient[i] = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        let mut quotient = BigUint(quotient);
+        quotient.trim();
+        (quotient, remainder as u32)
+    }
+
+    fn add(&self, other: &BigUint) -> BigUint {
+        let mut digits = Vec::new();
+        let mut carry = 0;
+        for i in 0..self.0.len().max(other.0.len()) {
+            let sum =
+                *self.0.get(i).unwrap_or(&0) as u64 + *other.0.get(i).unwrap_or(&0) as u64 + carry;
+            digits.push((sum % BASE) as u32);
+            carry = sum / BASE;
+        }
+        if carry > 0 {
+            digits.push(carry as u32);
+        }
+        BigUint(digits)
+    }
+
+    // `self - other`, which must not be negative
+    fn sub(&self, other: &BigUint) -> BigUint {
+        let mut digits = Vec::new();
+        let mut borrow = 0i64;
+        for i in 0..self.0.len() {
+            let mut difference = self.0[i] as i64 - *other.0.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = if difference < 0 {
+                difference += BASE as i64;
+                1
+            } else {
+                0
+            };
+            digits.push(difference as u32);
+        }
+        let mut result = BigUint(digits);
+        result.trim();
+        result
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.split_last() {
+            None => write!(f, "0"),
+            Some((most_significant, rest)) => {
+                write!(f, "{}", most_significant)?;
+                for digit in rest.iter().rev() {
+                    write!(f, "{:09}", digit)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// An exact decimal number: `±digits / 10^scale`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decimal {
+    negative: bool,
+    digits: BigUint,
+    scale: u32,
+}
+
+impl Decimal {
+    // The exact value of a decimal literal, `123.456`, `-1e-5` or
+    // `1_000.05f32`, as written rather than as stored
+    pub fn from_literal(literal: &Literal) -> Option<Decimal> {
+        if literal.radix != Radix::Decimal {
+            return None;
+        }
+        let negative = literal.text.starts_with('-');
+        let (mantissa, exponent) = match literal.digits.split_once('e') {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+            None => (literal.digits.as_str(), 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        let mut digits = BigUint(Vec::new());
+        for c in integer.chars().chain(fraction.chars()) {
+            digits.mul_small(10);
+            digits = digits.add(&BigUint::from_u64(c.to_digit(10)? as u64));
+        }
+        let mut scale = i32::try_from(fraction.len()).ok()?.checked_sub(exponent)?;
+
+        // Past about 10^±400 an `f64` is infinite or zero, and the digits
+        // would take forever to write out
+        let significant = integer
+            .chars()
+            .chain(fraction.chars())
+            .skip_while(|&c| c == '0')
+            .count();
+        if significant == 0 {
+            scale = 0;
+        }
+        let magnitude = i32::try_from(significant).ok()?.checked_sub(scale)?;
+        if !(-400..=400).contains(&magnitude) {
+            return None;
+        }
+        while scale < 0 {
+            digits.mul_small(10);
+            scale += 1;
+        }
+        Some(Decimal {
+            negative,
+            digits,
+            scale: scale as u32,
+        })
+    }
+
+    // Drop the trailing zeros of the fractional part
+    fn normalized(mut self) -> Decimal {
+        while self.scale > 0 {
+            let (quotient, remainder) = self.digits.div_small(10);
+            if remainder != 0 {
+                break;
+            }
+            self.digits = quotient;
+            self.scale -= 1;
+        }
+        if self.digits.is_zero() {
+            self.negative = false;
+        }
+        self
+    }
+
+    fn with_scale(&self, scale: u32) -> BigUint {
+        let mut digits = self.digits.clone();
+        for _ in self.scale..scale {
+            digits.mul_small(10);
+        }
+        digits
+    }
+
+    pub fn sub(&self, other: &Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+        let (a, b) = (self.with_scale(scale), other.with_scale(scale));
+        let (negative, digits) = match (self.negative, other.negative) {
+            // Same signs: subtract the magnitudes
+            (sa, sb) if sa == sb => {
+                if a >= b {
+                    (sa, a.sub(&b))
+                } else {
+                    (!sa, b.sub(&a))
+                }
+            }
+            // Opposite signs: add them
+            (sa, _) => (sa, a.add(&b)),
+        };
+        Decimal {
+            negative,
+            digits,
+            scale,
+        }
+        .normalized()
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.digits.to_string();
+        let scale = self.scale as usize;
+        let sign = if self.negative { "-" } else { "" };
+        if scale == 0 {
+            write!(f, "{}{}", sign, digits)
+        } else if digits.len() > scale {
+            let (integer, fraction) = digits.split_at(digits.len() - scale);
+            write!(f, "{}{}.{}", sign, integer, fraction)
+        } else {
+            let zeros = "0".repeat(scale - digits.len());
+            write!(f, "{}0.{}{}", sign, zeros, digits)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The exact value of `text` as written, if it is a decimal literal
+    fn written(text: &str) -> Option<String> {
+        Decimal::from_literal(&literal::lex(text).unwrap()).map(|decimal| decimal.to_string())
+    }
+
+    #[test]
+    fn subnormal() {
+        let smallest = f32::from_bits(1);
+        assert_eq!(classify(smallest), Class::Subnormal);
+        assert_eq!(significand_and_exponent(smallest), (1, -149));
+        // 2^-1074, 4.94e-324, written out to its last digit
+        let exact = exact_value(f64::from_bits(1)).to_string();
+        let leading = format!("0.{}4940656458412465441765", "0".repeat(323));
+        assert!(exact.starts_with(&leading), "{}", exact);
+        assert_eq!(exact.len(), 2 + 1074);
+        assert_eq!(classify(f64::MIN_POSITIVE), Class::Normal);
+        assert_eq!(classify(f64::MIN_POSITIVE / 2.0), Class::Subnormal);
+    }
+
+    #[test]
+    fn underflow() {
+        match parse("1e-400") {
+            Ok(Input::F64(value, Some(_))) => assert_eq!(value, 0.0),
+            _ => panic!("`1e-400` should be read as a zero `f64`"),
+        }
+        // Written out, with its leading zeros
+        let expected = format!("0.{}1", "0".repeat(399));
+        assert_eq!(written("1e-400"), Some(expected));
+        // Past 10^-400, the digits are not written out
+        assert_eq!(written("1e-500"), None);
+    }
+
+    #[test]
+    fn overflow() {
+        let error = match parse("1e400") {
+            Err(error) => error,
+            Ok(_) => panic!("`1e400` should be out of range"),
+        };
+        assert!(
+            error.starts_with("literal out of range for `f64`"),
+            "{}",
+            error
+        );
+        assert!(parse("3.5e38f32").is_err());
+        assert!(parse("3.4e38f32").is_ok());
+    }
+
+    #[test]
+    fn decimals() {
+        assert_eq!(written("0.00012"), Some("0.00012".to_owned()));
+        assert_eq!(written("-1e-5"), Some("-0.00001".to_owned()));
+        assert_eq!(written("1_000.05f64"), Some("1000.05".to_owned()));
+        assert_eq!(written("25e2"), Some("2500".to_owned()));
+        assert_eq!(written("0.0e-9999"), Some("0".to_owned()));
+        assert_eq!(written("0x10"), None);
+    }
+}
@@ -3,11 +3,14 @@ use rand::Rng;
 use std::cmp::Ordering;
 use std::io;
 
-pub fn main() {
-    guess_game();
+use crate::error::Error;
+use crate::refined::Bounded;
+
+pub fn main() -> Result<(), Error> {
+    guess_game()
 }
 
-fn guess_game() {
+fn guess_game() -> Result<(), Error> {
     println!("Guess the number!");
     let secret_number = rand::thread_rng().gen_range(1, 101);
 
@@ -15,13 +18,20 @@ fn guess_game() {
         let mut guess = String::new();
 
         println!("Please input your guess.");
-        io::stdin()
+        let read = io::stdin()
             .read_line(&mut guess)
-            .expect("Failed to read line");
+            .map_err(|error| Error::io("reading the guess", error))?;
+        if read == 0 {
+            let eof = io::Error::from(io::ErrorKind::UnexpectedEof);
+            return Err(Error::io("reading the guess", eof));
+        }
 
-        let guess = match guess.trim().parse::<u32>() {
+        let guess = match guess.trim().parse::<Bounded<1, 100>>() {
             Ok(num) => num,
-            Err(_) => continue,
+            Err(error) => {
+                println!("{}", error);
+                continue;
+            }
         };
 
         match guess.cmp(&secret_number) {
@@ -29,7 +39,7 @@ fn guess_game() {
             Ordering::Greater => println!("Too big!"),
             Ordering::Equal => {
                 println!("You win!");
-                break;
+                return Ok(());
             }
         }
     }
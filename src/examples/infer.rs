@@ -0,0 +1,44 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+
+use crate::error::Error;
+use crate::inference;
+
+// Finds the types of a program read from the file given as argument or from
+// the standard input, printing each step of the inference under the line it
+// happened on, then the type of every binding:
+//
+//     BINARY=infer cargo run -- snippet.rs
+//     echo 'let mut vec = Vec::new(); vec.push(5u8);' | BINARY=infer cargo run
+pub fn main() -> Result<(), Error> {
+    let source = match env::args().nth(1) {
+        Some(path) => {
+            fs::read_to_string(path).map_err(|error| Error::io("reading the program", error))?
+        }
+        None => {
+            let mut source = String::new();
+            io::stdin()
+                .read_to_string(&mut source)
+                .map_err(|error| Error::io("reading the program", error))?;
+            source
+        }
+    };
+
+    let inference = match inference::infer(&source) {
+        Ok(inference) => inference,
+        Err(error) => {
+            println!("{}", error.render(&source));
+            return Ok(());
+        }
+    };
+    print!("{}", inference.trace(&source));
+    println!();
+    for (name, ty) in &inference.bindings {
+        println!("{}: {}", name.node, ty);
+    }
+    for error in &inference.errors {
+        println!("\n{}", error.render(&source));
+    }
+    Ok(())
+}
@@ -0,0 +1,298 @@
+use std::env;
+use std::mem;
+
+use super::{conversion, custom_types, primitives, println};
+use crate::error::Error;
+
+// Shows how the compiler lays out the types defined by the other examples:
+// size, alignment, where each field ends up and how much padding sits
+// between them, and for enums where the discriminant is stored.
+//
+//     BINARY=layout cargo run
+//     BINARY=layout cargo run -- WebEvent custom_types::List
+//
+// Arguments select the types whose name contains them.
+pub fn main() -> Result<(), Error> {
+    let filters: Vec<String> = env::args().skip(1).collect();
+    let layouts = layouts();
+
+    for filter in &filters {
+        if !layouts
+            .iter()
+            .any(|layout| layout.name.contains(filter.as_str()))
+        {
+            println!("no example type matches `{}`", filter);
+        }
+    }
+    for layout in &layouts {
+        if filters.is_empty() || filters.iter().any(|f| layout.name.contains(f.as_str())) {
+            layout.print();
+            println!();
+        }
+    }
+
+    if filters.is_empty() {
+        niches();
+    }
+    Ok(())
+}
+
+struct Layout {
+    name: &'static str,
+    size: usize,
+    align: usize,
+    // Size of `Option<T>`
+    option_size: usize,
+    kind: Kind,
+}
+
+enum Kind {
+    // Fields in declaration order
+    Struct(Vec<Field>),
+    Enum(Vec<Variant>),
+}
+
+struct Field {
+    name: &'static str,
+    ty: &'static str,
+    offset: usize,
+    size: usize,
+}
+
+struct Variant {
+    name: &'static str,
+    // Size of the variant's fields laid out on their own, as a tuple
+    payload: usize,
+}
+
+// The fields are listed by hand, for their names and types to be printed.
+// The compiler checks they are those of the type: all of them, and each
+// with the type it really has.
+macro_rules! struct_layout {
+    ($ty:ty { $($field:tt: $field_ty:ty),* $(,)? }) => {{
+        type This = $ty;
+        let _ = |value: &This| {
+            let This { $($field: _),* } = value;
+            $(let _: &$field_ty = &value.$field;)*
+        };
+        Layout::new::<$ty>(
+            stringify!($ty),
+            Kind::Struct(vec![$(Field {
+                name: stringify!($field),
+                ty: stringify!($field_ty),
+                offset: mem::offset_of!($ty, $field),
+                size: mem::size_of::<$field_ty>(),
+            }),*]),
+        )
+    }};
+}
+
+// Likewise for the variants and what they hold
+macro_rules! enum_layout {
+    ($ty:ty {
+        $($variant:ident $(($($tuple_ty:ty),*))? $({ $($field:ident: $field_ty:ty),* })?),* $(,)?
+    }) => {{
+        type This = $ty;
+        let _ = |value: &This| match value {
+            $(This::$variant { .. } => (),)*
+        };
+        $(
+            $(let _: fn($($tuple_ty),*) -> This = This::$variant;)?
+            $(let _ = |$($field: $field_ty),*| This::$variant { $($field),* };)?
+        )*
+        Layout::new::<$ty>(
+            stringify!($ty),
+            Kind::Enum(vec![$(Variant {
+                name: stringify!($variant $(($($tuple_ty),*))? $({ $($field: $field_ty),* })?),
+                payload: mem::size_of::<($($($tuple_ty,)*)? $($($field_ty,)*)?)>(),
+            }),*]),
+        )
+    }};
+}
+
+fn layouts() -> Vec<Layout> {
+    vec![
+        struct_layout!(custom_types::Person {
+            name: String,
+            age: u8
+        }),
+        struct_layout!(custom_types::Unit {}),
+        struct_layout!(custom_types::Pair { 0: i32, 1: f32 }),
+        struct_layout!(custom_types::Point { x: f32, y: f32 }),
+        struct_layout!(custom_types::Rectangle {
+            top_left: custom_types::Point,
+            bottom_right: custom_types::Point,
+        }),
+        enum_layout!(custom_types::WebEvent {
+            PageLoad,
+            PageUnload,
+            KeyPress(char),
+            Paste(String),
+            Click { x: i64, y: i64 },
+        }),
+        enum_layout!(custom_types::Status { Rich, Poor }),
+        enum_layout!(custom_types::Work { Civilian, Soldier }),
+        enum_layout!(custom_types::Number { Zero, One, Two }),
+        enum_layout!(custom_types::Color { Red, Green, Blue }),
+        enum_layout!(custom_types::List {
+            Cons(u32, Box<custom_types::List>),
+            Nil,
+        }),
+        struct_layout!(println::Structure { 0: i32 }),
+        struct_layout!(println::Deep {
+            0: println::Structure
+        }),
+        struct_layout!(println::Person<'static> { name: &'static str, age: u8 }),
+        struct_layout!(println::Complex {
+            real: f64,
+            imag: f64
+        }),
+        struct_layout!(println::List { 0: Vec<i32> }),
+        struct_layout!(println::City {
+            name: &'static str,
+            lat: f32,
+            lon: f32,
+        }),
+        struct_layout!(println::Color {
+            red: u8,
+            green: u8,
+            blue: u8
+        }),
+        struct_layout!(primitives::Matrix {
+            0: f32,
+            1: f32,
+            2: f32,
+            3: f32
+        }),
+        struct_layout!(conversion::Number { value: i32 }),
+        struct_layout!(conversion::Numero { valeur: i32 }),
+        struct_layout!(conversion::EvenNumber { 0: i32 }),
+        struct_layout!(conversion::Circle { radius: i32 }),
+    ]
+}
+
+impl Layout {
+    fn new<T>(name: &'static str, kind: Kind) -> Layout {
+        Layout {
+            name,
+            size: mem::size_of::<T>(),
+            align: mem::align_of::<T>(),
+            option_size: mem::size_of::<Option<T>>(),
+            kind,
+        }
+    }
+
+    fn print(&self) {
+        println!("{}: size {}, align {}", self.name, self.size, self.align);
+        match &self.kind {
+            Kind::Struct(fields) => self.print_fields(fields),
+            Kind::Enum(variants) => self.print_variants(variants),
+        }
+        if self.option_size == self.size {
+            println!(
+                "  Option<_> is also {} bytes: `None` fits in a niche",
+                self.size
+            );
+        } else {
+            println!(
+                "  Option<_> is {} bytes: `None` needs a tag of its own",
+                self.option_size
+            );
+        }
+    }
+
+    fn print_fields(&self, fields: &[Field]) {
+        if fields.is_empty() {
+            println!("  no fields, values take no memory at all");
+            return;
+        }
+
+        let mut by_offset: Vec<&Field> = fields.iter().collect();
+        by_offset.sort_by_key(|field| field.offset);
+
+        println!("  {:>6} {:>4}  field", "offset", "size");
+        let mut end = 0;
+        for field in &by_offset {
+            if field.offset > end {
+                println!("  {:>6} {:>4}  (padding)", end, field.offset - end);
+            }
+            println!(
+                "  {:>6} {:>4}  {}: {}",
+                field.offset, field.size, field.name, field.ty
+            );
+            end = end.max(field.offset + field.size);
+        }
+        if self.size > end {
+            println!("  {:>6} {:>4}  (padding)", end, self.size - end);
+        }
+
+        let padding = self.size - fields.iter().map(|field| field.size).sum::<usize>();
+        println!("  {} of {} bytes are padding", padding, self.size);
+        if by_offset
+            .iter()
+            .zip(fields.iter())
+            .any(|(a, b)| a.name != b.name)
+        {
+            println!("  the compiler reordered the fields to reduce padding");
+        }
+    }
+
+    fn print_variants(&self, variants: &[Variant]) {
+        println!("  {:>7}  variant", "payload");
+        for variant in variants {
+            // `stringify!` puts spaces around the punctuation
+            let name = variant.name.replace(" (", "(").replace(" : ", ": ");
+            println!("  {:>7}  {}", variant.payload, name);
+        }
+
+        let largest = variants.iter().map(|v| v.payload).max().unwrap_or(0);
+        if largest == 0 {
+            println!(
+                "  no variant holds data, values are just a {} byte discriminant",
+                self.size
+            );
+        } else if self.size == largest {
+            println!(
+                "  the discriminant is stored in a niche of the payload, it costs no extra bytes"
+            );
+        } else {
+            println!(
+                "  the discriminant and its padding take {} of the {} bytes",
+                self.size - largest,
+                self.size
+            );
+        }
+    }
+}
+
+// Niches are invalid bit patterns of a type that an enum can use to encode
+// its discriminant: a `Box` or a reference is never null, a `char` is never
+// above 0x10ffff, a `bool` is only 0 or 1...
+fn niches() {
+    macro_rules! niche {
+        ($ty:ty) => {{
+            let (size, option_size) = (mem::size_of::<$ty>(), mem::size_of::<Option<$ty>>());
+            println!(
+                "{:>28} {:>2} bytes, Option of it {:>2} bytes{}",
+                stringify!($ty),
+                size,
+                option_size,
+                if size == option_size { " (niche)" } else { "" }
+            );
+        }};
+    }
+
+    println!("niches:");
+    niche!(Box<custom_types::List>);
+    niche!(&'static str);
+    niche!(String);
+    niche!(Vec<i32>);
+    niche!(char);
+    niche!(bool);
+    niche!(u32);
+    niche!(f64);
+    niche!(std::num::NonZeroU32);
+    niche!(custom_types::Number);
+    niche!(custom_types::List);
+    niche!(custom_types::WebEvent);
+}
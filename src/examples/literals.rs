@@ -0,0 +1,84 @@
+use std::env;
+
+use crate::error::Error;
+use crate::literal::{self, Literal};
+
+// Lexes numeric literals the way rustc does and shows what they turn into:
+//
+//     BINARY=literals cargo run -- 0x80u32 1_000_000u32 65.4321_f32 256u8
+pub fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args = if args.is_empty() {
+        // The literals of the types and primitives examples, and a few that
+        // rustc rejects
+        [
+            "1u8",
+            "2u32",
+            "3f32",
+            "1",
+            "1.0",
+            "4294967296i64",
+            "0b0011u32",
+            "0x80u32",
+            "1_000_000u32",
+            "1_000.05f64",
+            "65.4321_f32",
+            "1e-3",
+            "-128i8",
+            "256u8",
+            "0xffi8",
+            "3000000000",
+            "-1u8",
+            "0b102",
+            "0x1.5",
+            "1.0u8",
+            "1e",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    } else {
+        args
+    };
+
+    for arg in args {
+        println!("{}", arg);
+        match literal::lex(&arg) {
+            Ok(literal) => describe(&literal),
+            Err(error) => {
+                println!("  error: {}", error);
+                if let Some(note) = error.note() {
+                    println!("  note: {}", note);
+                }
+                if let Some(help) = error.help() {
+                    println!("  help: {}", help);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn describe(literal: &Literal) {
+    let kind = if literal.value.is_float() {
+        "float"
+    } else {
+        "integer"
+    };
+    println!("  kind   {} {}", literal.radix, kind);
+    println!(
+        "  type   {} ({})",
+        literal.value.type_name(),
+        if literal.suffixed {
+            "from the suffix"
+        } else {
+            "the default for unsuffixed literals"
+        }
+    );
+    println!("  value  {}", literal.value);
+    println!(
+        "  size   {} bits: {}",
+        literal.value.bits(),
+        literal.value.bit_pattern()
+    );
+}
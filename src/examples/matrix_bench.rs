@@ -0,0 +1,85 @@
+use rand::Rng;
+
+use std::env;
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use crate::human;
+use crate::matrix::{Matrix, MatrixError, Multiplication};
+
+// Times every multiplication strategy on square matrices of growing size.
+//
+// Arguments are either strategy names (`naive`, `transposed`, `blocked:32`,
+// `threaded:4`, ...) or sizes; by default every strategy runs on sizes from
+// 64 to 1024. Run it with `--release`, the debug build is far too slow for
+// the larger sizes:
+//
+//     BINARY=matrix_bench cargo run --release -- blocked:32 threaded 512
+pub fn main() -> Result<(), Error> {
+    let mut multiplications = Vec::new();
+    let mut sizes = Vec::new();
+    for arg in env::args().skip(1) {
+        match arg.parse::<usize>() {
+            Ok(size) => sizes.push(size),
+            Err(_) => match arg.parse::<Multiplication>() {
+                Ok(multiplication) => multiplications.push(multiplication),
+                Err(error) => return Err(Error::Usage(error)),
+            },
+        }
+    }
+    if multiplications.is_empty() {
+        multiplications = Multiplication::all();
+    }
+    if sizes.is_empty() {
+        sizes = vec![64, 128, 256, 512, 1024];
+    }
+
+    let width = multiplications
+        .iter()
+        .map(|m| m.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    for size in sizes {
+        println!("{}×{}", size, size);
+        let a = random_matrix(size);
+        let b = random_matrix(size);
+
+        // The naive product is the reference the other results are checked
+        // against. Every element is a sum of `size` products, so the
+        // rounding error grows with the size.
+        let reference = a.mul_with(&b, Multiplication::Naive)?;
+        let tolerance = 1e-12 * size as f64;
+
+        for &multiplication in &multiplications {
+            let start = Instant::now();
+            let product = a.mul_with(&b, multiplication)?;
+            let elapsed = start.elapsed();
+
+            let agrees = product.approx_eq(&reference, tolerance);
+            let status = if agrees { "ok" } else { "MISMATCH" };
+            println!(
+                "  {:<width$} {:>12.3} {:>10.2} GFLOP/s  {}",
+                multiplication.to_string(),
+                human::Duration::from(elapsed),
+                gflops(size, elapsed),
+                status,
+                width = width
+            );
+            if !agrees {
+                return Err(MatrixError::Disagreement(multiplication).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn random_matrix(size: usize) -> Matrix {
+    let mut rng = rand::thread_rng();
+    Matrix::from_fn(size, size, |_, _| rng.gen_range(-1.0, 1.0))
+}
+
+// A product of two n×n matrices takes n³ multiplications and n³ additions
+fn gflops(size: usize, elapsed: Duration) -> f64 {
+    2.0 * (size as f64).powi(3) / elapsed.as_secs_f64() / 1e9
+}
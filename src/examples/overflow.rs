@@ -0,0 +1,577 @@
+use std::convert::TryFrom;
+use std::env;
+use std::fmt;
+
+use crate::error::Error;
+
+// The operators section of the primitives example asks to turn `1i32 - 2`
+// into `1u32 - 2` to see what happens. This evaluates an expression over an
+// integer type under every overflow mode Rust offers:
+//
+//     BINARY=overflow cargo run -- u32 "1 - 2"
+//     BINARY=overflow cargo run -- i8 "-128 / -1"
+//
+// Without a type, the expression is evaluated as an `i32`, Rust's default.
+pub fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        for &(ty, expression) in &[
+            ("i32", "1 - 2"),
+            ("u32", "1 - 2"),
+            ("u8", "200 + 100"),
+            ("i8", "-128 / -1"),
+            ("i16", "300 * 300 % 7"),
+            ("u64", "1 / (2 - 2)"),
+        ] {
+            explore(ty, expression);
+        }
+        return Ok(());
+    }
+
+    let (ty, expression) = if TYPES.contains(&args[0].as_str()) {
+        (args[0].as_str(), args[1..].join(" "))
+    } else {
+        ("i32", args.join(" "))
+    };
+    explore(ty, &expression);
+    Ok(())
+}
+
+const TYPES: [&str; 12] = [
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+fn explore(ty: &str, expression: &str) {
+    println!("{} as {}", expression, ty);
+    let expr = match parse(expression) {
+        Ok(expr) => expr,
+        Err(error) => {
+            println!("  error: {}", error);
+            return;
+        }
+    };
+    let report = match ty {
+        "u8" => evaluate_all::<u8>(&expr),
+        "u16" => evaluate_all::<u16>(&expr),
+        "u32" => evaluate_all::<u32>(&expr),
+        "u64" => evaluate_all::<u64>(&expr),
+        "u128" => evaluate_all::<u128>(&expr),
+        "usize" => evaluate_all::<usize>(&expr),
+        "i8" => evaluate_all::<i8>(&expr),
+        "i16" => evaluate_all::<i16>(&expr),
+        "i32" => evaluate_all::<i32>(&expr),
+        "i64" => evaluate_all::<i64>(&expr),
+        "i128" => evaluate_all::<i128>(&expr),
+        "isize" => evaluate_all::<isize>(&expr),
+        _ => Err(format!("unknown integer type `{}`", ty)),
+    };
+    match report {
+        Ok(lines) => {
+            for (mode, result) in lines {
+                println!("  {:<12} {}", mode, result);
+            }
+        }
+        Err(error) => println!("  error: {}", error),
+    }
+    println!();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    // Negative literals are a `Neg` of one
+    Literal(u128),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    // `a + b`: panics on overflow in debug builds
+    Plain,
+    Wrapping,
+    Checked,
+    Saturating,
+    Overflowing,
+    // `a + b` in release builds: `+`, `-`, `*` and negation wrap, but
+    // division and remainder still panic on overflow, whatever
+    // `-C overflow-checks` says
+    Release,
+}
+
+impl Mode {
+    const ALL: [Mode; 5] = [
+        Mode::Plain,
+        Mode::Wrapping,
+        Mode::Checked,
+        Mode::Saturating,
+        Mode::Overflowing,
+    ];
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Mode::Plain => "plain",
+            Mode::Wrapping => "wrapping",
+            Mode::Checked => "checked",
+            Mode::Saturating => "saturating",
+            Mode::Overflowing => "overflowing",
+            Mode::Release => "release",
+        };
+        // Honor width and alignment so modes can be lined up
+        f.pad(name)
+    }
+}
+
+// Why an evaluation didn't produce a value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    // The message of the panic, as printed by a debug build
+    Panic(&'static str),
+    // A `checked_*` operation returned `None`
+    None,
+}
+
+// The operations every integer type provides in each overflow mode.
+// Division by zero is handled by `evaluate` before these are called.
+pub trait Integer: Copy + PartialEq + fmt::Display + TryFrom<i128> + TryFrom<u128> {
+    const NAME: &'static str;
+    const SIGNED: bool;
+    const ZERO: Self;
+
+    // The literal `magnitude`, negated if `negative`, if it fits
+    fn from_literal(negative: bool, magnitude: u128) -> Option<Self> {
+        if !negative {
+            return Self::try_from(magnitude).ok();
+        }
+        let value = if magnitude == i128::MIN.unsigned_abs() {
+            i128::MIN
+        } else {
+            -i128::try_from(magnitude).ok()?
+        };
+        Self::try_from(value).ok()
+    }
+
+    fn checked(op: Op, a: Self, b: Self) -> Option<Self>;
+    fn wrapping(op: Op, a: Self, b: Self) -> Self;
+    fn saturating(op: Op, a: Self, b: Self) -> Self;
+    fn overflowing(op: Op, a: Self, b: Self) -> (Self, bool);
+}
+
+macro_rules! impl_integer {
+    ($($ty:ident => $signed:expr),*) => {
+        $(
+            impl Integer for $ty {
+                const NAME: &'static str = stringify!($ty);
+                const SIGNED: bool = $signed;
+                const ZERO: Self = 0;
+
+                fn checked(op: Op, a: Self, b: Self) -> Option<Self> {
+                    match op {
+                        Op::Add => a.checked_add(b),
+                        Op::Sub => a.checked_sub(b),
+                        Op::Mul => a.checked_mul(b),
+                        Op::Div => a.checked_div(b),
+                        Op::Rem => a.checked_rem(b),
+                    }
+                }
+
+                fn wrapping(op: Op, a: Self, b: Self) -> Self {
+                    match op {
+                        Op::Add => a.wrapping_add(b),
+                        Op::Sub => a.wrapping_sub(b),
+                        Op::Mul => a.wrapping_mul(b),
+                        Op::Div => a.wrapping_div(b),
+                        Op::Rem => a.wrapping_rem(b),
+                    }
+                }
+
+                fn saturating(op: Op, a: Self, b: Self) -> Self {
+                    match op {
+                        Op::Add => a.saturating_add(b),
+                        Op::Sub => a.saturating_sub(b),
+                        Op::Mul => a.saturating_mul(b),
+                        Op::Div => a.saturating_div(b),
+                        // There is no `saturating_rem`: the only overflowing
+                        // case, `MIN % -1`, is 0 which always fits.
+                        Op::Rem => a.wrapping_rem(b),
+                    }
+                }
+
+                fn overflowing(op: Op, a: Self, b: Self) -> (Self, bool) {
+                    match op {
+                        Op::Add => a.overflowing_add(b),
+                        Op::Sub => a.overflowing_sub(b),
+                        Op::Mul => a.overflowing_mul(b),
+                        Op::Div => a.overflowing_div(b),
+                        Op::Rem => a.overflowing_rem(b),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(
+    u8 => false, u16 => false, u32 => false, u64 => false, u128 => false, usize => false,
+    i8 => true, i16 => true, i32 => true, i64 => true, i128 => true, isize => true
+);
+
+// The panic message of a debug build for an overflowing `op`
+fn overflow_message(op: Op) -> &'static str {
+    match op {
+        Op::Add => "attempt to add with overflow",
+        Op::Sub => "attempt to subtract with overflow",
+        Op::Mul => "attempt to multiply with overflow",
+        Op::Div => "attempt to divide with overflow",
+        Op::Rem => "attempt to calculate the remainder with overflow",
+    }
+}
+
+// Evaluate `expr` in the given mode. The `bool` tells whether any operation
+// overflowed along the way (only `Overflowing` reports it).
+pub fn evaluate<T: Integer>(expr: &Expr, mode: Mode) -> Result<(T, bool), Failure> {
+    match expr {
+        // Literals are range-checked before evaluation
+        Expr::Literal(value) => T::from_literal(false, *value)
+            .map(|value| (value, false))
+            .ok_or(Failure::Panic("literal out of range")),
+        // Negative literals like `-128i8` are a single value
+        Expr::Neg(operand) if matches!(**operand, Expr::Literal(_)) => match **operand {
+            Expr::Literal(value) => T::from_literal(true, value)
+                .map(|value| (value, false))
+                .ok_or(Failure::Panic("literal out of range")),
+            _ => unreachable!(),
+        },
+        Expr::Neg(operand) => {
+            // `-x` is `0 - x`, except for the panic message
+            let zero = Expr::Literal(0);
+            match evaluate::<T>(
+                &Expr::Binary(Op::Sub, Box::new(zero), operand.clone()),
+                mode,
+            ) {
+                Err(Failure::Panic("attempt to subtract with overflow")) => {
+                    Err(Failure::Panic("attempt to negate with overflow"))
+                }
+                result => result,
+            }
+        }
+        Expr::Binary(op, left, right) => {
+            let (a, left_overflowed) = evaluate::<T>(left, mode)?;
+            let (b, right_overflowed) = evaluate::<T>(right, mode)?;
+            let overflowed = left_overflowed || right_overflowed;
+
+            if b == T::ZERO && (*op == Op::Div || *op == Op::Rem) {
+                // Division by zero panics whatever the mode, except for
+                // `checked_*` which returns `None`
+                return match mode {
+                    Mode::Checked => Err(Failure::None),
+                    _ if *op == Op::Div => Err(Failure::Panic("attempt to divide by zero")),
+                    _ => Err(Failure::Panic(
+                        "attempt to calculate the remainder with a divisor of zero",
+                    )),
+                };
+            }
+
+            let plain = || match T::checked(*op, a, b) {
+                Some(value) => Ok((value, false)),
+                None => Err(Failure::Panic(overflow_message(*op))),
+            };
+            match mode {
+                Mode::Plain => plain(),
+                Mode::Release if *op == Op::Div || *op == Op::Rem => plain(),
+                Mode::Wrapping | Mode::Release => Ok((T::wrapping(*op, a, b), false)),
+                Mode::Checked => T::checked(*op, a, b)
+                    .map(|value| (value, false))
+                    .ok_or(Failure::None),
+                Mode::Saturating => Ok((T::saturating(*op, a, b), false)),
+                Mode::Overflowing => {
+                    let (value, overflow) = T::overflowing(*op, a, b);
+                    Ok((value, overflowed || overflow))
+                }
+            }
+        }
+    }
+}
+
+// One line per mode, or an error if the expression isn't valid for `T`
+fn evaluate_all<T: Integer>(expr: &Expr) -> Result<Vec<(Mode, String)>, String> {
+    check::<T>(expr)?;
+    Ok(Mode::ALL
+        .iter()
+        .map(|&mode| {
+            let result = match (mode, evaluate::<T>(expr, mode)) {
+                (Mode::Plain, Ok((value, _))) => value.to_string(),
+                (Mode::Plain, Err(Failure::Panic(message))) => {
+                    match evaluate::<T>(expr, Mode::Release) {
+                        Ok((value, _)) => format!(
+                            "panics with '{}' in debug builds, {} in release builds",
+                            message, value
+                        ),
+                        Err(Failure::Panic(other)) if other != message => format!(
+                            "panics with '{}' in debug builds, '{}' in release builds",
+                            message, other
+                        ),
+                        Err(_) => format!("panics with '{}'", message),
+                    }
+                }
+                (Mode::Checked, Ok((value, _))) => format!("Some({})", value),
+                (Mode::Overflowing, Ok((value, overflowed))) => {
+                    format!("({}, {})", value, overflowed)
+                }
+                (_, Ok((value, _))) => value.to_string(),
+                (_, Err(Failure::Panic(message))) => format!("panics with '{}'", message),
+                (_, Err(Failure::None)) => "None".to_owned(),
+            };
+            (mode, result)
+        })
+        .collect())
+}
+
+// Reject what wouldn't compile: literals that don't fit the type (the
+// `overflowing_literals` lint is deny-by-default) and negating unsigned values
+fn check<T: Integer>(expr: &Expr) -> Result<(), String> {
+    let out_of_range = || Err(format!("literal out of range for `{}`", T::NAME));
+    match expr {
+        // `-128i8` is fine: the literal is negated before the range check
+        Expr::Neg(operand) if T::SIGNED => match **operand {
+            Expr::Literal(value) if T::from_literal(true, value).is_none() => out_of_range(),
+            Expr::Literal(_) => Ok(()),
+            _ => check::<T>(operand),
+        },
+        Expr::Neg(_) => Err(format!(
+            "cannot apply unary operator `-` to type `{}` (E0600)",
+            T::NAME
+        )),
+        Expr::Literal(value) if T::from_literal(false, *value).is_none() => out_of_range(),
+        Expr::Literal(_) => Ok(()),
+        Expr::Binary(_, left, right) => {
+            check::<T>(left)?;
+            check::<T>(right)
+        }
+    }
+}
+
+// expression := term (('+' | '-') term)*
+// term       := unary (('*' | '/' | '%') unary)*
+// unary      := '-' unary | atom
+// atom       := integer | '(' expression ')'
+pub fn parse(input: &str) -> Result<Expr, String> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+    };
+    let expr = parser.expression()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected `{}`", token)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Integer(u128),
+    Symbol(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Integer(value) => write!(f, "{}", value),
+            Token::Symbol(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' => {
+                chars.next();
+            }
+            '0'..='9' => {
+                let mut digits = String::new();
+                while let Some(&d) = chars.peek() {
+                    if d.is_ascii_digit() || d == '_' {
+                        digits.push(d);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let value = digits
+                    .replace('_', "")
+                    .parse::<u128>()
+                    .map_err(|_| format!("integer literal is too large: {}", digits))?;
+                tokens.push(Token::Integer(value));
+            }
+            '+' | '-' | '*' | '/' | '%' | '(' | ')' => {
+                tokens.push(Token::Symbol(c));
+                chars.next();
+            }
+            _ => return Err(format!("unexpected character `{}`", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                Op::Add
+            } else if self.eat('-') {
+                Op::Sub
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                Op::Mul
+            } else if self.eat('/') {
+                Op::Div
+            } else if self.eat('%') {
+                Op::Rem
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else {
+            self.atom()
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.peek().cloned() {
+            Some(Token::Integer(value)) => {
+                self.position += 1;
+                Ok(Expr::Literal(value))
+            }
+            Some(Token::Symbol('(')) => {
+                self.position += 1;
+                let expr = self.expression()?;
+                if self.eat(')') {
+                    Ok(expr)
+                } else {
+                    Err("expected `)`".to_owned())
+                }
+            }
+            Some(token) => Err(format!("unexpected `{}`", token)),
+            None => Err("unexpected end of expression".to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The line of each mode, in the order of `Mode::ALL`
+    fn results<T: Integer>(expression: &str) -> Vec<String> {
+        let expr = parse(expression).unwrap();
+        let lines = evaluate_all::<T>(&expr).unwrap();
+        lines.into_iter().map(|(_, result)| result).collect()
+    }
+
+    #[test]
+    fn division_overflow() {
+        let expr = parse("-128 / -1").unwrap();
+        let overflow = Err(Failure::Panic("attempt to divide with overflow"));
+        assert_eq!(evaluate::<i8>(&expr, Mode::Plain), overflow);
+        // Unlike `+`, `-` and `*`, division checks for overflow in release
+        // builds too
+        assert_eq!(evaluate::<i8>(&expr, Mode::Release), overflow);
+        assert_eq!(evaluate::<i8>(&expr, Mode::Wrapping), Ok((-128, false)));
+        assert_eq!(evaluate::<i8>(&expr, Mode::Checked), Err(Failure::None));
+        assert_eq!(evaluate::<i8>(&expr, Mode::Saturating), Ok((127, false)));
+        assert_eq!(evaluate::<i8>(&expr, Mode::Overflowing), Ok((-128, true)));
+        assert_eq!(
+            results::<i8>("-128 / -1")[0],
+            "panics with 'attempt to divide with overflow'"
+        );
+    }
+
+    #[test]
+    fn release_wraps() {
+        let expr = parse("127 + 1").unwrap();
+        assert_eq!(evaluate::<i8>(&expr, Mode::Release), Ok((-128, false)));
+        assert_eq!(
+            results::<i8>("127 + 1")[0],
+            "panics with 'attempt to add with overflow' in debug builds, -128 in release builds"
+        );
+    }
+
+    #[test]
+    fn u128_max() {
+        let max = u128::MAX.to_string();
+        assert_eq!(results::<u128>(&max)[0], max);
+        assert_eq!(results::<u128>(&format!("{} + 1", max))[1], "0");
+        assert_eq!(
+            parse(&format!("{}0", max)),
+            Err(format!("integer literal is too large: {}0", max))
+        );
+    }
+
+    #[test]
+    fn i128_min() {
+        let min = i128::MIN.to_string();
+        assert_eq!(results::<i128>(&min)[0], min);
+        assert_eq!(
+            results::<i128>(&format!("{} - 1", min))[1],
+            i128::MAX.to_string()
+        );
+        let expr = parse(&min[1..]).unwrap();
+        assert_eq!(
+            check::<i128>(&expr),
+            Err("literal out of range for `i128`".to_owned())
+        );
+        let expr = parse(&format!("-{}", &min[1..])).unwrap();
+        assert_eq!(
+            evaluate::<i128>(&Expr::Neg(Box::new(expr)), Mode::Plain),
+            Err(Failure::Panic("attempt to negate with overflow"))
+        );
+    }
+}
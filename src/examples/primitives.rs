@@ -1,8 +1,15 @@
-pub fn main() -> () {
-    primitives();
-    litteral_and_operators();
-    tuples();
-    arrays_and_slices();
+use std::fmt;
+
+use crate::allocations;
+use crate::error::Error;
+
+pub fn main() -> Result<(), Error> {
+    allocations::section("primitives", primitives);
+    allocations::section("litteral_and_operators", litteral_and_operators);
+    allocations::section("tuples", tuples);
+    allocations::section("matrices", matrices)?;
+    allocations::section("arrays_and_slices", arrays_and_slices);
+    Ok(())
 }
 
 fn primitives() -> () {
@@ -43,6 +50,7 @@ fn litteral_and_operators() -> () {
     // Integer subtraction
     println!("1 - 2 = {}", 1i32 - 2);
     // TODO ^ Try changing `1i32` to `1u32` to see why the type is important
+    // (the `overflow` example shows what every overflow mode does with it)
 
     // Short-circuiting boolean logic
     println!("true AND false is {}", true && false);
@@ -61,6 +69,28 @@ fn litteral_and_operators() -> () {
     println!("1000.05 is written as {}", 1_000.05f64);
 }
 
+// The following struct is for the activity.
+#[derive(Debug)]
+pub(crate) struct Matrix(
+    pub(crate) f32,
+    pub(crate) f32,
+    pub(crate) f32,
+    pub(crate) f32,
+);
+
+impl fmt::Display for Matrix {
+    // `f` is a buffer, and this method must write the formatted string into it
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "( {} {} )\n( {} {} )", self.0, self.1, self.2, self.3)
+    }
+}
+
+impl Matrix {
+    fn transpose(&self) -> Matrix {
+        Matrix(self.0, self.2, self.1, self.3)
+    }
+}
+
 fn tuples() -> () {
     // Tuples can be used as function arguments and as return values
     fn reverse((integer, boolean): (i32, bool)) -> (bool, i32) {
@@ -69,24 +99,9 @@ fn tuples() -> () {
 
     println!("{:?}", reverse((42, false)));
 
-    // The following struct is for the activity.
-    #[derive(Debug)]
-    struct Matrix(f32, f32, f32, f32);
-    use std::fmt;
-    impl fmt::Display for Matrix {
-        // `f` is a buffer, and this method must write the formatted string into it
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "( {} {} )\n( {} {} )", self.0, self.1, self.2, self.3)
-        }
-    }
     fn transpose(matrix: &Matrix) -> Matrix {
         Matrix(matrix.0, matrix.2, matrix.1, matrix.3)
     }
-    impl Matrix {
-        fn transpose(&self) -> Matrix {
-            Matrix(self.0, self.2, self.1, self.3)
-        }
-    }
 
     // A tuple with a bunch of different types
     let long_tuple = (
@@ -128,6 +143,41 @@ fn tuples() -> () {
     println!("Transpose:\n{}", matrix.transpose());
 }
 
+fn matrices() -> Result<(), Error> {
+    use crate::matrix::Matrix;
+
+    // The 2×2 tuple struct from `tuples` only has one shape, `Matrix` from
+    // the `matrix` module stores its dimensions alongside the elements.
+    let matrix = Matrix::from_rows(&[[1.1, 1.2], [2.1, 2.2]]);
+    println!("Matrix:\n{}", matrix);
+    println!("Transpose:\n{}", matrix.transpose());
+
+    // Any N×M shape works, and columns are aligned on their widest element
+    let a = Matrix::from_rows(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    let b = Matrix::from_rows(&[[7.0, 8.0], [9.0, 10.0], [11.0, 12.0]]);
+    println!("A:\n{}\nB:\n{}", a, b);
+    println!("A·B:\n{}", &a * &b);
+    println!("A + A:\n{}", &a + &a);
+
+    // Shapes are checked: a 2×3 matrix can't be added to a 3×2 one
+    if let Err(error) = a.checked_add(&b) {
+        println!("A + B: {}", error);
+    }
+
+    // Determinant, inverse and `A·x = b` all go through an LU decomposition
+    let m = Matrix::from_rows(&[[2.0, 1.0, 1.0], [4.0, -6.0, 0.0], [-2.0, 7.0, 2.0]]);
+    println!("M:\n{}\ndet(M) = {}", m, m.determinant()?);
+    println!("M⁻¹:\n{:.4}", m.inverse()?);
+    println!("M·x = (5, -2, 9) for x = {:?}", m.solve(&[5.0, -2.0, 9.0])?);
+
+    // A singular matrix has a zero determinant and no inverse
+    let singular = Matrix::from_rows(&[[1.0, 2.0], [2.0, 4.0]]);
+    if let Err(error) = singular.inverse() {
+        println!("Inverse of\n{}\n{}", singular, error);
+    }
+    Ok(())
+}
+
 fn arrays_and_slices() -> () {
     use std::mem;
 
@@ -1,7 +1,108 @@
 // Import (via `use`) the `fmt` module to make it available.
 use std::fmt;
 
-pub fn main() {
+use crate::display::DisplayList;
+use crate::error::Error;
+
+// Derive the `fmt::Debug` implementation for `Structure`. `Structure`
+// is a structure which contains a single `i32`.
+#[derive(Debug)]
+pub(crate) struct Structure(pub(crate) i32);
+
+// Put a `Structure` inside of the structure `Deep`. Make it printable
+// also.
+#[derive(Debug)]
+pub(crate) struct Deep(pub(crate) Structure);
+
+#[derive(Debug)]
+pub(crate) struct Person<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) age: u8,
+}
+
+#[derive(Debug)]
+pub(crate) struct Complex {
+    pub(crate) real: f64,
+    pub(crate) imag: f64,
+}
+
+// Define a structure named `List` containing a `Vec`.
+pub(crate) struct List(pub(crate) Vec<i32>);
+
+pub(crate) struct City {
+    pub(crate) name: &'static str,
+    // Latitude
+    pub(crate) lat: f32,
+    // Longitude
+    pub(crate) lon: f32,
+}
+
+#[derive(Debug)]
+pub(crate) struct Color {
+    pub(crate) red: u8,
+    pub(crate) green: u8,
+    pub(crate) blue: u8,
+}
+
+impl fmt::Display for Structure {
+    // This trait requires `fmt` with this exact signature.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Write strictly the first element into the supplied output
+        // stream: `f`. Returns `fmt::Result` which indicates whether the
+        // operation succeeded or failed. Note that `write!` uses syntax which
+        // is very similar to `println!`.
+        write!(f, "THE VALUE IS {}", self.0)
+    }
+}
+
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{real} + {imag}i", real = self.real, imag = self.imag)
+    }
+}
+
+impl fmt::Display for List {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Extract the value using tuple indexing, and let `DisplayList`
+        // write the brackets, the separators and the indices.
+        DisplayList::new(&self.0).indexed(true).fmt(f)
+    }
+}
+
+impl fmt::Display for City {
+    // `f` is a buffer, and this method must write the formatted string into it
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
+        let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
+
+        // `write!` is like `format!`, but it will write the formatted string
+        // into a buffer (the first argument)
+        write!(
+            f,
+            "{}: {:.3}°{} {:.3}°{}",
+            self.name,
+            self.lat.abs(),
+            lat_c,
+            self.lon.abs(),
+            lon_c
+        )
+    }
+}
+
+impl fmt::Display for Color {
+    // `f` is a buffer, and this method must write the formatted string into it
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "RGB ({red}, {green}, {blue}) 0x{red:02X?}{green:02X?}{blue:02X?}",
+            red = self.red,
+            green = self.green,
+            blue = self.blue,
+        )
+    }
+}
+
+pub fn main() -> Result<(), Error> {
     println!("{} days", 31);
     println!("{0}, this is {1}. {1}, this is {0}", "Alice", "Bob");
     // As can named arguments.
@@ -42,27 +143,11 @@ pub fn main() {
     let pi = 3.141592;
     println!("PI is roughly {pi:.3}", pi = pi);
 
-    // Derive the `fmt::Debug` implementation for `Structure`. `Structure`
-    // is a structure which contains a single `i32`.
-    #[derive(Debug)]
-    struct Structure(i32);
-
-    // Put a `Structure` inside of the structure `Deep`. Make it printable
-    // also.
-    #[derive(Debug)]
-    struct Deep(Structure);
-
     // `Structure` is printable with :? formatter
     println!("Now {:?} will print!", Structure(3));
     // Deep too
     println!("Now {:?} will print!", Deep(Structure(7)));
 
-    #[derive(Debug)]
-    struct Person<'a> {
-        name: &'a str,
-        age: u8,
-    }
-
     let name = "Peter";
     let age = 27;
     let peter = Person { name, age };
@@ -70,30 +155,8 @@ pub fn main() {
     // Pretty print with #? formatter
     println!("{:#?}", peter);
 
-    impl fmt::Display for Structure {
-        // This trait requires `fmt` with this exact signature.
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            // Write strictly the first element into the supplied output
-            // stream: `f`. Returns `fmt::Result` which indicates whether the
-            // operation succeeded or failed. Note that `write!` uses syntax which
-            // is very similar to `println!`.
-            write!(f, "THE VALUE IS {}", self.0)
-        }
-    }
-
     println!("This struct now prints `{}` :)", Structure(3));
 
-    #[derive(Debug)]
-    struct Complex {
-        real: f64,
-        imag: f64,
-    }
-    impl fmt::Display for Complex {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{real} + {imag}i", real = self.real, imag = self.imag)
-        }
-    }
-
     let complex = Complex {
         real: 3.3,
         imag: 7.2,
@@ -101,82 +164,26 @@ pub fn main() {
     println!("Display: {}", complex);
     println!("Debug: {:?}", complex);
 
-    // Define a structure named `List` containing a `Vec`.
-    struct List(Vec<i32>);
-
-    impl fmt::Display for List {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            // Extract the value using tuple indexing,
-            // and create a reference to `vec`.
-            let vec = &self.0;
-
-            write!(f, "[")?;
-
-            // Iterate over `v` in `vec` while enumerating the iteration
-            // count in `count`.
-            for (count, v) in vec.iter().enumerate() {
-                // For every element except the first, add a comma.
-                // Use the ? operator to return on errors.
-                if count != 0 {
-                    write!(f, ", ")?;
-                }
-                write!(f, "{count}: {v}", count = count, v = v)?;
-            }
-
-            // Close the opened bracket and return a fmt::Result value.
-            write!(f, "]")
-        }
-    }
-
     let v = List(vec![1, 2, 3]);
     println!("{}", v);
 
-    struct City {
-        name: &'static str,
-        // Latitude
-        lat: f32,
-        // Longitude
-        lon: f32,
-    }
-
-    impl fmt::Display for City {
-        // `f` is a buffer, and this method must write the formatted string into it
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
-            let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
-
-            // `write!` is like `format!`, but it will write the formatted string
-            // into a buffer (the first argument)
-            write!(
-                f,
-                "{}: {:.3}°{} {:.3}°{}",
-                self.name,
-                self.lat.abs(),
-                lat_c,
-                self.lon.abs(),
-                lon_c
-            )
-        }
-    }
-
-    #[derive(Debug)]
-    struct Color {
-        red: u8,
-        green: u8,
-        blue: u8,
-    }
-    impl fmt::Display for Color {
-        // `f` is a buffer, and this method must write the formatted string into it
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(
-                f,
-                "RGB ({red}, {green}, {blue}) 0x{red:02X?}{green:02X?}{blue:02X?}",
-                red = self.red,
-                green = self.green,
-                blue = self.blue,
-            )
-        }
-    }
+    // `DisplayList` works for anything iterable whose items are `Display`.
+    println!("{}", DisplayList::new(1..=5));
+    println!(
+        "{}",
+        DisplayList::new(&["Alice", "Bob"])
+            .open("{ ")
+            .close(" }")
+            .separator(" | ")
+    );
+    // The formatting flags are forwarded to every item.
+    println!("{:.2}", DisplayList::new(&[1.0, 2.5, 3.33333]));
+    // Long lists can be truncated.
+    println!("{}", DisplayList::new(1..=100).indexed(true).truncate(3));
+    // And `{:#}` prints one item per line, indenting nested lists.
+    println!("{:#}", v);
+    let rows = vec![DisplayList::new(&v.0), DisplayList::new(&v.0)];
+    println!("{:#}", DisplayList::new(&rows).truncate(1));
 
     for city in [
         City {
@@ -222,4 +229,5 @@ pub fn main() {
         // for fmt::Display.
         println!("{}", color);
     }
+    Ok(())
 }
@@ -1,8 +1,15 @@
-pub fn main() -> () {
-    casting();
-    litterals();
-    inference();
-    aliasing();
+use crate::allocations;
+use crate::error::Error;
+
+pub fn main() -> Result<(), Error> {
+    allocations::section("casting", casting);
+    allocations::section("litterals", litterals);
+    allocations::section("inference", inference);
+    allocations::section("inference_steps", inference_steps)?;
+    allocations::section("aliasing", aliasing);
+    allocations::section("units", units);
+    allocations::section("durations_and_sizes", durations_and_sizes)?;
+    Ok(())
 }
 
 fn casting() -> () {
@@ -24,6 +31,9 @@ fn casting() -> () {
 
     println!("Casting: {} -> {} -> {}", decimal, integer, character);
 
+    // (The `cast_rules` example checks the rules below for every pair of
+    // numeric types.)
+
     // when casting any value to an unsigned type, T,
     // T::MAX + 1 is added or subtracted until the value
     // fits into the new type
@@ -86,7 +96,8 @@ fn litterals() -> () {
     let i = 1;
     let f = 1.0;
 
-    // `size_of_val` returns the size of a variable in bytes
+    // `size_of_val` returns the size of a variable in bytes. See
+    // `BINARY=layout cargo run` for the layout of the example types.
     println!("size of `x` in bytes: {}", std::mem::size_of_val(&x));
     println!("size of `y` in bytes: {}", std::mem::size_of_val(&y));
     println!("size of `z` in bytes: {}", std::mem::size_of_val(&z));
@@ -111,6 +122,24 @@ fn inference() -> () {
     println!("{:?}", vec);
 }
 
+fn inference_steps() -> Result<(), Error> {
+    use crate::inference;
+
+    // The same program, for the engine to find its types step by step, then
+    // without the `push`, so nothing tells what the vector holds
+    for source in &[
+        "let elem = 5u8;\nlet mut vec = Vec::new();\nvec.push(elem);\n",
+        "let elem = 5u8;\nlet mut vec = Vec::new();\n",
+    ] {
+        let found = inference::infer(source).map_err(|error| Error::parse(source, error))?;
+        println!("{}", found.trace(source));
+        for error in &found.errors {
+            println!("{}", error.render(source));
+        }
+    }
+    Ok(())
+}
+
 fn aliasing() -> () {
     // `NanoSecond` is a new name for `u64`.
     type NanoSecond = u64;
@@ -126,7 +155,7 @@ fn aliasing() -> () {
     let inches: Inch = 2 as u64_t;
 
     // Note that type aliases *don't* provide any extra type safety, because
-    // aliases are *not* new types
+    // aliases are *not* new types. See `units` below for types that do.
     println!(
         "{} nanoseconds + {} inches = {} unit?",
         nanoseconds,
@@ -134,3 +163,110 @@ fn aliasing() -> () {
         nanoseconds + inches
     );
 }
+
+fn units() {
+    use crate::units::*;
+
+    // A `Quantity` knows its unit, and so its dimension.
+    let nanoseconds: Quantity<Nanosecond> = Quantity::new(5.0);
+    let inches: Quantity<Inch> = Quantity::new(2.0);
+
+    // let nonsense = nanoseconds + inches;
+    // TODO ^ Try uncommenting this line
+
+    // Quantities of the same dimension can only be mixed once converted
+    let centimeters: Quantity<Centimeter> = Quantity::new(10.0);
+    let total = inches.convert::<Centimeter>() + centimeters;
+    println!("{} + {} = {:.2}", inches, centimeters, total);
+    println!(
+        "{} = {} = {}",
+        nanoseconds,
+        nanoseconds.convert::<Second>(),
+        nanoseconds.convert::<Millisecond>()
+    );
+
+    // Multiplication and division make quantities of derived units, with
+    // the dimension worked out by the compiler
+    let speed = inches / nanoseconds;
+    println!(
+        "{:.1} is a {}, or {:.0}",
+        speed,
+        speed.dimension(),
+        speed.convert::<MeterPerSecond>()
+    );
+
+    let marathon: Quantity<Kilometer> = Quantity::new(42.195);
+    let time = Quantity::<Hour>::new(2.0) + Quantity::<Minute>::new(35.0).convert::<Hour>();
+    let pace: Quantity<KilometerPerHour> = marathon / time;
+    println!(
+        "{} in {:.2} is {:.2}, or {:.2}",
+        marathon,
+        time,
+        pace,
+        pace.convert::<MilePerHour>()
+    );
+
+    let field: Quantity<SquareMeter> = Quantity::<Meter>::new(105.0) * Quantity::<Meter>::new(68.0);
+    println!("a football field is {} ({})", field, field.dimension());
+    // TODO ^ Try converting `field` to `Meter`
+
+    // F = m·a
+    let mass = Quantity::<Pound>::new(150.0).convert::<Kilogram>();
+    let gravity: Quantity<MeterPerSecondSquared> = Quantity::new(9.81);
+    let weight = (mass * gravity).convert::<Newton>();
+    println!(
+        "{:.1} weighs {:.1} on Earth",
+        Quantity::<Pound>::new(150.0),
+        weight
+    );
+
+    let grams = Quantity::<Gram>::new(500.0);
+    println!(
+        "{:?} less {} is {:.3}",
+        mass,
+        grams,
+        mass - grams.convert::<Kilogram>()
+    );
+}
+
+fn durations_and_sizes() -> Result<(), Error> {
+    use crate::human::{ByteSize, Duration};
+
+    // Like `NanoSecond`, a `Duration` is a number of nanoseconds, but it
+    // parses and prints the way people write durations.
+    let duration: Duration = "1h30m"
+        .parse()
+        .map_err(|error| Error::parse("1h30m", error))?;
+    println!(
+        "{} is {} ns, about {:.1}",
+        duration,
+        duration.as_nanos(),
+        duration
+    );
+    for text in ["250ms", "1.5s", "90s", "1d 2h 3m", "0.000001ms", "0s"] {
+        let duration: Duration = text.parse().map_err(|error| Error::parse(text, error))?;
+        println!("{:>10} -> {:<10} {:.2}", text, duration, duration);
+    }
+
+    let size: ByteSize = "1.5GiB"
+        .parse()
+        .map_err(|error| Error::parse("1.5GiB", error))?;
+    println!(
+        "{} is {} bytes, {:#} in decimal units",
+        size,
+        size.as_bytes(),
+        size
+    );
+    for text in ["512", "4 KiB", "10MB", "1000", "1500kb"] {
+        let size: ByteSize = text.parse().map_err(|error| Error::parse(text, error))?;
+        println!("{:>10} -> {:<10} {:.1} {:#.1}", text, size, size, size);
+    }
+
+    for text in ["", "1h30", "5 parsecs", "h", "1.2.3s", "1GiB 1MiB"] {
+        match text.parse::<Duration>() {
+            Ok(duration) => println!("{:?} -> {}", text, duration),
+            Err(error) => println!("{:?} -> error: {}", text, error),
+        }
+    }
+    Ok(())
+}
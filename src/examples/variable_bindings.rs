@@ -1,9 +1,17 @@
-pub fn main() -> () {
-    intro();
-    mutability();
-    scope_and_shadowing();
-    declare_first();
-    freezing();
+use crate::allocations;
+use crate::error::Error;
+
+pub fn main() -> Result<(), Error> {
+    allocations::section("intro", intro);
+    allocations::section("mutability", mutability);
+    allocations::section("scope_and_shadowing", scope_and_shadowing);
+    allocations::section("declare_first", declare_first);
+    allocations::section("freezing", freezing);
+    allocations::section("checked", checked);
+    allocations::section("timeline", timeline);
+    allocations::section("borrows", borrows);
+    allocations::section("initialization", initialization);
+    Ok(())
 }
 
 fn intro() -> () {
@@ -120,3 +128,195 @@ fn freezing() -> () {
     // Ok! `_mutable_integer` is not frozen in this scope
     _mutable_integer = 3;
 }
+
+// The `// Error!` lines above, uncommented, through the checker of the
+// `bindings` language
+fn checked() {
+    use crate::bindings;
+
+    for source in [
+        // mutability
+        "let _immutable_binding = 1;
+_immutable_binding += 1;",
+        // scope_and_shadowing
+        "{
+    let short_lived_binding = 2;
+}
+println!(\"outer short: {}\", short_lived_binding);",
+        // declare_first
+        "let another_binding;
+println!(\"another binding: {}\", another_binding);",
+        // freezing
+        "let mut _mutable_integer = 7i32;
+{
+    let _mutable_integer = _mutable_integer;
+    _mutable_integer = 50;
+}",
+    ] {
+        println!("{}", bindings::report(source));
+    }
+
+    // And with the errors commented out again, the programs run
+    print!(
+        "{}",
+        bindings::report(
+            "let a_binding;
+{
+    let x = 2;
+    a_binding = x * x;
+}
+println!(\"a binding: {}\", a_binding);"
+        )
+    );
+    print!(
+        "{}",
+        bindings::report(
+            "let mut _mutable_integer = 7i32;
+{
+    let _mutable_integer = _mutable_integer;
+}
+_mutable_integer = 3;
+println!(\"{}\", _mutable_integer);"
+        )
+    );
+}
+
+// The shadowing of `scope_and_shadowing` and the freezing of `freezing`,
+// binding by binding
+fn timeline() {
+    use crate::bindings;
+
+    let shadowing = "let shadowed_binding = 1;
+{
+    println!(\"before being shadowed: {}\", shadowed_binding);
+    let shadowed_binding = \"abc\";
+    println!(\"shadowed in inner block: {}\", shadowed_binding);
+}
+println!(\"outside inner block: {}\", shadowed_binding);
+let shadowed_binding = 2;
+println!(\"shadowed in outer block: {}\", shadowed_binding);
+";
+    let freezing = "let mut _mutable_integer = 7i32;
+{
+    let _mutable_integer = _mutable_integer;
+}
+_mutable_integer = 3;
+";
+
+    for source in [shadowing, freezing] {
+        match bindings::timeline(source) {
+            Ok(timeline) => println!("{}", timeline),
+            Err(error) => println!("{}\n", error.render(source)),
+        }
+    }
+}
+
+// Moving out of a value that is still borrowed, and the other mistakes of
+// the kind, through the borrow checker of the `bindings` language. The
+// second one is why `len` in the linked list of `custom_types` takes the
+// tail by `ref`
+fn borrows() {
+    use crate::bindings;
+
+    for source in [
+        // A `String` moves, and can't be used afterwards
+        "let list = String::from(\"1, 2, 3\");
+let tail = list;
+println!(\"{}\", list);",
+        // Taking the tail out of a borrowed list, as `Cons(_, tail)` would
+        "let list = String::from(\"1, 2, 3\");
+let this = &list;
+let tail = list;
+println!(\"{}\", this);",
+        "let mut list = String::from(\"1, 2, 3\");
+let first = &mut list;
+let second = &mut list;
+println!(\"{} {}\", first, second);",
+        "let mut list = String::from(\"1, 2, 3\");
+let reader = &list;
+let writer = &mut list;
+println!(\"{}\", reader);",
+        "let mut length = 3;
+let reader = &length;
+length = 4;
+println!(\"{}\", reader);",
+        "let tail;
+{
+    let list = String::from(\"1, 2, 3\");
+    tail = &list;
+}
+println!(\"{}\", tail);",
+    ] {
+        println!("{}", bindings::report(source));
+    }
+
+    // A borrow ends with its last use, not with its scope
+    print!(
+        "{}",
+        bindings::report(
+            "let mut list = String::from(\"1, 2\");
+let reader = &list;
+println!(\"{}\", reader);
+let writer = &mut list;
+*writer = String::from(\"1, 2, 3\");
+println!(\"{}\", list);"
+        )
+    );
+}
+
+// `another_binding` of `declare_first`, initialized on some paths only: a
+// read is fine when every path to it goes through an assignment, whatever
+// the conditions would be when run
+fn initialization() {
+    use crate::bindings;
+
+    for source in [
+        // rustc doesn't look at the condition, even when it can't be false
+        "let another_binding;
+if true {
+    another_binding = 1;
+}
+println!(\"another binding: {}\", another_binding);",
+        "let mut another_binding;
+let mut x = 2;
+while x < 10 {
+    x = x * x;
+    another_binding = x;
+}
+println!(\"another binding: {}\", another_binding);",
+        "let mut another_binding;
+let mut x = 2;
+loop {
+    if x > 10 {
+        break;
+    }
+    x = x * x;
+    another_binding = x;
+}
+println!(\"another binding: {}\", another_binding);",
+    ] {
+        println!("{}", bindings::report(source));
+    }
+
+    // Assigned on every path, or leaving early on the one that doesn't
+    print!(
+        "{}",
+        bindings::report(
+            "let another_binding;
+let x = 2;
+if x > 1 {
+    another_binding = 1;
+} else if x > 0 {
+    another_binding = 2;
+} else {
+    return;
+}
+let mut squares;
+loop {
+    squares = x * x;
+    break;
+}
+println!(\"another binding: {}, {}\", another_binding, squares);"
+        )
+    );
+}
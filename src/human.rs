@@ -0,0 +1,391 @@
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+use std::time;
+
+// Durations and byte sizes that parse from and print to the strings people
+// write: `1h30m`, `250ms`, `1.5GiB`.
+//
+// Both print exactly by default, so that what they print parses back to the
+// same value, and with the largest unit that fits and a fixed number of
+// decimals when given a precision:
+//
+//     let d: Duration = "1h30m".parse()?;
+//     println!("{} {:.2}", d, d); // 1h30m 1.50h
+//     let s: ByteSize = "1.5GiB".parse()?;
+//     println!("{} {:.0} {:#}", s, s, s); // 1.5GiB 2GiB 1.610612736GB
+
+// A span of time, to the nanosecond. It can't be negative, and is at most
+// `u64::MAX` nanoseconds: about 584 years.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Duration {
+    nanoseconds: u64,
+}
+
+// A number of bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ByteSize {
+    bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    // The text that should have been a number
+    InvalidNumber(String),
+    // The number that isn't followed by a unit
+    MissingUnit(String),
+    UnknownUnit(String),
+    // A byte size is a single number and unit, `1GiB512MiB` isn't one
+    TooManyParts,
+    Overflow,
+}
+
+// From the largest unit to the smallest
+const DURATION_UNITS: [(&str, u64); 7] = [
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+const BINARY_UNITS: [(&str, u64); 7] = [
+    ("EiB", 1 << 60),
+    ("PiB", 1 << 50),
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+    ("B", 1),
+];
+
+const DECIMAL_UNITS: [(&str, u64); 7] = [
+    ("EB", 1_000_000_000_000_000_000),
+    ("PB", 1_000_000_000_000_000),
+    ("TB", 1_000_000_000_000),
+    ("GB", 1_000_000_000),
+    ("MB", 1_000_000),
+    ("kB", 1_000),
+    ("B", 1),
+];
+
+impl Duration {
+    pub const ZERO: Duration = Duration { nanoseconds: 0 };
+    pub const MAX: Duration = Duration {
+        nanoseconds: u64::MAX,
+    };
+
+    pub fn from_nanos(nanoseconds: u64) -> Duration {
+        Duration { nanoseconds }
+    }
+
+    pub fn as_nanos(self) -> u64 {
+        self.nanoseconds
+    }
+
+    pub fn checked_add(self, other: Duration) -> Option<Duration> {
+        self.nanoseconds
+            .checked_add(other.nanoseconds)
+            .map(Duration::from_nanos)
+    }
+}
+
+// Saturates at `Duration::MAX`, `std::time::Duration` goes much further
+impl From<time::Duration> for Duration {
+    fn from(duration: time::Duration) -> Duration {
+        Duration::from_nanos(u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX))
+    }
+}
+
+impl From<Duration> for time::Duration {
+    fn from(duration: Duration) -> time::Duration {
+        time::Duration::from_nanos(duration.nanoseconds)
+    }
+}
+
+impl ByteSize {
+    pub fn from_bytes(bytes: u64) -> ByteSize {
+        ByteSize { bytes }
+    }
+
+    pub fn as_bytes(self) -> u64 {
+        self.bytes
+    }
+}
+
+// `1h30m`, `1m30.5s` or `1d 2h`: numbers, possibly with decimals, each
+// followed by a unit among `d`, `h`, `m`, `s`, `ms`, `us` (or `µs`) and `ns`.
+impl FromStr for Duration {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Duration, ParseError> {
+        let mut nanoseconds: u64 = 0;
+        for (number, unit) in split(s)? {
+            let unit = if unit == "µs" { "us" } else { unit };
+            let factor = lookup(&DURATION_UNITS, unit, false)?;
+            nanoseconds = nanoseconds
+                .checked_add(amount(number, factor)?)
+                .ok_or(ParseError::Overflow)?;
+        }
+        Ok(Duration::from_nanos(nanoseconds))
+    }
+}
+
+// A number of bytes, like `512`, or a number followed by a unit like `1.5GiB`
+// or `10 MB`. Units are case-insensitive: `kb` is `kB`.
+impl FromStr for ByteSize {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<ByteSize, ParseError> {
+        let trimmed = s.trim();
+        if !trimmed.is_empty() && trimmed.chars().all(|c| c.is_ascii_digit() || c == '_') {
+            return amount(trimmed, 1).map(ByteSize::from_bytes);
+        }
+
+        let parts = split(s)?;
+        if parts.len() > 1 {
+            return Err(ParseError::TooManyParts);
+        }
+        let (number, unit) = parts[0];
+        let factor =
+            lookup(&BINARY_UNITS, unit, true).or_else(|_| lookup(&DECIMAL_UNITS, unit, true))?;
+        amount(number, factor).map(ByteSize::from_bytes)
+    }
+}
+
+// Split `1h 30.5m` into `[("1", "h"), ("30.5", "m")]`
+fn split(s: &str) -> Result<Vec<(&str, &str)>, ParseError> {
+    let mut parts = Vec::new();
+    let mut rest = s.trim_start();
+    if rest.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_'))
+            .unwrap_or(rest.len());
+        let (number, after) = rest.split_at(number_end);
+        if number.is_empty() {
+            let word_end = after.find(char::is_whitespace).unwrap_or(after.len());
+            return Err(ParseError::InvalidNumber(after[..word_end].to_owned()));
+        }
+
+        let after = after.trim_start();
+        let unit_end = after
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(after.len());
+        let (unit, after) = after.split_at(unit_end);
+        if unit.is_empty() {
+            return Err(ParseError::MissingUnit(number.to_owned()));
+        }
+
+        parts.push((number, unit));
+        rest = after.trim_start();
+    }
+    Ok(parts)
+}
+
+fn lookup(units: &[(&str, u64)], unit: &str, ignore_case: bool) -> Result<u64, ParseError> {
+    units
+        .iter()
+        .find(|(name, _)| *name == unit || (ignore_case && name.eq_ignore_ascii_case(unit)))
+        .map(|&(_, factor)| factor)
+        .ok_or_else(|| ParseError::UnknownUnit(unit.to_owned()))
+}
+
+// `number` times `factor`, exactly, with the fraction rounded down to a
+// whole amount of the smallest unit
+fn amount(number: &str, factor: u64) -> Result<u64, ParseError> {
+    let invalid = || ParseError::InvalidNumber(number.to_owned());
+    let digits = number.replace('_', "");
+    let (integer, fraction) = match digits.find('.') {
+        Some(dot) => (&digits[..dot], &digits[dot + 1..]),
+        None => (&digits[..], ""),
+    };
+    if (integer.is_empty() && fraction.is_empty()) || fraction.contains('.') {
+        return Err(invalid());
+    }
+
+    let mut total: u128 = 0;
+    for digit in integer.bytes() {
+        total = total
+            .checked_mul(10)
+            .and_then(|total| total.checked_add(u128::from(digit - b'0')))
+            .filter(|&total| total <= u128::from(u64::MAX))
+            .ok_or(ParseError::Overflow)?;
+    }
+    let mut total = total * u128::from(factor);
+
+    // factor × 0.d₁d₂…dₙ = (d₁ × factor + factor × 0.d₂…dₙ) / 10, from the
+    // last digit up. Rounding down at each step gives the same result as
+    // rounding down once at the end.
+    let part = fraction.bytes().rev().fold(0u128, |part, digit| {
+        (u128::from(digit - b'0') * u128::from(factor) + part) / 10
+    });
+    total += part;
+
+    u64::try_from(total).map_err(|_| ParseError::Overflow)
+}
+
+// Every non-zero unit, `1h30m` or `2m0.5s` style, which parses back to the
+// same duration. With a precision, the largest unit that fits instead:
+// `{:.1}` gives `1.5h`.
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(precision) = f.precision() {
+            let (unit, factor) = largest_unit(&DURATION_UNITS, self.nanoseconds);
+            let value = self.nanoseconds as f64 / factor as f64;
+            return pad(f, &format!("{:.*}{}", precision, value, unit));
+        }
+
+        if self.nanoseconds == 0 {
+            return pad(f, "0s");
+        }
+        let mut text = String::new();
+        let mut rest = self.nanoseconds;
+        for &(unit, factor) in &DURATION_UNITS {
+            if rest >= factor {
+                text += &format!("{}{}", rest / factor, unit);
+                rest %= factor;
+            }
+        }
+        pad(f, &text)
+    }
+}
+
+// In binary units, `1.5GiB`, or in decimal ones with `{:#}`, `1.61GB`.
+// Exact unless given a precision.
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let units: &[(&str, u64)] = if f.alternate() {
+            &DECIMAL_UNITS
+        } else {
+            &BINARY_UNITS
+        };
+        let (unit, factor) = largest_unit(units, self.bytes);
+
+        let number = match f.precision() {
+            Some(precision) => format!("{:.*}", precision, self.bytes as f64 / factor as f64),
+            None => exact_quotient(self.bytes, factor),
+        };
+        pad(f, &format!("{}{}", number, unit))
+    }
+}
+
+// `Formatter::pad` would treat the precision as a maximum width
+fn pad(f: &mut fmt::Formatter, text: &str) -> fmt::Result {
+    let padding = f.width().unwrap_or(0).saturating_sub(text.chars().count());
+    let (before, after) = match f.align() {
+        Some(fmt::Alignment::Right) => (padding, 0),
+        Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+        Some(fmt::Alignment::Left) | None => (0, padding),
+    };
+    let fill = f.fill().to_string();
+    write!(f, "{}{}{}", fill.repeat(before), text, fill.repeat(after))
+}
+
+fn largest_unit<'a>(units: &[(&'a str, u64)], value: u64) -> (&'a str, u64) {
+    units
+        .iter()
+        .copied()
+        .find(|&(_, factor)| value >= factor)
+        .unwrap_or(units[units.len() - 1])
+}
+
+// `value / divisor` in decimal, for divisors that are powers of 2 or of 10,
+// whose quotients always have a finite number of decimals
+fn exact_quotient(value: u64, divisor: u64) -> String {
+    let mut text = (value / divisor).to_string();
+    let mut remainder = u128::from(value % divisor);
+    if remainder != 0 {
+        text.push('.');
+    }
+    while remainder != 0 {
+        remainder *= 10;
+        text += &(remainder / u128::from(divisor)).to_string();
+        remainder %= u128::from(divisor);
+    }
+    text
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "nothing to parse"),
+            ParseError::InvalidNumber(text) => write!(f, "`{}` is not a number", text),
+            ParseError::MissingUnit(number) => write!(f, "`{}` needs a unit", number),
+            ParseError::UnknownUnit(unit) => write!(f, "unknown unit `{}`", unit),
+            ParseError::TooManyParts => write!(f, "expected a single number and unit"),
+            ParseError::Overflow => write!(f, "too large"),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_round_trip() {
+        for &nanoseconds in &[
+            0,
+            1,
+            999,
+            1_000,
+            1_500_000,
+            60_000_000_001,
+            86_400_000_000_000,
+            u64::MAX,
+        ] {
+            let duration = Duration::from_nanos(nanoseconds);
+            assert_eq!(duration.to_string().parse(), Ok(duration));
+        }
+    }
+
+    #[test]
+    fn byte_size_round_trip() {
+        for &bytes in &[0, 1, 1023, 1024, 1500, 1 << 30 | 1, u64::MAX] {
+            let size = ByteSize::from_bytes(bytes);
+            assert_eq!(size.to_string().parse(), Ok(size));
+            assert_eq!(format!("{:#}", size).parse(), Ok(size));
+        }
+    }
+
+    #[test]
+    fn duration_overflow() {
+        assert_eq!(Duration::MAX.to_string(), "213503d23h34m33s709ms551us615ns");
+        assert_eq!("213504d".parse::<Duration>(), Err(ParseError::Overflow));
+        assert_eq!("213503d 1d".parse::<Duration>(), Err(ParseError::Overflow));
+        assert_eq!(
+            "99999999999999999999ns".parse::<Duration>(),
+            Err(ParseError::Overflow)
+        );
+        assert_eq!(Duration::MAX.checked_add(Duration::from_nanos(1)), None);
+    }
+
+    #[test]
+    fn byte_size_overflow() {
+        assert_eq!("16EiB".parse::<ByteSize>(), Err(ParseError::Overflow));
+        assert_eq!(
+            "15.9999EiB"
+                .parse::<ByteSize>()
+                .map(|s| s > ByteSize::from_bytes(1 << 63)),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn too_many_parts() {
+        assert_eq!(
+            "1GiB 1MiB".parse::<ByteSize>(),
+            Err(ParseError::TooManyParts)
+        );
+    }
+}
@@ -0,0 +1,981 @@
+use std::error;
+use std::fmt;
+
+use crate::source::{self, Span, Spanned};
+
+mod syntax;
+
+pub use syntax::{parse, Expr, Signature, Stmt};
+
+// The types of a program found the way rustc finds them, by unification,
+// with each step it took:
+//
+//     let inference = inference::infer("let elem = 5u8;\nlet mut vec = Vec::new();\nvec.push(elem);")?;
+//     print!("{}", inference.trace(source));
+//
+//     1 | let elem = 5u8;
+//       |   `5u8` is a `u8`
+//       |   `elem`: u8
+//     2 | let mut vec = Vec::new();
+//       |   `Vec::new` takes () and returns Vec<?1>
+//       |   `vec`: Vec<?1>
+//     3 | vec.push(elem);
+//       |   `push` on Vec<?1> takes (?1) and returns ()
+//       |   ?1 is u8
+//
+// `?1` is a type still to be found. A literal without a suffix is an integer
+// or a float to be found, which becomes an `i32` or an `f64` if nothing says
+// which one; any other type left to be found needs an annotation.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    // `u8`, `bool`, `String`, `&str`, `()`..., and `Vec<T>` with its
+    // parameter. `_` in an annotation, and the type parameters of a `fn`,
+    // until they are replaced by variables.
+    Named(String, Vec<Type>),
+    // A type to be found
+    Var(usize),
+}
+
+// What a variable may be
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Any,
+    // `{integer}` and `{float}`, in rustc's messages
+    Integer,
+    Float,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Var {
+    kind: Kind,
+    found: Option<Type>,
+    // The expression it is the type of
+    origin: Span,
+}
+
+// Something the engine learned, while at `span`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub span: Span,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inference {
+    pub steps: Vec<Step>,
+    // The name of every `let`, and its type, with `_` where it wasn't found
+    pub bindings: Vec<(Spanned<String>, Type)>,
+    pub errors: Vec<Error>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnexpectedCharacter(char),
+    InvalidNumber(String),
+    UnterminatedString,
+    // What was found, and what was expected instead
+    UnexpectedToken(String, &'static str),
+    UnexpectedEnd(&'static str),
+    NotInScope(String),
+    UnknownFunction(String),
+    UnknownType(String),
+    // The method, and the type it was looked for on
+    UnknownMethod(String, Type),
+    // The arguments the function takes, and those it was given
+    ArgumentCount(usize, usize),
+    Mismatched { expected: Type, found: Type },
+    // The operator, and the type of its left operand
+    UnsupportedOperation(&'static str, Type),
+    // For a binding, its name and what is known of its type, for a method
+    // call nothing
+    AnnotationsNeeded(Option<(String, Type)>),
+    // A variable found to be a type containing it, `?1` being `Vec<?1>`
+    Cyclic,
+    // An integer literal too large for its type, and the type
+    LiteralOutOfRange(String),
+}
+
+const INTEGERS: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+const TYPES: [&str; 7] = ["f32", "f64", "bool", "char", "String", "&str", "()"];
+
+// Parses `source` and finds its types. Only syntax errors stop it, the
+// others are in the inference.
+pub fn infer(source: &str) -> Result<Inference, Error> {
+    let program = parse(source)?;
+    let mut engine = Engine {
+        source,
+        vars: Vec::new(),
+        scope: Vec::new(),
+        functions: prelude(),
+        steps: Vec::new(),
+        errors: Vec::new(),
+        bindings: Vec::new(),
+        reported: Vec::new(),
+        literals: Vec::new(),
+    };
+    for statement in &program.statements {
+        engine.statement(statement);
+    }
+    Ok(engine.finish())
+}
+
+// The functions every program can call
+fn prelude() -> Vec<Signature> {
+    let t = || Type::named("T");
+    let function = |name: &str, generics: &[&str], parameters, output| Signature {
+        name: Spanned {
+            node: name.to_owned(),
+            span: Span { start: 0, end: 0 },
+        },
+        generics: generics.iter().map(|name| name.to_string()).collect(),
+        parameters,
+        output,
+    };
+    vec![
+        function("Vec::new", &["T"], vec![], Type::vec(t())),
+        function("String::new", &[], vec![], Type::named("String")),
+        function(
+            "String::from",
+            &[],
+            vec![Type::named("&str")],
+            Type::named("String"),
+        ),
+        function("drop", &["T"], vec![t()], Type::unit()),
+    ]
+}
+
+struct Engine<'a> {
+    source: &'a str,
+    vars: Vec<Var>,
+    // The bindings, latest last, so that the last one with a name is the one
+    // it refers to
+    scope: Vec<(String, Type)>,
+    functions: Vec<Signature>,
+    steps: Vec<Step>,
+    errors: Vec<Error>,
+    bindings: Vec<(Spanned<String>, Type)>,
+    // The variables an error was already reported for
+    reported: Vec<usize>,
+    // The integer literals, their type, and whether they are negated, to
+    // check they fit once the types are known
+    literals: Vec<(Span, Type, bool)>,
+}
+
+// Why two types can't be made the same
+enum Unification {
+    Mismatched,
+    Cyclic,
+}
+
+impl<'a> Engine<'a> {
+    fn statement(&mut self, statement: &Spanned<Stmt>) {
+        match &statement.node {
+            Stmt::Let {
+                name,
+                annotation,
+                value,
+            } => {
+                let annotation = annotation
+                    .as_ref()
+                    .map(|annotation| (self.annotation(annotation), annotation.span));
+                let value = value
+                    .as_ref()
+                    .map(|value| (self.expression(value), value.span));
+                let ty = match (annotation, value) {
+                    (Some((annotation, _)), Some((value, span))) => {
+                        self.expect(&annotation, &value, span);
+                        annotation
+                    }
+                    (Some((ty, _)), None) | (None, Some((ty, _))) => ty,
+                    // Found from the assignments
+                    (None, None) => self.fresh(Kind::Any, name.span),
+                };
+                self.step(name.span, format!("`{}`: {}", name.node, self.resolve(&ty)));
+                self.scope.push((name.node.clone(), ty.clone()));
+                self.bindings.push((name.clone(), ty));
+            }
+            Stmt::Assign { name, value } => {
+                let found = self.expression(value);
+                match self.lookup(&name.node) {
+                    Some(expected) => self.expect(&expected, &found, value.span),
+                    None => self.error(ErrorKind::NotInScope(name.node.clone()), name.span),
+                }
+            }
+            Stmt::Function(signature) => {
+                let types = signature.parameters.iter().chain(Some(&signature.output));
+                for ty in types {
+                    if let Some(unknown) = ty.unknown(&signature.generics) {
+                        self.error(ErrorKind::UnknownType(unknown), signature.name.span);
+                    }
+                }
+                self.functions.push(signature.clone());
+            }
+            Stmt::Expr(expr) => {
+                self.expression(expr);
+            }
+        }
+    }
+
+    // The type of an annotation, with variables for its `_`s
+    fn annotation(&mut self, annotation: &Spanned<Type>) -> Type {
+        if let Some(unknown) = annotation.node.unknown(&[]) {
+            self.error(ErrorKind::UnknownType(unknown), annotation.span);
+            return self.fresh(Kind::Any, annotation.span);
+        }
+        self.instantiate(&annotation.node, &[], annotation.span)
+    }
+
+    fn expression(&mut self, expr: &Spanned<Expr>) -> Type {
+        let text = self.text(expr.span);
+        match &expr.node {
+            Expr::Int(None) => {
+                let ty = self.fresh(Kind::Integer, expr.span);
+                self.step(expr.span, format!("`{}` is an integer, {}", text, ty));
+                self.literals.push((expr.span, ty.clone(), false));
+                ty
+            }
+            Expr::Float(None) => {
+                let ty = self.fresh(Kind::Float, expr.span);
+                self.step(expr.span, format!("`{}` is a float, {}", text, ty));
+                ty
+            }
+            Expr::Int(Some(suffix)) | Expr::Float(Some(suffix)) => {
+                self.step(expr.span, format!("`{}` is a `{}`", text, suffix));
+                if let Expr::Int(_) = expr.node {
+                    self.literals.push((expr.span, Type::named(suffix), false));
+                }
+                Type::named(suffix)
+            }
+            Expr::Str => Type::named("&str"),
+            Expr::Bool => Type::named("bool"),
+            Expr::Variable(name) => match self.lookup(name) {
+                Some(ty) => ty,
+                None => {
+                    self.error(ErrorKind::NotInScope(name.clone()), expr.span);
+                    self.unknown(expr.span)
+                }
+            },
+            Expr::Call {
+                function,
+                arguments,
+            } => {
+                let signature = self
+                    .functions
+                    .iter()
+                    .rev()
+                    .find(|signature| signature.name.node == function.node)
+                    .cloned();
+                let signature = match signature {
+                    Some(signature) => signature,
+                    None => {
+                        let kind = ErrorKind::UnknownFunction(function.node.clone());
+                        self.error(kind, function.span);
+                        return self.unknown(expr.span);
+                    }
+                };
+                // The same variable for each use of a type parameter
+                let mut vars = Vec::new();
+                let generics = &signature.generics;
+                let parameters: Vec<Type> = signature
+                    .parameters
+                    .iter()
+                    .map(|ty| self.instantiate_with(ty, generics, &mut vars, expr.span))
+                    .collect();
+                let output =
+                    self.instantiate_with(&signature.output, generics, &mut vars, expr.span);
+                self.step(
+                    function.span,
+                    format!(
+                        "`{}` takes ({}) and returns {}",
+                        function.node,
+                        list(&parameters),
+                        output
+                    ),
+                );
+                self.arguments(&parameters, arguments, function.span);
+                output
+            }
+            Expr::Method {
+                receiver,
+                method,
+                arguments,
+            } => {
+                let ty = self.expression(receiver);
+                let ty = self.shallow(&ty);
+                // The type of the receiver has to be known by now
+                if let Type::Var(var) = ty {
+                    if self.vars[var].kind == Kind::Any {
+                        if !self.reported.contains(&var) {
+                            self.error(ErrorKind::AnnotationsNeeded(None), receiver.span);
+                            self.reported.push(var);
+                        }
+                        return self.unknown(expr.span);
+                    }
+                }
+                let (parameters, output) = match ty.method(&method.node) {
+                    Some(signature) => signature,
+                    None => {
+                        let kind = ErrorKind::UnknownMethod(method.node.clone(), self.shown(&ty));
+                        self.error(kind, method.span);
+                        return self.unknown(expr.span);
+                    }
+                };
+                self.step(
+                    method.span,
+                    format!(
+                        "`{}` on {} takes ({}) and returns {}",
+                        method.node,
+                        self.resolve(&ty),
+                        list(&parameters),
+                        output
+                    ),
+                );
+                self.arguments(&parameters, arguments, method.span);
+                output
+            }
+            Expr::Index(vec, index) => {
+                let found = self.expression(vec);
+                let element = self.fresh(Kind::Any, expr.span);
+                self.expect(&Type::vec(element.clone()), &found, vec.span);
+                let found = self.expression(index);
+                self.expect(&Type::named("usize"), &found, index.span);
+                self.step(
+                    expr.span,
+                    format!(
+                        "`{}` is an element of {}",
+                        text,
+                        self.resolve(&Type::vec(element.clone()))
+                    ),
+                );
+                element
+            }
+            Expr::Vec(items) => {
+                let element = self.fresh(Kind::Any, expr.span);
+                self.step(
+                    expr.span,
+                    format!("`{}` is a {}", text, Type::vec(element.clone())),
+                );
+                for item in items {
+                    let found = self.expression(item);
+                    self.expect(&element, &found, item.span);
+                }
+                Type::vec(element)
+            }
+            // Anything can be printed, as far as types go
+            Expr::Print(arguments) => {
+                for argument in arguments {
+                    self.expression(argument);
+                }
+                Type::unit()
+            }
+            Expr::Negate(operand) => {
+                let ty = self.expression(operand);
+                // `-128i8` fits, the literal being negated before it is
+                // checked
+                if let Expr::Int(_) = operand.node {
+                    if let Some(literal) = self.literals.last_mut() {
+                        literal.2 = true;
+                    }
+                }
+                let resolved = self.resolve(&ty);
+                let signed = match &resolved {
+                    Type::Named(name, _) => name.starts_with('i') || resolved.is_float(),
+                    Type::Var(_) => true,
+                };
+                if !signed {
+                    let ty = self.shown(&ty);
+                    self.error(ErrorKind::UnsupportedOperation("-", ty), expr.span);
+                }
+                ty
+            }
+            Expr::Binary(op, left, right) => {
+                let ty = self.expression(left);
+                let found = self.expression(right);
+                self.expect(&ty, &found, right.span);
+                let resolved = self.resolve(&ty);
+                let number = match &resolved {
+                    Type::Named(..) => resolved.is_integer() || resolved.is_float(),
+                    Type::Var(_) => true,
+                };
+                if ["==", "!="].contains(op) {
+                    Type::named("bool")
+                } else if !number {
+                    let shown = self.shown(&ty);
+                    self.error(ErrorKind::UnsupportedOperation(op, shown), expr.span);
+                    ty
+                } else if ["<", "<=", ">", ">="].contains(op) {
+                    Type::named("bool")
+                } else {
+                    ty
+                }
+            }
+        }
+    }
+
+    // Checks the arguments of a call to what takes `parameters`
+    fn arguments(&mut self, parameters: &[Type], arguments: &[Spanned<Expr>], callee: Span) {
+        if parameters.len() != arguments.len() {
+            self.error(
+                ErrorKind::ArgumentCount(parameters.len(), arguments.len()),
+                callee,
+            );
+        }
+        for (expected, argument) in parameters.iter().zip(arguments) {
+            let found = self.expression(argument);
+            self.expect(expected, &found, argument.span);
+        }
+    }
+
+    // Unifies what was `found` at `span` with what was `expected` there
+    fn expect(&mut self, expected: &Type, found: &Type, span: Span) {
+        let kind = match self.unify(expected, found, span) {
+            Ok(()) => return,
+            Err(Unification::Mismatched) => ErrorKind::Mismatched {
+                expected: self.shown(expected),
+                found: self.shown(found),
+            },
+            Err(Unification::Cyclic) => ErrorKind::Cyclic,
+        };
+        self.error(kind, span);
+        // What is left unknown in either is so because of this error, not
+        // for lack of annotations
+        let (expected, found) = (self.resolve(expected), self.resolve(found));
+        let unknown =
+            (0..self.vars.len()).filter(|&var| expected.contains(var) || found.contains(var));
+        self.reported.extend(unknown.collect::<Vec<_>>());
+    }
+
+    // Makes `a` and `b` the same type, finding variables on the way, unless
+    // they can't be
+    fn unify(&mut self, a: &Type, b: &Type, span: Span) -> Result<(), Unification> {
+        let (a, b) = (self.shallow(a), self.shallow(b));
+        match (&a, &b) {
+            (Type::Var(a), Type::Var(b)) if a == b => Ok(()),
+            (Type::Var(var), Type::Var(other)) => {
+                let kind = match (self.vars[*var].kind, self.vars[*other].kind) {
+                    (Kind::Any, kind) | (kind, Kind::Any) => kind,
+                    (kind, other) if kind == other => kind,
+                    _ => return Err(Unification::Mismatched),
+                };
+                self.vars[*other].kind = kind;
+                self.bind(*var, b.clone(), span);
+                Ok(())
+            }
+            (Type::Var(var), ty) | (ty, Type::Var(var)) => {
+                let fits = match self.vars[*var].kind {
+                    Kind::Any => true,
+                    Kind::Integer => ty.is_integer(),
+                    Kind::Float => ty.is_float(),
+                };
+                if !fits {
+                    return Err(Unification::Mismatched);
+                }
+                // `?1` can't be a `Vec<?1>`
+                if self.resolve(ty).contains(*var) {
+                    return Err(Unification::Cyclic);
+                }
+                self.bind(*var, ty.clone(), span);
+                Ok(())
+            }
+            (Type::Named(name, parameters), Type::Named(other, others)) => {
+                if name != other || parameters.len() != others.len() {
+                    return Err(Unification::Mismatched);
+                }
+                parameters
+                    .iter()
+                    .zip(others)
+                    .try_for_each(|(a, b)| self.unify(a, b, span))
+            }
+        }
+    }
+
+    fn bind(&mut self, var: usize, ty: Type, span: Span) {
+        let text = format!("{} is {}", Type::Var(var), self.resolve(&ty));
+        self.vars[var].found = Some(ty);
+        self.step(span, text);
+    }
+
+    fn fresh(&mut self, kind: Kind, origin: Span) -> Type {
+        self.vars.push(Var {
+            kind,
+            found: None,
+            origin,
+        });
+        Type::Var(self.vars.len() - 1)
+    }
+
+    // The type of what an error was reported for, not to report it again
+    fn unknown(&mut self, origin: Span) -> Type {
+        let ty = self.fresh(Kind::Any, origin);
+        self.reported.push(self.vars.len() - 1);
+        ty
+    }
+
+    // `ty` with fresh variables for its `_`s and for the type parameters
+    // `generics`
+    fn instantiate(&mut self, ty: &Type, generics: &[String], origin: Span) -> Type {
+        self.instantiate_with(ty, generics, &mut Vec::new(), origin)
+    }
+
+    // The same, with the variables of the type parameters so far in `vars`
+    fn instantiate_with(
+        &mut self,
+        ty: &Type,
+        generics: &[String],
+        vars: &mut Vec<(String, Type)>,
+        origin: Span,
+    ) -> Type {
+        match ty {
+            Type::Named(name, _) if name == "_" => self.fresh(Kind::Any, origin),
+            Type::Named(name, _) if generics.contains(name) => {
+                if let Some((_, var)) = vars.iter().find(|(generic, _)| generic == name) {
+                    return var.clone();
+                }
+                let var = self.fresh(Kind::Any, origin);
+                vars.push((name.clone(), var.clone()));
+                var
+            }
+            Type::Named(name, parameters) => Type::Named(
+                name.clone(),
+                parameters
+                    .iter()
+                    .map(|ty| self.instantiate_with(ty, generics, vars, origin))
+                    .collect(),
+            ),
+            Type::Var(_) => ty.clone(),
+        }
+    }
+
+    // `ty`, or what the variable it is was found to be
+    fn shallow(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Var(var) => match &self.vars[*var].found {
+                Some(found) => self.shallow(found),
+                None => ty.clone(),
+            },
+            ty => ty.clone(),
+        }
+    }
+
+    // `ty` with every variable found replaced
+    fn resolve(&self, ty: &Type) -> Type {
+        match self.shallow(ty) {
+            Type::Named(name, parameters) => {
+                Type::Named(name, parameters.iter().map(|ty| self.resolve(ty)).collect())
+            }
+            var => var,
+        }
+    }
+
+    // `ty` as rustc shows it in errors, `{integer}` or `_` for what is still
+    // to be found
+    fn shown(&self, ty: &Type) -> Type {
+        match self.shallow(ty) {
+            Type::Named(name, parameters) => {
+                Type::Named(name, parameters.iter().map(|ty| self.shown(ty)).collect())
+            }
+            Type::Var(var) => Type::named(match self.vars[var].kind {
+                Kind::Any => "_",
+                Kind::Integer => "{integer}",
+                Kind::Float => "{float}",
+            }),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Type> {
+        self.scope
+            .iter()
+            .rev()
+            .find(|(binding, _)| binding == name)
+            .map(|(_, ty)| ty.clone())
+    }
+
+    fn text(&self, span: Span) -> &'a str {
+        self.source.get(span.start..span.end).unwrap_or("")
+    }
+
+    fn step(&mut self, span: Span, text: String) {
+        self.steps.push(Step { span, text });
+    }
+
+    fn error(&mut self, kind: ErrorKind, span: Span) {
+        self.errors.push(Error::new(kind, span));
+    }
+
+    // Defaults the integers and floats left, and reports the bindings whose
+    // type still isn't known
+    fn finish(mut self) -> Inference {
+        let end = Span {
+            start: self.source.len(),
+            end: self.source.len(),
+        };
+        for var in 0..self.vars.len() {
+            if self.vars[var].found.is_some() {
+                continue;
+            }
+            let default = match self.vars[var].kind {
+                Kind::Integer => "i32",
+                Kind::Float => "f64",
+                Kind::Any => continue,
+            };
+            let origin = self.text(self.vars[var].origin);
+            let text = format!("{} of `{}` defaults to {}", Type::Var(var), origin, default);
+            self.vars[var].found = Some(Type::named(default));
+            self.step(end, text);
+        }
+
+        for (span, ty, negated) in std::mem::take(&mut self.literals) {
+            let ty = self.resolve(&ty);
+            if !fits(self.text(span), &ty, negated) {
+                self.error(ErrorKind::LiteralOutOfRange(ty.to_string()), span);
+            }
+        }
+
+        // Once per variable, at the first binding it leaves unknown
+        let mut reported = std::mem::take(&mut self.reported);
+        let mut bindings = Vec::new();
+        for (name, ty) in std::mem::take(&mut self.bindings) {
+            let ty = self.resolve(&ty);
+            let unknown: Vec<usize> = (0..self.vars.len())
+                .filter(|&var| ty.contains(var) && !reported.contains(&var))
+                .collect();
+            let ty = ty.finished();
+            if !unknown.is_empty() {
+                let kind = ErrorKind::AnnotationsNeeded(Some((name.node.clone(), ty.clone())));
+                self.error(kind, name.span);
+                reported.extend(unknown);
+            }
+            bindings.push((name, ty));
+        }
+        self.errors.sort_by_key(|error| error.span.start);
+
+        Inference {
+            steps: self.steps,
+            bindings,
+            errors: self.errors,
+        }
+    }
+}
+
+// Whether the integer literal `text` fits in `ty`, negated or not, as
+// rustc's `overflowing_literals` lint checks
+fn fits(text: &str, ty: &Type, negated: bool) -> bool {
+    let bits = match ty {
+        Type::Named(name, _) if ty.is_integer() => match &name[1..] {
+            "size" => 64,
+            bits => bits.parse().unwrap_or(128),
+        },
+        _ => return true,
+    };
+    let digits: String = text
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '_')
+        .filter(|c| *c != '_')
+        .collect();
+    let value = match digits.parse::<u128>() {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+    let max = match ty.to_string().starts_with('i') {
+        true if negated => 1u128 << (bits - 1),
+        true => (1u128 << (bits - 1)) - 1,
+        false => u128::MAX >> (128 - bits),
+    };
+    value <= max
+}
+
+// `a, b`
+fn list(types: &[Type]) -> String {
+    types
+        .iter()
+        .map(Type::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Type {
+    pub fn named(name: &str) -> Type {
+        Type::Named(name.to_owned(), Vec::new())
+    }
+
+    pub fn unit() -> Type {
+        Type::named("()")
+    }
+
+    pub fn vec(element: Type) -> Type {
+        Type::Named("Vec".to_owned(), vec![element])
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::Named(name, _) if INTEGERS.contains(&name.as_str()))
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::Named(name, _) if name == "f32" || name == "f64")
+    }
+
+    fn contains(&self, var: usize) -> bool {
+        match self {
+            Type::Named(_, parameters) => parameters.iter().any(|ty| ty.contains(var)),
+            Type::Var(other) => *other == var,
+        }
+    }
+
+    // The first name in `self` that isn't a type, a type parameter among
+    // `generics`, or `_`
+    fn unknown(&self, generics: &[String]) -> Option<String> {
+        match self {
+            Type::Named(name, parameters) => {
+                let arity = if name == "Vec" { 1 } else { 0 };
+                let known = INTEGERS.contains(&name.as_str())
+                    || TYPES.contains(&name.as_str())
+                    || name == "Vec"
+                    || name == "_"
+                    || generics.contains(name);
+                if !known || parameters.len() != arity && !generics.contains(name) {
+                    return Some(self.to_string());
+                }
+                parameters
+                    .iter()
+                    .find_map(|parameter| parameter.unknown(generics))
+            }
+            Type::Var(_) => None,
+        }
+    }
+
+    // The parameters and the output of a method on `self`
+    fn method(&self, name: &str) -> Option<(Vec<Type>, Type)> {
+        let usize = Type::named("usize");
+        match (self, name) {
+            (Type::Named(vec, element), "push") if vec == "Vec" => {
+                Some((element.clone(), Type::unit()))
+            }
+            (Type::Named(string, _), "push_str") if string == "String" => {
+                Some((vec![Type::named("&str")], Type::unit()))
+            }
+            (Type::Named(ty, _), "len") if ["Vec", "String", "&str"].contains(&ty.as_str()) => {
+                Some((vec![], usize))
+            }
+            _ => None,
+        }
+    }
+
+    // `self` with the variables left as `_`
+    fn finished(self) -> Type {
+        match self {
+            Type::Named(name, parameters) => {
+                Type::Named(name, parameters.into_iter().map(Type::finished).collect())
+            }
+            Type::Var(_) => Type::named("_"),
+        }
+    }
+}
+
+impl Inference {
+    // The lines of `source`, each followed by the steps taken on it
+    pub fn trace(&self, source: &str) -> String {
+        let lines: Vec<&str> = source.lines().collect();
+        let gutter = (lines.len() + 1).to_string().len();
+        let mut steps = self.steps.iter().peekable();
+        let mut out = String::new();
+        for (index, line) in lines.iter().enumerate() {
+            let row = format!("{:>width$} | {}", index + 1, line, width = gutter);
+            out += row.trim_end();
+            out.push('\n');
+            let on_line = |step: &&Step| {
+                step.span.start < source.len()
+                    && source::line_of(source, step.span.start).0 == index
+            };
+            while let Some(step) = steps.next_if(on_line) {
+                out += &format!("{} |   {}\n", " ".repeat(gutter), step.text);
+            }
+        }
+        // The defaults, after the last line
+        for step in steps {
+            out += &format!("{} = {}\n", " ".repeat(gutter), step.text);
+        }
+        out
+    }
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, span: Span) -> Error {
+        Error { kind, span }
+    }
+
+    // The code rustc gives the same error
+    pub fn code(&self) -> Option<&'static str> {
+        match &self.kind {
+            ErrorKind::NotInScope(_) | ErrorKind::UnknownFunction(_) => Some("E0425"),
+            ErrorKind::UnknownType(_) => Some("E0412"),
+            ErrorKind::UnknownMethod(..) => Some("E0599"),
+            ErrorKind::ArgumentCount(..) => Some("E0061"),
+            ErrorKind::Mismatched { .. } => Some("E0308"),
+            ErrorKind::UnsupportedOperation("-", _) => Some("E0600"),
+            ErrorKind::UnsupportedOperation(..) => Some("E0369"),
+            ErrorKind::AnnotationsNeeded(_) => Some("E0282"),
+            ErrorKind::Cyclic => Some("E0308"),
+            _ => None,
+        }
+    }
+
+    // What the caret under `span` says
+    fn label(&self) -> String {
+        match &self.kind {
+            ErrorKind::NotInScope(_) | ErrorKind::UnknownFunction(_) => {
+                "not found in this scope".to_owned()
+            }
+            ErrorKind::UnknownMethod(_, ty) => format!("method not found in `{}`", ty),
+            ErrorKind::Mismatched { expected, found } => {
+                format!("expected `{}`, found `{}`", expected, found)
+            }
+            ErrorKind::AnnotationsNeeded(Some((name, ty))) => {
+                format!(
+                    "consider giving `{}` an explicit type: `{}: {}`",
+                    name, name, ty
+                )
+            }
+            ErrorKind::AnnotationsNeeded(None) => "cannot infer type".to_owned(),
+            ErrorKind::Cyclic => "cyclic type of infinite size".to_owned(),
+            ErrorKind::LiteralOutOfRange(ty) => {
+                format!("the literal doesn't fit into the type `{}`", ty)
+            }
+            _ => String::new(),
+        }
+    }
+
+    // The line of `source` the error is about, in the style of rustc:
+    //
+    //     error[E0282]: type annotations needed for `Vec<_>`
+    //       |
+    //     2 | let mut vec = Vec::new();
+    //       |         ^^^ consider giving `vec` an explicit type: `vec: Vec<_>`
+    pub fn render(&self, source: &str) -> String {
+        let label = (self.span, '^', self.label());
+        source::render(source, self.code(), &self.to_string(), vec![label])
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Named(name, parameters) if parameters.is_empty() => write!(f, "{}", name),
+            Type::Named(name, parameters) => write!(f, "{}<{}>", name, list(parameters)),
+            Type::Var(var) => write!(f, "?{}", var + 1),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character `{}`", c),
+            ErrorKind::InvalidNumber(text) => write!(f, "invalid number `{}`", text),
+            ErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ErrorKind::UnexpectedToken(found, expected) => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            ErrorKind::UnexpectedEnd(expected) => {
+                write!(f, "expected {}, found the end of the input", expected)
+            }
+            ErrorKind::NotInScope(name) => write!(f, "cannot find value `{}` in this scope", name),
+            ErrorKind::UnknownFunction(name) => {
+                write!(f, "cannot find function `{}` in this scope", name)
+            }
+            ErrorKind::UnknownType(name) => write!(f, "cannot find type `{}` in this scope", name),
+            ErrorKind::UnknownMethod(name, ty) => {
+                write!(f, "no method named `{}` found for `{}`", name, ty)
+            }
+            ErrorKind::ArgumentCount(expected, found) => write!(
+                f,
+                "this function takes {} argument(s) but {} were supplied",
+                expected, found
+            ),
+            ErrorKind::Mismatched { .. } => write!(f, "mismatched types"),
+            ErrorKind::UnsupportedOperation("-", ty) => {
+                write!(f, "cannot apply unary operator `-` to type `{}`", ty)
+            }
+            ErrorKind::UnsupportedOperation(op, ty) => {
+                write!(f, "cannot apply binary operator `{}` to type `{}`", op, ty)
+            }
+            ErrorKind::AnnotationsNeeded(Some((_, ty))) => {
+                write!(f, "type annotations needed for `{}`", ty)
+            }
+            ErrorKind::AnnotationsNeeded(None) => write!(f, "type annotations needed"),
+            ErrorKind::Cyclic => write!(f, "mismatched types"),
+            ErrorKind::LiteralOutOfRange(ty) => write!(f, "literal out of range for `{}`", ty),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(source: &str) -> Vec<Error> {
+        infer(source).unwrap().errors
+    }
+
+    #[test]
+    fn pushed_element() {
+        let source = "let elem = 5u8;\nlet mut vec = Vec::new();\nvec.push(elem);\n";
+        let found = infer(source).unwrap();
+        assert!(found.errors.is_empty());
+        assert_eq!(found.bindings[1].1, Type::vec(Type::named("u8")));
+    }
+
+    #[test]
+    fn annotations_needed() {
+        let errors = errors("let elem = 5u8;\nlet mut vec = Vec::new();\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code(), Some("E0282"));
+        assert_eq!(
+            errors[0].to_string(),
+            "type annotations needed for `Vec<_>`"
+        );
+        assert!(matches!(
+            errors[0].kind,
+            ErrorKind::AnnotationsNeeded(Some(_))
+        ));
+    }
+
+    #[test]
+    fn integer_default() {
+        let found = infer("let x = 5;").unwrap();
+        assert_eq!(found.bindings[0].1, Type::named("i32"));
+    }
+
+    #[test]
+    fn cyclic() {
+        let errors = errors("let mut v = Vec::new();\nv.push(v);\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code(), Some("E0308"));
+        assert!(matches!(errors[0].kind, ErrorKind::Cyclic));
+    }
+
+    #[test]
+    fn literal_out_of_range() {
+        let errors = errors("let x = 300u8;\nlet y: i8 = 128;\n");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].to_string(), "literal out of range for `u8`");
+        assert_eq!(errors[1].to_string(), "literal out of range for `i8`");
+        assert!(
+            infer("let x = 255u8;\nlet y = -128i8;\nlet z = 1_000u16;\n")
+                .unwrap()
+                .errors
+                .is_empty()
+        );
+    }
+}
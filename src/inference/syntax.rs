@@ -0,0 +1,564 @@
+use std::fmt;
+
+use super::{Error, ErrorKind, Type};
+use crate::source::{self, LexError, Lexeme, Span, Spanned};
+
+// Statements around `let`, with the types left out where they can be found:
+//
+//     program    = statement*
+//     statement  = "let" "mut"? name (":" type)? ("=" expression)? ";"
+//                | name "=" expression ";"
+//                | "fn" name ("<" name ("," name)* ">")? "(" (name ":" type ("," ...)*)? ")"
+//                  ("->" type)? ";"
+//                | expression ";"
+//     expression = sum (("==" | "!=" | "<" | "<=" | ">" | ">=") sum)?
+//     sum        = term (("+" | "-") term)*
+//     term       = unary (("*" | "/") unary)*
+//     unary      = "-" unary | postfix
+//     postfix    = primary ("." name "(" arguments ")" | "[" expression "]")*
+//     primary    = literal | name | path "(" arguments ")" | "vec!" "[" arguments "]"
+//                | ("println" | "print") "!" "(" arguments ")" | "(" expression ")"
+//     type       = "_" | "()" | "&" name | name ("<" type ">")?
+//
+// A `fn` is only declared, for calls to have a signature to follow. Its
+// type parameters are found again at each call.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub statements: Vec<Spanned<Stmt>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Let {
+        name: Spanned<String>,
+        annotation: Option<Spanned<Type>>,
+        value: Option<Spanned<Expr>>,
+    },
+    Assign {
+        name: Spanned<String>,
+        value: Spanned<Expr>,
+    },
+    Function(Signature),
+    Expr(Spanned<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub name: Spanned<String>,
+    pub generics: Vec<String>,
+    pub parameters: Vec<Type>,
+    pub output: Type,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    // The suffix of `5u8`, if any
+    Int(Option<String>),
+    Float(Option<String>),
+    Str,
+    Bool,
+    Variable(String),
+    Call {
+        function: Spanned<String>,
+        arguments: Vec<Spanned<Expr>>,
+    },
+    Method {
+        receiver: Box<Spanned<Expr>>,
+        method: Spanned<String>,
+        arguments: Vec<Spanned<Expr>>,
+    },
+    Index(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    // `vec![...]`
+    Vec(Vec<Spanned<Expr>>),
+    // `println!(...)`, its format string first
+    Print(Vec<Spanned<Expr>>),
+    Negate(Box<Spanned<Expr>>),
+    Binary(&'static str, Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    // With its suffix, if any
+    Int(Option<String>),
+    Float(Option<String>),
+    Str,
+    Identifier(String),
+    Symbol(&'static str),
+}
+
+// Longest first, for `::` not to be read as `:` then `:`
+const SYMBOLS: [&str; 25] = [
+    "::", "->", "==", "!=", "<=", ">=", ":", ";", ",", ".", "=", "<", ">", "(", ")", "[", "]", "{",
+    "}", "+", "-", "*", "/", "!", "&",
+];
+
+const COMPARISONS: [&str; 6] = ["==", "!=", "<", "<=", ">", ">="];
+
+fn tokenize(source: &str) -> Result<Vec<Spanned<Token>>, Error> {
+    let lexemes = source::lex(source, &SYMBOLS, number).map_err(|error| {
+        let kind = match error.node {
+            LexError::UnexpectedCharacter(c) => ErrorKind::UnexpectedCharacter(c),
+            LexError::UnterminatedString => ErrorKind::UnterminatedString,
+        };
+        Error::new(kind, error.span)
+    })?;
+
+    let mut tokens = Vec::new();
+    for Spanned { node, span } in lexemes {
+        let token = match node {
+            Lexeme::Number => {
+                let text = &source[span.start..span.end];
+                let length = text
+                    .find(|c: char| c.is_ascii_alphabetic())
+                    .unwrap_or(text.len());
+                let float = text[..length].contains('.');
+                let suffix = match &text[length..] {
+                    "" => None,
+                    suffix => match Type::named(suffix) {
+                        ty if ty.is_float() || (!float && ty.is_integer()) => {
+                            Some(suffix.to_owned())
+                        }
+                        _ => {
+                            let kind = ErrorKind::InvalidNumber(text.to_owned());
+                            return Err(Error::new(kind, span));
+                        }
+                    },
+                };
+                let float = float
+                    || suffix
+                        .as_deref()
+                        .is_some_and(|suffix| suffix.starts_with('f'));
+                if float {
+                    Token::Float(suffix)
+                } else {
+                    Token::Int(suffix)
+                }
+            }
+            Lexeme::Identifier(name) => Token::Identifier(name),
+            Lexeme::Str(_) => Token::Str,
+            Lexeme::Symbol(symbol) => Token::Symbol(symbol),
+        };
+        tokens.push(Spanned { node: token, span });
+    }
+    Ok(tokens)
+}
+
+// The length of the number `rest` starts with: digits, a fraction if a digit
+// follows the `.`, `1.5` but not `v.len()`, and a suffix
+fn number(rest: &str) -> usize {
+    let digits = |text: &str| {
+        text.find(|c: char| !(c.is_ascii_digit() || c == '_'))
+            .unwrap_or(text.len())
+    };
+    let mut length = digits(rest);
+    if rest[length..].starts_with('.')
+        && rest[length + 1..].starts_with(|c: char| c.is_ascii_digit())
+    {
+        length += 1 + digits(&rest[length + 1..]);
+    }
+    length
+        + rest[length..]
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len() - length)
+}
+
+pub fn parse(source: &str) -> Result<Program, Error> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        end: source.len(),
+    };
+    let mut statements = Vec::new();
+    while parser.peek().is_some() {
+        statements.push(parser.statement()?);
+    }
+    Ok(Program { statements })
+}
+
+struct Parser<'a> {
+    tokens: &'a [Spanned<Token>],
+    position: usize,
+    // Where errors about a missing token point at
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Spanned<Token>> {
+        self.tokens.get(self.position)
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Spanned { node: Token::Symbol(s), .. }) if *s == symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Spanned { node: Token::Identifier(name), .. }) if name == keyword)
+    }
+
+    // Whether the token after the current one is `symbol`
+    fn is_next_symbol(&self, symbol: &str) -> bool {
+        matches!(self.tokens.get(self.position + 1), Some(Spanned { node: Token::Symbol(s), .. }) if *s == symbol)
+    }
+
+    // The span of the token before the current one
+    fn previous(&self) -> Span {
+        self.tokens[self.position - 1].span
+    }
+
+    fn skip_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.is_symbol(symbol);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &'static str) -> Result<Span, Error> {
+        if self.skip_symbol(symbol) {
+            return Ok(self.previous());
+        }
+        Err(self.unexpected(match symbol {
+            ";" => "`;`",
+            "(" => "`(`",
+            ")" => "`)`",
+            "[" => "`[`",
+            "]" => "`]`",
+            ">" => "`>`",
+            ":" => "`:`",
+            "=" => "`=`",
+            _ => "a symbol",
+        }))
+    }
+
+    fn identifier(&mut self) -> Result<Spanned<String>, Error> {
+        match self.peek() {
+            Some(Spanned {
+                node: Token::Identifier(name),
+                span,
+            }) => {
+                self.position += 1;
+                Ok(Spanned {
+                    node: name.clone(),
+                    span: *span,
+                })
+            }
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> Error {
+        match self.peek() {
+            Some(token) => Error::new(
+                ErrorKind::UnexpectedToken(token.node.to_string(), expected),
+                token.span,
+            ),
+            None => Error::new(
+                ErrorKind::UnexpectedEnd(expected),
+                Span {
+                    start: self.end,
+                    end: self.end + 1,
+                },
+            ),
+        }
+    }
+
+    // The items of a list up to `close`, the opening symbol being already
+    // read
+    fn list<T>(
+        &mut self,
+        close: &'static str,
+        mut item: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        let mut items = Vec::new();
+        while !self.is_symbol(close) {
+            if !items.is_empty() {
+                self.expect_symbol(",")?;
+            }
+            items.push(item(self)?);
+        }
+        self.expect_symbol(close)?;
+        Ok(items)
+    }
+
+    fn statement(&mut self) -> Result<Spanned<Stmt>, Error> {
+        let start = match self.peek() {
+            Some(token) => token.span.start,
+            None => return Err(self.unexpected("a statement")),
+        };
+
+        let stmt = if self.is_keyword("let") {
+            self.position += 1;
+            if self.is_keyword("mut") {
+                self.position += 1;
+            }
+            let name = self.identifier()?;
+            let annotation = if self.skip_symbol(":") {
+                Some(self.spanned_type()?)
+            } else {
+                None
+            };
+            let value = if self.skip_symbol("=") {
+                Some(self.expression()?)
+            } else {
+                None
+            };
+            Stmt::Let {
+                name,
+                annotation,
+                value,
+            }
+        } else if self.is_keyword("fn") {
+            self.position += 1;
+            Stmt::Function(self.signature()?)
+        } else if self.is_next_symbol("=") {
+            let name = self.identifier()?;
+            self.position += 1;
+            Stmt::Assign {
+                name,
+                value: self.expression()?,
+            }
+        } else {
+            Stmt::Expr(self.expression()?)
+        };
+
+        // The `;` is left out of the span
+        let span = Span {
+            start,
+            end: self.previous().end,
+        };
+        self.expect_symbol(";")?;
+        Ok(Spanned { node: stmt, span })
+    }
+
+    // What follows `fn`
+    fn signature(&mut self) -> Result<Signature, Error> {
+        let name = self.identifier()?;
+        let generics = if self.skip_symbol("<") {
+            self.list(">", |parser| Ok(parser.identifier()?.node))?
+        } else {
+            Vec::new()
+        };
+        self.expect_symbol("(")?;
+        let parameters = self.list(")", |parser| {
+            parser.identifier()?;
+            parser.expect_symbol(":")?;
+            parser.parse_type()
+        })?;
+        let output = if self.skip_symbol("->") {
+            self.parse_type()?
+        } else {
+            Type::unit()
+        };
+        Ok(Signature {
+            name,
+            generics,
+            parameters,
+            output,
+        })
+    }
+
+    fn spanned_type(&mut self) -> Result<Spanned<Type>, Error> {
+        let start = self.peek().map_or(self.end, |token| token.span.start);
+        let node = self.parse_type()?;
+        Ok(Spanned {
+            node,
+            span: Span {
+                start,
+                end: self.previous().end,
+            },
+        })
+    }
+
+    fn parse_type(&mut self) -> Result<Type, Error> {
+        if self.skip_symbol("(") {
+            self.expect_symbol(")")?;
+            return Ok(Type::unit());
+        }
+        if self.skip_symbol("&") {
+            let name = self.identifier()?;
+            return Ok(Type::named(&format!("&{}", name.node)));
+        }
+        let name = match self.identifier() {
+            Ok(name) => name.node,
+            Err(_) => return Err(self.unexpected("a type")),
+        };
+        let parameters = if self.skip_symbol("<") {
+            let parameter = self.parse_type()?;
+            self.expect_symbol(">")?;
+            vec![parameter]
+        } else {
+            Vec::new()
+        };
+        Ok(Type::Named(name, parameters))
+    }
+
+    fn expression(&mut self) -> Result<Spanned<Expr>, Error> {
+        let left = self.sum()?;
+        let op = match self.peek() {
+            Some(Spanned {
+                node: Token::Symbol(op),
+                ..
+            }) if COMPARISONS.contains(op) => *op,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.sum()?;
+        Ok(binary(op, left, right))
+    }
+
+    fn sum(&mut self) -> Result<Spanned<Expr>, Error> {
+        let mut left = self.term()?;
+        while let Some(op) = ["+", "-"].iter().copied().find(|op| self.is_symbol(op)) {
+            self.position += 1;
+            let right = self.term()?;
+            left = binary(op, left, right);
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Spanned<Expr>, Error> {
+        let mut left = self.unary()?;
+        while let Some(op) = ["*", "/"].iter().copied().find(|op| self.is_symbol(op)) {
+            self.position += 1;
+            let right = self.unary()?;
+            left = binary(op, left, right);
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Spanned<Expr>, Error> {
+        if self.skip_symbol("-") {
+            let start = self.previous().start;
+            let operand = self.unary()?;
+            return Ok(Spanned {
+                span: Span {
+                    start,
+                    end: operand.span.end,
+                },
+                node: Expr::Negate(Box::new(operand)),
+            });
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Spanned<Expr>, Error> {
+        let mut expr = self.primary()?;
+        loop {
+            let start = expr.span.start;
+            let node = if self.skip_symbol(".") {
+                let method = self.identifier()?;
+                self.expect_symbol("(")?;
+                let arguments = self.list(")", Parser::expression)?;
+                Expr::Method {
+                    receiver: Box::new(expr),
+                    method,
+                    arguments,
+                }
+            } else if self.skip_symbol("[") {
+                let index = self.expression()?;
+                self.expect_symbol("]")?;
+                Expr::Index(Box::new(expr), Box::new(index))
+            } else {
+                return Ok(expr);
+            };
+            expr = Spanned {
+                node,
+                span: Span {
+                    start,
+                    end: self.previous().end,
+                },
+            };
+        }
+    }
+
+    fn primary(&mut self) -> Result<Spanned<Expr>, Error> {
+        const EXPECTED: &str = "a literal, a name or `(`";
+        let token = match self.peek() {
+            Some(token) => token,
+            None => return Err(self.unexpected(EXPECTED)),
+        };
+        let start = token.span.start;
+        let node = match &token.node {
+            Token::Int(suffix) => Expr::Int(suffix.clone()),
+            Token::Float(suffix) => Expr::Float(suffix.clone()),
+            Token::Str => Expr::Str,
+            Token::Identifier(name) if name == "true" || name == "false" => Expr::Bool,
+            Token::Identifier(name) if name == "vec" && self.is_next_symbol("!") => {
+                self.position += 2;
+                self.expect_symbol("[")?;
+                Expr::Vec(self.list("]", Parser::expression)?)
+            }
+            Token::Identifier(name)
+                if (name == "println" || name == "print") && self.is_next_symbol("!") =>
+            {
+                self.position += 2;
+                self.expect_symbol("(")?;
+                Expr::Print(self.list(")", Parser::expression)?)
+            }
+            Token::Identifier(_) if self.is_next_symbol("(") || self.is_next_symbol("::") => {
+                let mut function = self.identifier()?;
+                while self.skip_symbol("::") {
+                    let name = self.identifier()?;
+                    function = Spanned {
+                        node: format!("{}::{}", function.node, name.node),
+                        span: Span {
+                            start,
+                            end: name.span.end,
+                        },
+                    };
+                }
+                self.expect_symbol("(")?;
+                Expr::Call {
+                    function,
+                    arguments: self.list(")", Parser::expression)?,
+                }
+            }
+            Token::Identifier(name) => Expr::Variable(name.clone()),
+            Token::Symbol("(") => {
+                self.position += 1;
+                let inner = self.expression()?;
+                self.expect_symbol(")")?;
+                inner.node
+            }
+            Token::Symbol(_) => return Err(self.unexpected(EXPECTED)),
+        };
+        // Literals and names are a single token, the rest moved on already
+        if matches!(
+            node,
+            Expr::Int(_) | Expr::Float(_) | Expr::Str | Expr::Bool | Expr::Variable(_)
+        ) {
+            self.position += 1;
+        }
+        Ok(Spanned {
+            node,
+            span: Span {
+                start,
+                end: self.previous().end,
+            },
+        })
+    }
+}
+
+fn binary(op: &'static str, left: Spanned<Expr>, right: Spanned<Expr>) -> Spanned<Expr> {
+    Spanned {
+        span: Span {
+            start: left.span.start,
+            end: right.span.end,
+        },
+        node: Expr::Binary(op, Box::new(left), Box::new(right)),
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Int(_) => write!(f, "an integer"),
+            Token::Float(_) => write!(f, "a float"),
+            Token::Str => write!(f, "a string"),
+            Token::Identifier(name) => write!(f, "`{}`", name),
+            Token::Symbol(symbol) => write!(f, "`{}`", symbol),
+        }
+    }
+}
@@ -0,0 +1,730 @@
+use std::fmt;
+
+// Rust numeric literals: `1u8`, `0b0011u32`, `0x80u32`, `1_000_000u32`,
+// `65.4321_f32`, `1e-3`, ... lexed into a typed `Value`.
+//
+// The rules follow rustc: unsuffixed integers are `i32` and unsuffixed
+// floats are `f64`, and a literal that doesn't fit its type is rejected like
+// the (deny-by-default) `overflowing_literals` lint does. A leading `-` is
+// accepted so that `-128i8` can be checked the way rustc checks it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Literal {
+    pub text: String,
+    // The digits alone, without sign, prefix, `_` or suffix: `1000.05`,
+    // `1e-3`, `ff` for `0xffu8`
+    pub digits: String,
+    pub value: Value,
+    pub radix: Radix,
+    // Whether the type was given by a suffix rather than inferred
+    pub suffixed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Radix {
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+}
+
+impl Radix {
+    pub fn base(self) -> u32 {
+        match self {
+            Radix::Binary => 2,
+            Radix::Octal => 8,
+            Radix::Decimal => 10,
+            Radix::Hexadecimal => 16,
+        }
+    }
+}
+
+impl fmt::Display for Radix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Radix::Binary => write!(f, "binary"),
+            Radix::Octal => write!(f, "octal"),
+            Radix::Decimal => write!(f, "decimal"),
+            Radix::Hexadecimal => write!(f, "hexadecimal"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiteralError {
+    // Not a number at all
+    Empty,
+    // `0x`, `0b_`
+    NoDigits,
+    // `0b102`
+    InvalidDigit {
+        digit: char,
+        radix: Radix,
+    },
+    // `1e`, `1e+`
+    MissingExponentDigits,
+    // `0x1.5`, `0b1e3`, `0o7f32`
+    UnsupportedFloatRadix(Radix),
+    // `1u7`, `1.0u8`
+    InvalidSuffix {
+        suffix: String,
+        float: bool,
+    },
+    // Doesn't even fit in a `u128`
+    TooLarge,
+    // `-1u8`
+    NegatedUnsigned {
+        ty: &'static str,
+    },
+    // `256u8`: the `overflowing_literals` lint
+    OutOfRange {
+        ty: &'static str,
+        note: String,
+        help: Option<String>,
+    },
+}
+
+impl LiteralError {
+    // The explanation rustc attaches to the error, if any
+    pub fn note(&self) -> Option<&str> {
+        match self {
+            LiteralError::OutOfRange { note, .. } => Some(note),
+            _ => None,
+        }
+    }
+
+    pub fn help(&self) -> Option<&str> {
+        match self {
+            LiteralError::OutOfRange { help, .. } => help.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LiteralError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LiteralError::Empty => write!(f, "expected a numeric literal"),
+            LiteralError::NoDigits => write!(f, "no valid digits found for number"),
+            LiteralError::InvalidDigit { digit, radix } => write!(
+                f,
+                "invalid digit `{}` for a base {} literal",
+                digit,
+                radix.base()
+            ),
+            LiteralError::MissingExponentDigits => {
+                write!(f, "expected at least one digit in exponent")
+            }
+            LiteralError::UnsupportedFloatRadix(radix) => {
+                write!(f, "{} float literal is not supported", radix)
+            }
+            LiteralError::InvalidSuffix { suffix, float } => write!(
+                f,
+                "invalid suffix `{}` for {} literal",
+                suffix,
+                if *float { "float" } else { "number" }
+            ),
+            LiteralError::TooLarge => write!(f, "integer literal is too large"),
+            LiteralError::NegatedUnsigned { ty } => {
+                write!(f, "cannot apply unary operator `-` to type `{}`", ty)
+            }
+            LiteralError::OutOfRange { ty, .. } => write!(f, "literal out of range for `{}`", ty),
+        }
+    }
+}
+
+impl std::error::Error for LiteralError {}
+
+const INTEGER_TYPES: [&str; 12] = [
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+// Lex a whole string as one numeric literal, optionally negated
+pub fn lex(text: &str) -> Result<Literal, LiteralError> {
+    let (negative, unsigned_text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let chars: Vec<char> = unsigned_text.chars().collect();
+    if chars.first().is_none_or(|c| !c.is_ascii_digit()) {
+        return Err(LiteralError::Empty);
+    }
+
+    let (radix, mut position) = match (chars[0], chars.get(1)) {
+        ('0', Some('b')) => (Radix::Binary, 2),
+        ('0', Some('o')) => (Radix::Octal, 2),
+        ('0', Some('x')) => (Radix::Hexadecimal, 2),
+        _ => (Radix::Decimal, 0),
+    };
+
+    // Digits and underscores. Binary and octal literals take every decimal
+    // digit so that `0b102` reports the `2` instead of a bogus suffix.
+    let is_digit = |c: char| match radix {
+        Radix::Hexadecimal => c.is_ascii_hexdigit(),
+        _ => c.is_ascii_digit(),
+    };
+    let mut digits = String::new();
+    while position < chars.len() && (is_digit(chars[position]) || chars[position] == '_') {
+        if chars[position] != '_' {
+            digits.push(chars[position]);
+        }
+        position += 1;
+    }
+    if digits.is_empty() {
+        return Err(LiteralError::NoDigits);
+    }
+    if let Some(digit) = digits.chars().find(|c| !c.is_digit(radix.base())) {
+        return Err(LiteralError::InvalidDigit { digit, radix });
+    }
+
+    // A fractional part and/or an exponent make it a float
+    let mut float = false;
+    if chars.get(position) == Some(&'.')
+        && chars.get(position + 1).is_none_or(|c| c.is_ascii_digit())
+    {
+        float = true;
+        digits.push('.');
+        position += 1;
+        while position < chars.len() && (chars[position].is_ascii_digit() || chars[position] == '_')
+        {
+            if chars[position] != '_' {
+                digits.push(chars[position]);
+            }
+            position += 1;
+        }
+    }
+    if radix != Radix::Hexadecimal && matches!(chars.get(position), Some('e') | Some('E')) {
+        float = true;
+        digits.push('e');
+        position += 1;
+        if let Some(&sign) = chars.get(position).filter(|&&c| c == '+' || c == '-') {
+            digits.push(sign);
+            position += 1;
+        }
+        let mut exponent_digits = 0;
+        while position < chars.len() && (chars[position].is_ascii_digit() || chars[position] == '_')
+        {
+            if chars[position] != '_' {
+                digits.push(chars[position]);
+                exponent_digits += 1;
+            }
+            position += 1;
+        }
+        if exponent_digits == 0 {
+            return Err(LiteralError::MissingExponentDigits);
+        }
+    }
+
+    let suffix: String = chars[position..].iter().collect();
+    let suffix = if suffix.is_empty() {
+        None
+    } else {
+        Some(suffix)
+    };
+    let float = float || matches!(suffix.as_deref(), Some("f32") | Some("f64"));
+    if float && radix != Radix::Decimal {
+        return Err(LiteralError::UnsupportedFloatRadix(radix));
+    }
+
+    let (value, suffixed) = if float {
+        let ty = match suffix.as_deref() {
+            None | Some("f64") => "f64",
+            Some("f32") => "f32",
+            Some(other) => {
+                return Err(LiteralError::InvalidSuffix {
+                    suffix: other.to_owned(),
+                    float: true,
+                })
+            }
+        };
+        (float_value(text, &digits, negative, ty)?, suffix.is_some())
+    } else {
+        let ty = match suffix.as_deref() {
+            None => "i32",
+            Some(suffix) => match INTEGER_TYPES.iter().find(|&&ty| ty == suffix) {
+                Some(ty) => ty,
+                None => {
+                    return Err(LiteralError::InvalidSuffix {
+                        suffix: suffix.to_owned(),
+                        float: false,
+                    })
+                }
+            },
+        };
+        let value = integer_value(text, &digits, radix, negative, ty)?;
+        (value, suffix.is_some())
+    };
+
+    Ok(Literal {
+        text: text.to_owned(),
+        digits,
+        value,
+        radix,
+        suffixed,
+    })
+}
+
+fn float_value(
+    text: &str,
+    digits: &str,
+    negative: bool,
+    ty: &'static str,
+) -> Result<Value, LiteralError> {
+    let digits = if negative {
+        format!("-{}", digits)
+    } else {
+        digits.to_owned()
+    };
+    let value = match ty {
+        // `digits` only holds valid float syntax at this point
+        "f32" => Value::F32(digits.parse().expect("invalid float digits")),
+        _ => Value::F64(digits.parse().expect("invalid float digits")),
+    };
+    let infinite = match value {
+        Value::F32(v) => v.is_infinite(),
+        Value::F64(v) => v.is_infinite(),
+        _ => false,
+    };
+    if infinite {
+        let infinity = if negative { "NEG_INFINITY" } else { "INFINITY" };
+        return Err(LiteralError::OutOfRange {
+            ty,
+            note: format!(
+                "the literal `{}` does not fit into the type `{}` and will be converted to `{}::{}`",
+                text, ty, ty, infinity
+            ),
+            help: None,
+        });
+    }
+    Ok(value)
+}
+
+fn integer_value(
+    text: &str,
+    digits: &str,
+    radix: Radix,
+    negative: bool,
+    ty: &'static str,
+) -> Result<Value, LiteralError> {
+    let mut magnitude: u128 = 0;
+    for digit in digits.chars() {
+        magnitude = magnitude
+            .checked_mul(radix.base() as u128)
+            .and_then(|m| m.checked_add(digit.to_digit(radix.base()).unwrap_or(0) as u128))
+            .ok_or(LiteralError::TooLarge)?;
+    }
+
+    let template = integer_template(ty);
+    if negative && !template.is_signed() {
+        return Err(LiteralError::NegatedUnsigned { ty });
+    }
+    if let Some(value) = Value::from_sign_and_magnitude(template, negative, magnitude) {
+        return Ok(value);
+    }
+
+    let note = if radix == Radix::Decimal {
+        format!(
+            "the literal `{}` does not fit into the type `{}` whose range is `{}`",
+            text,
+            ty,
+            range(template)
+        )
+    } else {
+        // The bits are kept, the value wraps around
+        let wrapped = template.with_bits(if negative {
+            magnitude.wrapping_neg()
+        } else {
+            magnitude
+        });
+        format!(
+            "the literal `{}` (decimal `{}{}`) does not fit into the type `{}` and will become `{}{}`",
+            text,
+            if negative { "-" } else { "" },
+            magnitude,
+            ty,
+            wrapped,
+            ty
+        )
+    };
+    // Suggest the smallest type that would hold the value, preferring the
+    // unsigned type of the same size for positive values
+    let help = INTEGER_TYPES
+        .iter()
+        .filter(|&&candidate| candidate != "usize" && candidate != "isize")
+        .map(|&candidate| integer_template(candidate))
+        .filter(|candidate| {
+            Value::from_sign_and_magnitude(*candidate, negative, magnitude).is_some()
+        })
+        .min_by_key(|candidate| {
+            let same_size_unsigned = !negative
+                && template.is_signed()
+                && !candidate.is_signed()
+                && candidate.bits() == template.bits();
+            (
+                !same_size_unsigned,
+                candidate.bits(),
+                candidate.is_signed() != template.is_signed(),
+            )
+        })
+        .map(|candidate| {
+            format!(
+                "consider using the type `{}` instead",
+                candidate.type_name()
+            )
+        });
+
+    Err(LiteralError::OutOfRange { ty, note, help })
+}
+
+// A zero of the integer type named `ty`
+fn integer_template(ty: &str) -> Value {
+    match ty {
+        "u8" => Value::U8(0),
+        "u16" => Value::U16(0),
+        "u32" => Value::U32(0),
+        "u64" => Value::U64(0),
+        "u128" => Value::U128(0),
+        "usize" => Value::Usize(0),
+        "i8" => Value::I8(0),
+        "i16" => Value::I16(0),
+        "i64" => Value::I64(0),
+        "i128" => Value::I128(0),
+        "isize" => Value::Isize(0),
+        _ => Value::I32(0),
+    }
+}
+
+// `0..=255`, `-128..=127`
+fn range(template: Value) -> String {
+    let bits = template.bits();
+    if template.is_signed() {
+        let half = 1u128 << (bits - 1);
+        format!("-{}..={}", half, half - 1)
+    } else {
+        let max = if bits == 128 {
+            u128::MAX
+        } else {
+            (1 << bits) - 1
+        };
+        format!("0..={}", max)
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+// Every primitive numeric type, holding a value of that type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Usize(usize),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    Isize(isize),
+    F32(f32),
+    F64(f64),
+}
+
+macro_rules! for_each_value {
+    ($value:expr, $v:ident => $body:expr) => {
+        match $value {
+            Value::U8($v) => $body,
+            Value::U16($v) => $body,
+            Value::U32($v) => $body,
+            Value::U64($v) => $body,
+            Value::U128($v) => $body,
+            Value::Usize($v) => $body,
+            Value::I8($v) => $body,
+            Value::I16($v) => $body,
+            Value::I32($v) => $body,
+            Value::I64($v) => $body,
+            Value::I128($v) => $body,
+            Value::Isize($v) => $body,
+            Value::F32($v) => $body,
+            Value::F64($v) => $body,
+        }
+    };
+}
+
+// `$v as T` for every primitive `T`, in the order of `Value`
+macro_rules! cast_all {
+    ($v:expr) => {
+        [
+            Value::U8($v as u8),
+            Value::U16($v as u16),
+            Value::U32($v as u32),
+            Value::U64($v as u64),
+            Value::U128($v as u128),
+            Value::Usize($v as usize),
+            Value::I8($v as i8),
+            Value::I16($v as i16),
+            Value::I32($v as i32),
+            Value::I64($v as i64),
+            Value::I128($v as i128),
+            Value::Isize($v as isize),
+            Value::F32($v as f32),
+            Value::F64($v as f64),
+        ]
+    };
+}
+
+impl Value {
+    pub fn type_name(self) -> &'static str {
+        match self {
+            Value::U8(_) => "u8",
+            Value::U16(_) => "u16",
+            Value::U32(_) => "u32",
+            Value::U64(_) => "u64",
+            Value::U128(_) => "u128",
+            Value::Usize(_) => "usize",
+            Value::I8(_) => "i8",
+            Value::I16(_) => "i16",
+            Value::I32(_) => "i32",
+            Value::I64(_) => "i64",
+            Value::I128(_) => "i128",
+            Value::Isize(_) => "isize",
+            Value::F32(_) => "f32",
+            Value::F64(_) => "f64",
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        for_each_value!(self, v => std::mem::size_of_val(&v) as u32 * 8)
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, Value::F32(_) | Value::F64(_))
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            Value::I8(_)
+                | Value::I16(_)
+                | Value::I32(_)
+                | Value::I64(_)
+                | Value::I128(_)
+                | Value::Isize(_)
+                | Value::F32(_)
+                | Value::F64(_)
+        )
+    }
+
+    // The raw bits, zero-extended to 128 bits
+    pub fn to_bits(self) -> u128 {
+        match self {
+            Value::U8(v) => v as u128,
+            Value::U16(v) => v as u128,
+            Value::U32(v) => v as u128,
+            Value::U64(v) => v as u128,
+            Value::U128(v) => v,
+            Value::Usize(v) => v as u128,
+            Value::I8(v) => v as u8 as u128,
+            Value::I16(v) => v as u16 as u128,
+            Value::I32(v) => v as u32 as u128,
+            Value::I64(v) => v as u64 as u128,
+            Value::I128(v) => v as u128,
+            Value::Isize(v) => v as usize as u128,
+            Value::F32(v) => v.to_bits() as u128,
+            Value::F64(v) => v.to_bits() as u128,
+        }
+    }
+
+    // A value of the same type as `self`, built from raw bits (only the low
+    // `self.bits()` bits are used)
+    pub fn with_bits(self, bits: u128) -> Value {
+        match self {
+            Value::U8(_) => Value::U8(bits as u8),
+            Value::U16(_) => Value::U16(bits as u16),
+            Value::U32(_) => Value::U32(bits as u32),
+            Value::U64(_) => Value::U64(bits as u64),
+            Value::U128(_) => Value::U128(bits),
+            Value::Usize(_) => Value::Usize(bits as usize),
+            Value::I8(_) => Value::I8(bits as u8 as i8),
+            Value::I16(_) => Value::I16(bits as u16 as i16),
+            Value::I32(_) => Value::I32(bits as u32 as i32),
+            Value::I64(_) => Value::I64(bits as u64 as i64),
+            Value::I128(_) => Value::I128(bits as i128),
+            Value::Isize(_) => Value::Isize(bits as usize as isize),
+            Value::F32(_) => Value::F32(f32::from_bits(bits as u32)),
+            Value::F64(_) => Value::F64(f64::from_bits(bits as u64)),
+        }
+    }
+
+    // Sign and magnitude of an integer value
+    pub fn sign_and_magnitude(self) -> (bool, u128) {
+        let bits = self.bits();
+        let raw = self.to_bits();
+        if self.is_signed() && !self.is_float() && raw >> (bits - 1) == 1 {
+            // Sign-extend to 128 bits, then negate to get the magnitude
+            let extended = if bits == 128 { raw } else { raw | (!0 << bits) };
+            (true, extended.wrapping_neg())
+        } else {
+            (false, raw)
+        }
+    }
+
+    // The integer of the same type as `template` holding ±`magnitude`, if
+    // it fits
+    pub fn from_sign_and_magnitude(
+        template: Value,
+        negative: bool,
+        magnitude: u128,
+    ) -> Option<Value> {
+        let negative = negative && magnitude != 0;
+        let bits = if negative {
+            magnitude.wrapping_neg()
+        } else {
+            magnitude
+        };
+        let candidate = template.with_bits(bits);
+        if candidate.sign_and_magnitude() == (negative, magnitude) {
+            Some(candidate)
+        } else {
+            None
+        }
+    }
+
+    // Binary up to 32 bits, hexadecimal above that
+    pub fn bit_pattern(self) -> String {
+        let bits = self.bits() as usize;
+        if bits <= 32 {
+            format!("0b{:0width$b}", self.to_bits(), width = bits)
+        } else {
+            format!("0x{:0width$x}", self.to_bits(), width = bits / 4)
+        }
+    }
+
+    // `self as T` for every primitive type `T`
+    pub fn cast_all(self) -> [Value; 14] {
+        for_each_value!(self, v => cast_all!(v))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for_each_value!(self, v => write!(f, "{:?}", v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> Value {
+        lex(text).unwrap().value
+    }
+
+    #[test]
+    fn radixes() {
+        let literal = lex("0b1010").unwrap();
+        assert_eq!(
+            (literal.value, literal.radix),
+            (Value::I32(10), Radix::Binary)
+        );
+        assert_eq!(lex("0o17").unwrap().radix, Radix::Octal);
+        assert_eq!(value("0o17"), Value::I32(15));
+        assert_eq!(lex("42").unwrap().radix, Radix::Decimal);
+        assert_eq!(lex("0xff").unwrap().radix, Radix::Hexadecimal);
+        assert_eq!(value("0xffu8"), Value::U8(255));
+        assert_eq!(
+            lex("0b102"),
+            Err(LiteralError::InvalidDigit {
+                digit: '2',
+                radix: Radix::Binary
+            })
+        );
+        assert_eq!(lex("0x"), Err(LiteralError::NoDigits));
+        assert_eq!(
+            lex("0x1.5"),
+            Err(LiteralError::UnsupportedFloatRadix(Radix::Hexadecimal))
+        );
+    }
+
+    #[test]
+    fn separators() {
+        assert_eq!(value("1_000_000u32"), Value::U32(1_000_000));
+        assert_eq!(value("0b_1111_0000u8"), Value::U8(0xf0));
+        assert_eq!(value("65.432_1_f32"), Value::F32(65.4321));
+        assert_eq!(value("1_e1_0"), Value::F64(1e10));
+        assert_eq!(lex("0b_"), Err(LiteralError::NoDigits));
+    }
+
+    #[test]
+    fn suffixes() {
+        assert_eq!(
+            lex("1u7"),
+            Err(LiteralError::InvalidSuffix {
+                suffix: "u7".to_owned(),
+                float: false
+            })
+        );
+        assert_eq!(
+            lex("1.0u8").unwrap_err().to_string(),
+            "invalid suffix `u8` for float literal"
+        );
+        assert_eq!(value("1f32"), Value::F32(1.0));
+        assert!(lex("7usize").unwrap().suffixed);
+        assert_eq!(lex("1e"), Err(LiteralError::MissingExponentDigits));
+    }
+
+    #[test]
+    fn default_types() {
+        let literal = lex("7").unwrap();
+        assert_eq!(literal.value, Value::I32(7));
+        assert!(!literal.suffixed);
+        assert_eq!(value("1.5"), Value::F64(1.5));
+        assert_eq!(value("1e-3"), Value::F64(1e-3));
+        assert_eq!(value("-2147483648"), Value::I32(i32::MIN));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let error = lex("256u8").unwrap_err();
+        assert_eq!(error.to_string(), "literal out of range for `u8`");
+        assert_eq!(
+            error.note(),
+            Some("the literal `256u8` does not fit into the type `u8` whose range is `0..=255`")
+        );
+        assert_eq!(error.help(), Some("consider using the type `u16` instead"));
+        // The unsigned type of the same size is preferred for a signed one
+        let error = lex("128i8").unwrap_err();
+        assert_eq!(error.help(), Some("consider using the type `u8` instead"));
+        assert_eq!(value("-128i8"), Value::I8(-128));
+
+        let error = lex("0xffi8").unwrap_err();
+        assert_eq!(
+            error.note(),
+            Some("the literal `0xffi8` (decimal `255`) does not fit into the type `i8` and will become `-1i8`")
+        );
+        let error = lex("1e400").unwrap_err();
+        assert_eq!(
+            error.note(),
+            Some("the literal `1e400` does not fit into the type `f64` and will be converted to `f64::INFINITY`")
+        );
+        assert_eq!(error.help(), None);
+        let too_large = format!("{}0", u128::MAX);
+        assert_eq!(lex(&too_large), Err(LiteralError::TooLarge));
+    }
+
+    #[test]
+    fn negated_unsigned() {
+        let error = lex("-1u8").unwrap_err();
+        assert_eq!(error, LiteralError::NegatedUnsigned { ty: "u8" });
+        assert_eq!(
+            error.to_string(),
+            "cannot apply unary operator `-` to type `u8`"
+        );
+        assert_eq!(value("-1"), Value::I32(-1));
+        assert_eq!(lex("-"), Err(LiteralError::Empty));
+    }
+}
@@ -1,10 +1,45 @@
+mod allocations;
+mod bindings;
+mod calculator;
+mod canvas;
+mod convert;
+mod display;
+mod error;
 mod examples;
+mod human;
+mod inference;
+mod literal;
+mod matrix;
+mod refined;
+mod shapes;
+mod source;
+mod svg;
+mod units;
+mod words;
 
 fn main() -> () {
+    use std::error::Error as _;
+    use std::process;
+
+    if let Err(error) = run() {
+        eprintln!("error: {}", error);
+        let mut source = error.source();
+        while let Some(cause) = source {
+            eprintln!("  caused by: {}", cause);
+            source = cause.source();
+        }
+        process::exit(error.exit_code());
+    }
+}
+
+fn run() -> error::Result<()> {
     use std::env;
-    let binary = env::var("BINARY").expect("Environment variable $BINARY must be present!");
+    use std::time::Instant;
+    let binary = env::var("BINARY").map_err(error::Error::MissingBinary)?;
+
+    let start = Instant::now();
 
-    match binary.as_ref() {
+    let result = allocations::section(&binary, || match binary.as_ref() {
         "guess_number" => examples::guess_number::main(),
         "println" => examples::println::main(),
         "primitives" => examples::primitives::main(),
@@ -12,6 +47,24 @@ fn main() -> () {
         "variable_bindings" => examples::variable_bindings::main(),
         "types" => examples::types::main(),
         "conversion" => examples::conversion::main(),
-        _ => panic!("no such binary configuration '{}'", binary),
-    }
+        "matrix_bench" => examples::matrix_bench::main(),
+        "casting_explorer" => examples::casting_explorer::main(),
+        "cast_rules" => examples::cast_rules::main(),
+        "overflow" => examples::overflow::main(),
+        "float_inspector" => examples::float_inspector::main(),
+        "literals" => examples::literals::main(),
+        "layout" => examples::layout::main(),
+        "calc" => examples::calculator::main(),
+        "convert" => examples::convert::main(),
+        "bindings" => examples::bindings::main(),
+        "infer" => examples::infer::main(),
+        _ => Err(error::Error::UnknownBinary(binary.clone())),
+    });
+    // On stderr, to keep the output of the examples as it was
+    eprintln!(
+        "{} ran in {:.1}",
+        binary,
+        human::Duration::from(start.elapsed())
+    );
+    result
 }
@@ -0,0 +1,488 @@
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Mul};
+
+mod multiply;
+
+pub use multiply::Multiplication;
+
+// A dense `rows × cols` matrix of `f64`, stored row by row.
+//
+// This is the grown-up version of the `Matrix(f32, f32, f32, f32)` tuple
+// struct from the primitives example: the dimensions are only known at
+// runtime, so one type covers every shape.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatrixError {
+    // The operands of an operation don't have compatible shapes
+    DimensionMismatch {
+        left: (usize, usize),
+        right: (usize, usize),
+    },
+    // The operation is only defined for square matrices
+    NotSquare {
+        rows: usize,
+        cols: usize,
+    },
+    // The matrix has no inverse (a zero pivot showed up during the LU
+    // decomposition)
+    Singular,
+    // A multiplication gave a different product than the naive one
+    Disagreement(Multiplication),
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MatrixError::DimensionMismatch { left, right } => write!(
+                f,
+                "dimension mismatch: {}×{} and {}×{}",
+                left.0, left.1, right.0, right.1
+            ),
+            MatrixError::NotSquare { rows, cols } => {
+                write!(f, "expected a square matrix, got {}×{}", rows, cols)
+            }
+            MatrixError::Singular => write!(f, "matrix is singular"),
+            MatrixError::Disagreement(multiplication) => {
+                write!(f, "{} disagrees with naive", multiplication)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {}
+
+impl Matrix {
+    // A `rows × cols` matrix filled with zeros
+    pub fn zeros(rows: usize, cols: usize) -> Matrix {
+        Matrix {
+            rows,
+            cols,
+            data: vec![0.0; rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Matrix {
+        let mut matrix = Matrix::zeros(n, n);
+        for i in 0..n {
+            matrix[(i, i)] = 1.0;
+        }
+        matrix
+    }
+
+    // Build a matrix from its rows, which must all have the same length
+    pub fn from_rows<R: AsRef<[f64]>>(rows: &[R]) -> Matrix {
+        let cols = rows.first().map_or(0, |row| row.as_ref().len());
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for row in rows {
+            assert_eq!(row.as_ref().len(), cols, "rows must have the same length");
+            data.extend_from_slice(row.as_ref());
+        }
+        Matrix {
+            rows: rows.len(),
+            cols,
+            data,
+        }
+    }
+
+    // Build a matrix by calling `f(row, col)` for every element
+    pub fn from_fn<F: FnMut(usize, usize) -> f64>(rows: usize, cols: usize, mut f: F) -> Matrix {
+        let mut data = Vec::with_capacity(rows * cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                data.push(f(i, j));
+            }
+        }
+        Matrix { rows, cols, data }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[f64] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        Matrix::from_fn(self.cols, self.rows, |i, j| self[(j, i)])
+    }
+
+    pub fn checked_add(&self, other: &Matrix) -> Result<Matrix, MatrixError> {
+        if self.shape() != other.shape() {
+            return Err(MatrixError::DimensionMismatch {
+                left: self.shape(),
+                right: other.shape(),
+            });
+        }
+        let data = self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(a, b)| a + b)
+            .collect();
+        Ok(Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data,
+        })
+    }
+
+    pub fn checked_mul(&self, other: &Matrix) -> Result<Matrix, MatrixError> {
+        self.mul_with(other, Multiplication::Naive)
+    }
+
+    // Multiply using one of the strategies from the `multiply` module
+    pub fn mul_with(
+        &self,
+        other: &Matrix,
+        multiplication: Multiplication,
+    ) -> Result<Matrix, MatrixError> {
+        if self.cols != other.rows {
+            return Err(MatrixError::DimensionMismatch {
+                left: self.shape(),
+                right: other.shape(),
+            });
+        }
+        let mut product = Matrix::zeros(self.rows, other.cols);
+        if product.data.is_empty() {
+            return Ok(product);
+        }
+        match multiplication {
+            Multiplication::Naive => multiply::naive(self, other, 0, &mut product.data),
+            Multiplication::Transposed => {
+                multiply::transposed(self, &other.transpose(), 0, &mut product.data)
+            }
+            Multiplication::Blocked { block } => {
+                multiply::blocked(self, other, block, &mut product.data)
+            }
+            Multiplication::Threaded { threads } => {
+                multiply::threaded(self, &other.transpose(), threads, &mut product.data)
+            }
+        }
+        Ok(product)
+    }
+
+    // Decompose the matrix into `P·A = L·U` using partial pivoting
+    pub fn lu(&self) -> Result<Lu, MatrixError> {
+        if !self.is_square() {
+            return Err(MatrixError::NotSquare {
+                rows: self.rows,
+                cols: self.cols,
+            });
+        }
+        let n = self.rows;
+        let mut lu = self.clone();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+
+        for k in 0..n {
+            // Pick the row with the largest pivot to keep the error small.
+            let pivot = (k..n)
+                .max_by(|&a, &b| lu[(a, k)].abs().total_cmp(&lu[(b, k)].abs()))
+                .unwrap_or(k);
+            if lu[(pivot, k)].abs() <= f64::EPSILON * self.max_abs() {
+                return Err(MatrixError::Singular);
+            }
+            if pivot != k {
+                lu.swap_rows(pivot, k);
+                permutation.swap(pivot, k);
+                sign = -sign;
+            }
+            for i in k + 1..n {
+                let factor = lu[(i, k)] / lu[(k, k)];
+                lu[(i, k)] = factor;
+                for j in k + 1..n {
+                    let delta = factor * lu[(k, j)];
+                    lu[(i, j)] -= delta;
+                }
+            }
+        }
+
+        Ok(Lu {
+            lu,
+            permutation,
+            sign,
+        })
+    }
+
+    pub fn determinant(&self) -> Result<f64, MatrixError> {
+        match self.lu() {
+            Ok(lu) => Ok(lu.determinant()),
+            Err(MatrixError::Singular) => Ok(0.0),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn inverse(&self) -> Result<Matrix, MatrixError> {
+        Ok(self.lu()?.inverse())
+    }
+
+    // Solve `A·x = b` for `x`
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, MatrixError> {
+        if b.len() != self.rows {
+            return Err(MatrixError::DimensionMismatch {
+                left: self.shape(),
+                right: (b.len(), 1),
+            });
+        }
+        Ok(self.lu()?.solve(b))
+    }
+
+    // Whether every element is within `tolerance` of the other matrix
+    pub fn approx_eq(&self, other: &Matrix, tolerance: f64) -> bool {
+        self.shape() == other.shape()
+            && self
+                .data
+                .iter()
+                .zip(&other.data)
+                .all(|(a, b)| (a - b).abs() <= tolerance)
+    }
+
+    fn max_abs(&self) -> f64 {
+        self.data.iter().fold(0.0, |max, x| x.abs().max(max))
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for j in 0..self.cols {
+            self.data.swap(a * self.cols + j, b * self.cols + j);
+        }
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        assert!(i < self.rows && j < self.cols, "index out of bounds");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        assert!(i < self.rows && j < self.cols, "index out of bounds");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+// Like the std operators on integers, `+` and `*` panic when the shapes
+// don't match; use `checked_add` and `checked_mul` to get an error instead.
+impl Add for &Matrix {
+    type Output = Matrix;
+
+    fn add(self, other: &Matrix) -> Matrix {
+        self.checked_add(other).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl Mul for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, other: &Matrix) -> Matrix {
+        self.checked_mul(other).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl fmt::Display for Matrix {
+    // Same layout as the 2×2 `Matrix`: one `( ... )` line per row, with
+    // every column right-aligned on its widest element.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells: Vec<String> = self
+            .data
+            .iter()
+            .map(|x| match f.precision() {
+                Some(precision) => format!("{:.*}", precision, x),
+                None => x.to_string(),
+            })
+            .collect();
+        let widths: Vec<usize> = (0..self.cols)
+            .map(|j| {
+                (0..self.rows)
+                    .map(|i| cells[i * self.cols + j].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        for i in 0..self.rows {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "(")?;
+            for (j, width) in widths.iter().enumerate() {
+                write!(f, " {:>width$}", cells[i * self.cols + j], width = width)?;
+            }
+            write!(f, " )")?;
+        }
+        Ok(())
+    }
+}
+
+// The result of `Matrix::lu`: `L` (below the diagonal, with an implicit
+// unit diagonal) and `U` (on and above it) packed into a single matrix,
+// along with the row permutation applied while pivoting.
+#[derive(Debug, Clone)]
+pub struct Lu {
+    lu: Matrix,
+    permutation: Vec<usize>,
+    sign: f64,
+}
+
+impl Lu {
+    pub fn determinant(&self) -> f64 {
+        (0..self.lu.rows).fold(self.sign, |det, i| det * self.lu[(i, i)])
+    }
+
+    pub fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.lu.rows;
+        assert_eq!(b.len(), n, "right-hand side has the wrong length");
+
+        // Forward substitution: L·y = P·b
+        let mut x: Vec<f64> = self.permutation.iter().map(|&p| b[p]).collect();
+        for i in 0..n {
+            for k in 0..i {
+                x[i] -= self.lu[(i, k)] * x[k];
+            }
+        }
+        // Back substitution: U·x = y
+        for i in (0..n).rev() {
+            for k in i + 1..n {
+                x[i] -= self.lu[(i, k)] * x[k];
+            }
+            x[i] /= self.lu[(i, i)];
+        }
+        x
+    }
+
+    pub fn inverse(&self) -> Matrix {
+        let n = self.lu.rows;
+        let mut inverse = Matrix::zeros(n, n);
+        for j in 0..n {
+            let mut unit = vec![0.0; n];
+            unit[j] = 1.0;
+            for (i, x) in self.solve(&unit).into_iter().enumerate() {
+                inverse[(i, j)] = x;
+            }
+        }
+        inverse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    #[test]
+    fn transpose() {
+        let matrix = Matrix::from_rows(&[[1.1, 1.2], [2.1, 2.2]]);
+        assert_eq!(
+            matrix.transpose(),
+            Matrix::from_rows(&[[1.1, 2.1], [1.2, 2.2]])
+        );
+        let wide = Matrix::from_rows(&[[1.0, 2.0, 3.0]]);
+        assert_eq!(wide.transpose().shape(), (3, 1));
+    }
+
+    #[test]
+    fn mul() {
+        let a = Matrix::from_rows(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let b = Matrix::from_rows(&[[7.0, 8.0], [9.0, 10.0], [11.0, 12.0]]);
+        assert_eq!(&a * &b, Matrix::from_rows(&[[58.0, 64.0], [139.0, 154.0]]));
+        assert_eq!(&Matrix::identity(2) * &a, a);
+        assert_eq!(
+            a.checked_mul(&a),
+            Err(MatrixError::DimensionMismatch {
+                left: (2, 3),
+                right: (2, 3)
+            })
+        );
+    }
+
+    #[test]
+    fn mul_kernels_agree() {
+        let a = Matrix::from_fn(7, 5, |i, j| (i * 5 + j) as f64);
+        let b = Matrix::from_fn(5, 6, |i, j| i as f64 - j as f64);
+        let expected = a.checked_mul(&b).unwrap();
+        for multiplication in Multiplication::all().into_iter().chain(vec![
+            Multiplication::Blocked { block: 2 },
+            Multiplication::Threaded { threads: 3 },
+            Multiplication::Threaded { threads: 0 },
+            Multiplication::Blocked { block: 0 },
+        ]) {
+            let product = a.mul_with(&b, multiplication).unwrap();
+            assert!(product.approx_eq(&expected, 1e-9), "{}", multiplication);
+        }
+    }
+
+    #[test]
+    fn add() {
+        let a = Matrix::from_rows(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        assert_eq!(
+            &a + &a,
+            Matrix::from_rows(&[[2.0, 4.0, 6.0], [8.0, 10.0, 12.0]])
+        );
+        assert_eq!(
+            a.checked_add(&a.transpose()),
+            Err(MatrixError::DimensionMismatch {
+                left: (2, 3),
+                right: (3, 2)
+            })
+        );
+    }
+
+    #[test]
+    fn determinant() {
+        let m = Matrix::from_rows(&[[2.0, 1.0, 1.0], [4.0, -6.0, 0.0], [-2.0, 7.0, 2.0]]);
+        assert!((m.determinant().unwrap() - -16.0).abs() < 1e-9);
+        assert_eq!(Matrix::identity(4).determinant(), Ok(1.0));
+        assert_eq!(
+            Matrix::zeros(2, 3).determinant(),
+            Err(MatrixError::NotSquare { rows: 2, cols: 3 })
+        );
+    }
+
+    #[test]
+    fn inverse() {
+        let m = Matrix::from_rows(&[[2.0, 1.0, 1.0], [4.0, -6.0, 0.0], [-2.0, 7.0, 2.0]]);
+        let inverse = m.inverse().unwrap();
+        assert!((&m * &inverse).approx_eq(&Matrix::identity(3), 1e-9));
+        assert!((&inverse * &m).approx_eq(&Matrix::identity(3), 1e-9));
+    }
+
+    #[test]
+    fn solve() {
+        let m = Matrix::from_rows(&[[2.0, 1.0, 1.0], [4.0, -6.0, 0.0], [-2.0, 7.0, 2.0]]);
+        let x = m.solve(&[5.0, -2.0, 9.0]).unwrap();
+        assert!(close(&x, &[1.0, 1.0, 2.0]));
+    }
+
+    #[test]
+    fn singular() {
+        let singular = Matrix::from_rows(&[[1.0, 2.0], [2.0, 4.0]]);
+        assert_eq!(singular.determinant(), Ok(0.0));
+        assert_eq!(singular.inverse(), Err(MatrixError::Singular));
+        assert_eq!(singular.solve(&[1.0, 2.0]), Err(MatrixError::Singular));
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+
+use super::Matrix;
+
+// The different ways `Matrix::mul_with` can compute a product. They all
+// produce the same matrix (up to floating point rounding), but differ in how
+// they walk through memory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Multiplication {
+    // The textbook triple loop: `other` is read column by column, which
+    // jumps a whole row ahead in memory on every step.
+    Naive,
+    // Transpose `other` first, so both operands are read row by row.
+    Transposed,
+    // Work on `block × block` tiles small enough to stay in the cache.
+    Blocked { block: usize },
+    // Split the rows of the result between `threads` threads, each running
+    // the transposed variant.
+    Threaded { threads: usize },
+}
+
+impl Multiplication {
+    pub const DEFAULT_BLOCK: usize = 64;
+
+    // Every variant, with the block size and thread count picked for this
+    // machine
+    pub fn all() -> Vec<Multiplication> {
+        vec![
+            Multiplication::Naive,
+            Multiplication::Transposed,
+            Multiplication::Blocked {
+                block: Multiplication::DEFAULT_BLOCK,
+            },
+            Multiplication::Threaded {
+                threads: available_threads(),
+            },
+        ]
+    }
+}
+
+impl fmt::Display for Multiplication {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Multiplication::Naive => write!(f, "naive"),
+            Multiplication::Transposed => write!(f, "transposed"),
+            Multiplication::Blocked { block } => write!(f, "blocked:{}", block),
+            Multiplication::Threaded { threads } => write!(f, "threaded:{}", threads),
+        }
+    }
+}
+
+// Parses the names printed by `Display`; the `:<n>` parameter is optional.
+impl FromStr for Multiplication {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, parameter) = match s.find(':') {
+            Some(index) => (&s[..index], Some(&s[index + 1..])),
+            None => (s, None),
+        };
+        let parameter = match parameter {
+            Some(parameter) => match parameter.parse::<usize>() {
+                Ok(0) | Err(_) => {
+                    return Err(format!("invalid parameter '{}' in '{}'", parameter, s))
+                }
+                Ok(value) => Some(value),
+            },
+            None => None,
+        };
+
+        match (name, parameter) {
+            ("naive", None) => Ok(Multiplication::Naive),
+            ("transposed", None) => Ok(Multiplication::Transposed),
+            ("blocked", block) => Ok(Multiplication::Blocked {
+                block: block.unwrap_or(Multiplication::DEFAULT_BLOCK),
+            }),
+            ("threaded", threads) => Ok(Multiplication::Threaded {
+                threads: threads.unwrap_or_else(available_threads),
+            }),
+            ("naive", Some(_)) | ("transposed", Some(_)) => {
+                Err(format!("'{}' doesn't take a parameter", name))
+            }
+            _ => Err(format!("unknown multiplication '{}'", s)),
+        }
+    }
+}
+
+fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// Each kernel fills `out`, the rows of the product starting at `first_row`.
+// The shapes have already been checked by `Matrix::mul_with`.
+
+pub(super) fn naive(a: &Matrix, b: &Matrix, first_row: usize, out: &mut [f64]) {
+    for (r, out_row) in out.chunks_mut(b.cols).enumerate() {
+        let i = first_row + r;
+        for (j, cell) in out_row.iter_mut().enumerate() {
+            let mut sum = 0.0;
+            for k in 0..a.cols {
+                sum += a.data[i * a.cols + k] * b.data[k * b.cols + j];
+            }
+            *cell = sum;
+        }
+    }
+}
+
+// `b_t` is the transpose of the right operand.
+pub(super) fn transposed(a: &Matrix, b_t: &Matrix, first_row: usize, out: &mut [f64]) {
+    for (r, out_row) in out.chunks_mut(b_t.rows).enumerate() {
+        let a_row = a.row(first_row + r);
+        for (j, cell) in out_row.iter_mut().enumerate() {
+            *cell = a_row.iter().zip(b_t.row(j)).map(|(x, y)| x * y).sum();
+        }
+    }
+}
+
+pub(super) fn blocked(a: &Matrix, b: &Matrix, block: usize, out: &mut [f64]) {
+    let block = block.max(1);
+    let (n, m, p) = (a.rows, a.cols, b.cols);
+    out.iter_mut().for_each(|cell| *cell = 0.0);
+
+    for ii in (0..n).step_by(block) {
+        for kk in (0..m).step_by(block) {
+            for jj in (0..p).step_by(block) {
+                for i in ii..(ii + block).min(n) {
+                    for k in kk..(kk + block).min(m) {
+                        let a_ik = a.data[i * m + k];
+                        let b_row = &b.data[k * p + jj..k * p + (jj + block).min(p)];
+                        let out_row = &mut out[i * p + jj..i * p + (jj + block).min(p)];
+                        for (cell, b_kj) in out_row.iter_mut().zip(b_row) {
+                            *cell += a_ik * b_kj;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn threaded(a: &Matrix, b_t: &Matrix, threads: usize, out: &mut [f64]) {
+    let cols = b_t.rows;
+    if cols == 0 {
+        return;
+    }
+    // Zero threads, which `FromStr` rejects, run on one
+    let rows_per_thread = a.rows.div_ceil(threads.max(1)).max(1);
+
+    thread::scope(|scope| {
+        for (chunk, out_rows) in out.chunks_mut(rows_per_thread * cols).enumerate() {
+            scope.spawn(move || transposed(a, b_t, chunk * rows_per_thread, out_rows));
+        }
+    });
+}
@@ -0,0 +1,266 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+// Newtypes of values that are known to satisfy a constraint, generalizing the
+// `EvenNumber` of the conversion example. The constraint is checked once,
+// when the value is made with `TryFrom` or `FromStr`, and the type carries
+// the guarantee from then on:
+//
+//     let n = Even::try_from(8)?;
+//     let guess: Bounded<1, 100> = "42".parse()?;
+//     let half: i32 = *n / 2; // `Deref` gives the inner value
+//
+// New ones are declared with `refined!`.
+
+// A newtype whose values satisfy a constraint
+pub trait Refinement: Sized {
+    type Inner;
+
+    fn holds(value: &Self::Inner) -> bool;
+
+    // What values satisfying the constraint are, "an even number" for
+    // instance, to complete "`5` is not ..."
+    fn description() -> String;
+
+    // Only called once the constraint holds
+    fn wrap(value: Self::Inner) -> Self;
+
+    fn into_inner(self) -> Self::Inner;
+}
+
+// Why a value couldn't be refined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefinementError {
+    // The text didn't even parse into the inner type
+    Parse { text: String, error: String },
+    // The value, and the description of the constraint it breaks
+    Violated { value: String, constraint: String },
+}
+
+// What `TryFrom` does for every refinement
+pub fn refine<R: Refinement>(value: R::Inner) -> Result<R, RefinementError>
+where
+    R::Inner: fmt::Display,
+{
+    if R::holds(&value) {
+        Ok(R::wrap(value))
+    } else {
+        Err(RefinementError::Violated {
+            value: value.to_string(),
+            constraint: R::description(),
+        })
+    }
+}
+
+// What `FromStr` does for every refinement
+pub fn parse<R: Refinement>(s: &str) -> Result<R, RefinementError>
+where
+    R::Inner: FromStr + fmt::Display,
+    <R::Inner as FromStr>::Err: fmt::Display,
+{
+    match s.parse() {
+        Ok(value) => refine(value),
+        Err(error) => Err(RefinementError::Parse {
+            text: s.to_owned(),
+            error: error.to_string(),
+        }),
+    }
+}
+
+// Declares refinements, with their `TryFrom`, `FromStr`, `Deref` and
+// `Display` impls:
+//
+//     refined! {
+//         pub struct Even(i32): "an even number", |n| n % 2 == 0;
+//     }
+//
+// Const generic parameters are allowed, and are in scope in the description
+// and the constraint.
+macro_rules! refined {
+    ($(
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident $(<$(const $param:ident: $param_ty:ty),*>)? ($inner:ty):
+            $description:expr, |$value:ident| $holds:expr;
+    )*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+            $vis struct $name $(<$(const $param: $param_ty),*>)? ($inner);
+
+            impl $(<$(const $param: $param_ty),*>)? Refinement for $name $(<$($param),*>)? {
+                type Inner = $inner;
+
+                fn holds($value: &$inner) -> bool {
+                    let $value = *$value;
+                    $holds
+                }
+
+                fn description() -> String {
+                    $description.to_string()
+                }
+
+                fn wrap(value: $inner) -> Self {
+                    $name(value)
+                }
+
+                fn into_inner(self) -> $inner {
+                    self.0
+                }
+            }
+
+            impl $(<$(const $param: $param_ty),*>)? std::convert::TryFrom<$inner>
+                for $name $(<$($param),*>)?
+            {
+                type Error = RefinementError;
+
+                fn try_from(value: $inner) -> Result<Self, RefinementError> {
+                    refine(value)
+                }
+            }
+
+            impl $(<$(const $param: $param_ty),*>)? FromStr for $name $(<$($param),*>)? {
+                type Err = RefinementError;
+
+                fn from_str(s: &str) -> Result<Self, RefinementError> {
+                    parse(s)
+                }
+            }
+
+            impl $(<$(const $param: $param_ty),*>)? std::ops::Deref for $name $(<$($param),*>)? {
+                type Target = $inner;
+
+                fn deref(&self) -> &$inner {
+                    &self.0
+                }
+            }
+
+            impl $(<$(const $param: $param_ty),*>)? fmt::Display for $name $(<$($param),*>)? {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    self.0.fmt(f)
+                }
+            }
+        )*
+    };
+}
+
+refined! {
+    pub struct Even(i32): "an even number", |n| n % 2 == 0;
+    pub struct Positive(i32): "a positive number", |n| n > 0;
+    pub struct NonZero(i32): "a number other than zero", |n| n != 0;
+    // Both bounds included
+    pub struct Bounded<const MIN: i64, const MAX: i64>(i64):
+        format!("a number between {} and {}", MIN, MAX), |n| (MIN..=MAX).contains(&n);
+    // NaN isn't a percentage either
+    pub struct Percentage(f64): "a percentage between 0 and 100", |p| (0.0..=100.0).contains(&p);
+}
+
+impl fmt::Display for RefinementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RefinementError::Parse { text, error } => write!(f, "`{}`: {}", text, error),
+            RefinementError::Violated { value, constraint } => {
+                write!(f, "`{}` is not {}", value, constraint)
+            }
+        }
+    }
+}
+
+impl error::Error for RefinementError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn violated(value: &str, constraint: &str) -> RefinementError {
+        RefinementError::Violated {
+            value: value.to_owned(),
+            constraint: constraint.to_owned(),
+        }
+    }
+
+    #[test]
+    fn even() {
+        let even = Even::try_from(8).unwrap();
+        assert_eq!(*even, 8);
+        assert_eq!(even.into_inner(), 8);
+        assert_eq!(Even::try_from(-4).map(Even::into_inner), Ok(-4));
+        assert_eq!(Even::try_from(5), Err(violated("5", "an even number")));
+        assert_eq!(
+            Even::try_from(5).unwrap_err().to_string(),
+            "`5` is not an even number"
+        );
+    }
+
+    #[test]
+    fn positive() {
+        assert_eq!("42".parse::<Positive>().map(Positive::into_inner), Ok(42));
+        assert_eq!(
+            "0".parse::<Positive>(),
+            Err(violated("0", "a positive number"))
+        );
+        assert_eq!(
+            "-3".parse::<Positive>(),
+            Err(violated("-3", "a positive number"))
+        );
+        let error = "many".parse::<Positive>().unwrap_err();
+        assert!(matches!(&error, RefinementError::Parse { text, .. } if text == "many"));
+        assert_eq!(error.to_string(), "`many`: invalid digit found in string");
+    }
+
+    #[test]
+    fn non_zero() {
+        assert_eq!(NonZero::try_from(-1).map(NonZero::into_inner), Ok(-1));
+        assert_eq!(
+            NonZero::try_from(0),
+            Err(violated("0", "a number other than zero"))
+        );
+    }
+
+    #[test]
+    fn bounded() {
+        type Guess = Bounded<1, 100>;
+        assert_eq!(Guess::try_from(1).map(Guess::into_inner), Ok(1));
+        assert_eq!(Guess::try_from(100).map(Guess::into_inner), Ok(100));
+        assert_eq!(
+            Guess::try_from(0),
+            Err(violated("0", "a number between 1 and 100"))
+        );
+        assert_eq!(
+            "101".parse::<Guess>(),
+            Err(violated("101", "a number between 1 and 100"))
+        );
+        assert_eq!(
+            Bounded::<-5, -5>::try_from(-5).map(Bounded::into_inner),
+            Ok(-5)
+        );
+    }
+
+    #[test]
+    fn percentage() {
+        assert_eq!(
+            Percentage::try_from(0.0).map(Percentage::into_inner),
+            Ok(0.0)
+        );
+        assert_eq!(
+            "100".parse::<Percentage>().map(Percentage::into_inner),
+            Ok(100.0)
+        );
+        assert_eq!("99.5".parse::<Percentage>().unwrap().to_string(), "99.5");
+        let constraint = "a percentage between 0 and 100";
+        assert_eq!(
+            Percentage::try_from(100.5),
+            Err(violated("100.5", constraint))
+        );
+        assert_eq!(
+            Percentage::try_from(-0.1),
+            Err(violated("-0.1", constraint))
+        );
+        assert_eq!(
+            Percentage::try_from(f64::NAN),
+            Err(violated("NaN", constraint))
+        );
+        assert!(Percentage::try_from(f64::INFINITY).is_err());
+    }
+}
@@ -0,0 +1,477 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+// The `Point` and `Rectangle` of the custom types example and the `Circle` of
+// the conversion example, joined by a few more shapes behind one trait:
+//
+//     let shapes: Vec<Box<dyn Shape>> = vec![
+//         Box::new(Circle::new(Point::new(0.0, 0.0), 6.0)),
+//         Box::new(Rectangle::square(Point::new(3.0, 3.0), 2.0)),
+//     ];
+//     let total: f64 = shapes.iter().map(|shape| shape.area()).sum();
+//
+// As in the examples, y goes up: a rectangle's top left corner has the
+// smallest x and the largest y.
+
+pub trait Shape: fmt::Display + fmt::Debug {
+    fn area(&self) -> f64;
+    fn perimeter(&self) -> f64;
+    // The smallest axis-aligned rectangle the shape fits in
+    fn bounding_box(&self) -> Rectangle;
+    // Points on the edge are inside
+    fn contains(&self, point: Point) -> bool;
+    // What to draw
+    fn outline(&self) -> Outline;
+}
+
+// The three kinds of outline every shape comes down to, for the drawing code
+// not to know about each shape
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outline {
+    Rectangle(Rectangle),
+    Ellipse {
+        center: Point,
+        semi_x: f64,
+        semi_y: f64,
+    },
+    Polygon(Vec<Point>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    pub top_left: Point,
+    pub bottom_right: Point,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub vertices: [Point; 3],
+}
+
+// With its axes along x and y
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ellipse {
+    pub center: Point,
+    pub semi_x: f64,
+    pub semi_y: f64,
+}
+
+// A simple polygon, its vertices in order in either direction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    pub vertices: Vec<Point>,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    pub fn distance(self, other: Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+impl Rectangle {
+    // From any two opposite corners
+    pub fn new(a: Point, b: Point) -> Rectangle {
+        Rectangle {
+            top_left: Point::new(a.x.min(b.x), a.y.max(b.y)),
+            bottom_right: Point::new(a.x.max(b.x), a.y.min(b.y)),
+        }
+    }
+
+    // `point` is the bottom left corner, as in the example
+    pub fn square(point: Point, size: f64) -> Rectangle {
+        Rectangle::new(point, Point::new(point.x + size, point.y + size))
+    }
+
+    pub fn width(&self) -> f64 {
+        self.bottom_right.x - self.top_left.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.top_left.y - self.bottom_right.y
+    }
+
+    // The smallest rectangle holding both
+    pub fn union(&self, other: &Rectangle) -> Rectangle {
+        Rectangle {
+            top_left: Point::new(
+                self.top_left.x.min(other.top_left.x),
+                self.top_left.y.max(other.top_left.y),
+            ),
+            bottom_right: Point::new(
+                self.bottom_right.x.max(other.bottom_right.x),
+                self.bottom_right.y.min(other.bottom_right.y),
+            ),
+        }
+    }
+
+    fn enclosing(points: &[Point]) -> Rectangle {
+        let first = Rectangle {
+            top_left: points[0],
+            bottom_right: points[0],
+        };
+        points.iter().fold(first, |bounds, &point| {
+            bounds.union(&Rectangle {
+                top_left: point,
+                bottom_right: point,
+            })
+        })
+    }
+}
+
+impl Circle {
+    pub fn new(center: Point, radius: f64) -> Circle {
+        Circle { center, radius }
+    }
+}
+
+impl Triangle {
+    pub fn new(a: Point, b: Point, c: Point) -> Triangle {
+        Triangle {
+            vertices: [a, b, c],
+        }
+    }
+}
+
+impl Ellipse {
+    pub fn new(center: Point, semi_x: f64, semi_y: f64) -> Ellipse {
+        Ellipse {
+            center,
+            semi_x,
+            semi_y,
+        }
+    }
+}
+
+impl Polygon {
+    // Panics with fewer than 3 vertices, which don't make a polygon
+    pub fn new(vertices: Vec<Point>) -> Polygon {
+        assert!(vertices.len() >= 3, "a polygon needs at least 3 vertices");
+        Polygon { vertices }
+    }
+
+    // Pairs of consecutive vertices, the last one joining the first
+    fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(&a, &b)| (a, b))
+    }
+}
+
+impl Shape for Rectangle {
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width() + self.height())
+    }
+
+    fn bounding_box(&self) -> Rectangle {
+        *self
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        (self.top_left.x..=self.bottom_right.x).contains(&point.x)
+            && (self.bottom_right.y..=self.top_left.y).contains(&point.y)
+    }
+
+    fn outline(&self) -> Outline {
+        Outline::Rectangle(*self)
+    }
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * PI * self.radius
+    }
+
+    fn bounding_box(&self) -> Rectangle {
+        Ellipse::new(self.center, self.radius, self.radius).bounding_box()
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        self.center.distance(point) <= self.radius
+    }
+
+    fn outline(&self) -> Outline {
+        Ellipse::new(self.center, self.radius, self.radius).outline()
+    }
+}
+
+impl Shape for Triangle {
+    fn area(&self) -> f64 {
+        let [a, b, c] = self.vertices;
+        cross(a, b, c).abs() / 2.0
+    }
+
+    fn perimeter(&self) -> f64 {
+        let [a, b, c] = self.vertices;
+        a.distance(b) + b.distance(c) + c.distance(a)
+    }
+
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::enclosing(&self.vertices)
+    }
+
+    // On the same side of the three edges
+    fn contains(&self, point: Point) -> bool {
+        let [a, b, c] = self.vertices;
+        let sides = [cross(a, b, point), cross(b, c, point), cross(c, a, point)];
+        sides.iter().all(|&side| side >= 0.0) || sides.iter().all(|&side| side <= 0.0)
+    }
+
+    fn outline(&self) -> Outline {
+        Outline::Polygon(self.vertices.to_vec())
+    }
+}
+
+impl Shape for Ellipse {
+    fn area(&self) -> f64 {
+        PI * self.semi_x * self.semi_y
+    }
+
+    // Ramanujan's second approximation, exact for circles
+    fn perimeter(&self) -> f64 {
+        let (a, b) = (self.semi_x, self.semi_y);
+        let h = ((a - b) / (a + b)).powi(2);
+        PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+    }
+
+    fn bounding_box(&self) -> Rectangle {
+        let Point { x, y } = self.center;
+        Rectangle::new(
+            Point::new(x - self.semi_x, y - self.semi_y),
+            Point::new(x + self.semi_x, y + self.semi_y),
+        )
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        let dx = (point.x - self.center.x) / self.semi_x;
+        let dy = (point.y - self.center.y) / self.semi_y;
+        dx * dx + dy * dy <= 1.0
+    }
+
+    fn outline(&self) -> Outline {
+        Outline::Ellipse {
+            center: self.center,
+            semi_x: self.semi_x,
+            semi_y: self.semi_y,
+        }
+    }
+}
+
+impl Shape for Polygon {
+    // The shoelace formula
+    fn area(&self) -> f64 {
+        let twice: f64 = self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum();
+        twice.abs() / 2.0
+    }
+
+    fn perimeter(&self) -> f64 {
+        self.edges().map(|(a, b)| a.distance(b)).sum()
+    }
+
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::enclosing(&self.vertices)
+    }
+
+    // Casting a ray towards +x and counting the edges it crosses
+    fn contains(&self, point: Point) -> bool {
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if on_segment(a, b, point) {
+                return true;
+            }
+            if (a.y > point.y) != (b.y > point.y) {
+                let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if point.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    fn outline(&self) -> Outline {
+        Outline::Polygon(self.vertices.clone())
+    }
+}
+
+// Positive when `c` is to the left of the line from `a` to `b`
+fn cross(a: Point, b: Point, c: Point) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn on_segment(a: Point, b: Point, point: Point) -> bool {
+    cross(a, b, point) == 0.0
+        && point.x >= a.x.min(b.x)
+        && point.x <= a.x.max(b.x)
+        && point.y >= a.y.min(b.y)
+        && point.y <= a.y.max(b.y)
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl fmt::Display for Rectangle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Rectangle from {} to {}",
+            self.top_left, self.bottom_right
+        )
+    }
+}
+
+impl fmt::Display for Circle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Circle of radius {} at {}", self.radius, self.center)
+    }
+}
+
+impl fmt::Display for Triangle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c] = self.vertices;
+        write!(f, "Triangle {} {} {}", a, b, c)
+    }
+}
+
+impl fmt::Display for Ellipse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Ellipse of semi-axes {} and {} at {}",
+            self.semi_x, self.semi_y, self.center
+        )
+    }
+}
+
+impl fmt::Display for Polygon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Polygon")?;
+        for vertex in &self.vertices {
+            write!(f, " {}", vertex)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    // An L made of three unit squares
+    fn l_shape() -> Polygon {
+        Polygon::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 0.0),
+            Point::new(2.0, 1.0),
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 2.0),
+            Point::new(0.0, 2.0),
+        ])
+    }
+
+    #[test]
+    fn rectangle() {
+        // Same area as `Rectangle::area` in the `structures` example
+        let rectangle = Rectangle::new(Point::new(1.0, 10.4), Point::new(10.3, 0.4));
+        assert!(close(rectangle.area(), 9.3 * 10.0));
+        assert!(close(rectangle.perimeter(), 2.0 * (9.3 + 10.0)));
+        // Any two opposite corners give the same rectangle
+        assert_eq!(
+            Rectangle::new(Point::new(10.3, 0.4), Point::new(1.0, 10.4)),
+            rectangle
+        );
+        let square = Rectangle::square(Point::new(3.0, 3.0), 2.0);
+        assert_eq!(square.top_left, Point::new(3.0, 5.0));
+        assert!(square.contains(Point::new(5.0, 4.0)));
+        assert!(!square.contains(Point::new(5.1, 4.0)));
+    }
+
+    #[test]
+    fn circle_and_ellipse() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 6.0);
+        assert!(close(circle.area(), 36.0 * PI));
+        assert!(close(circle.perimeter(), 12.0 * PI));
+        assert!(circle.contains(Point::new(0.0, -6.0)));
+        assert!(!circle.contains(Point::new(4.5, 4.5)));
+        // The ellipse is a circle when both semi-axes are the same
+        let round = Ellipse::new(Point::new(0.0, 0.0), 6.0, 6.0);
+        assert!(close(round.perimeter(), circle.perimeter()));
+        assert_eq!(round.bounding_box(), circle.bounding_box());
+
+        let ellipse = Ellipse::new(Point::new(-2.0, 1.0), 3.0, 1.5);
+        assert!(close(ellipse.area(), 4.5 * PI));
+        assert!(ellipse.contains(Point::new(1.0, 1.0)));
+        assert!(!ellipse.contains(Point::new(-2.0, 2.6)));
+    }
+
+    #[test]
+    fn triangle() {
+        let triangle = Triangle::new(
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(0.0, 3.0),
+        );
+        assert_eq!(triangle.area(), 6.0);
+        assert_eq!(triangle.perimeter(), 12.0);
+        assert!(triangle.contains(Point::new(1.0, 1.0)));
+        assert!(triangle.contains(Point::new(2.0, 0.0)));
+        assert!(!triangle.contains(Point::new(3.0, 2.0)));
+        assert_eq!(
+            triangle.bounding_box(),
+            Rectangle::new(Point::new(0.0, 3.0), Point::new(4.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn polygon() {
+        let polygon = l_shape();
+        assert_eq!(polygon.area(), 3.0);
+        assert_eq!(polygon.perimeter(), 8.0);
+        assert!(polygon.contains(Point::new(0.5, 1.5)));
+        assert!(!polygon.contains(Point::new(1.5, 1.5)));
+        // On the edges
+        assert!(polygon.contains(Point::new(1.0, 1.5)));
+        assert!(polygon.contains(Point::new(2.0, 0.0)));
+        // Reversing the vertices changes nothing
+        let mut vertices = polygon.vertices.clone();
+        vertices.reverse();
+        assert_eq!(Polygon::new(vertices).area(), 3.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "a polygon needs at least 3 vertices")]
+    fn degenerate_polygon() {
+        Polygon::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)]);
+    }
+}
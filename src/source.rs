@@ -0,0 +1,236 @@
+// What the small languages of the examples share about their source text:
+// spans, finding the line of an offset, showing errors under the lines they
+// are about, and splitting the text into tokens.
+
+// Byte offsets of a part of the input, `end` excluded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+// The index of the line `offset` is on, and the offset where it starts
+pub fn line_of(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    (
+        before.matches('\n').count(),
+        before.rfind('\n').map_or(0, |index| index + 1),
+    )
+}
+
+// An error and the lines of `source` it is about, in the style of rustc:
+//
+//     error[E0505]: cannot move out of `s` because it is borrowed
+//      |
+//     2 | let r = &s;
+//      |         -- borrow of `s` occurs here
+//     3 | let t = s;
+//      |         ^ move out of `s` occurs here
+//
+// Each label is a span, the character to underline it with, and a text.
+pub fn render(
+    source: &str,
+    code: Option<&str>,
+    message: &str,
+    mut labels: Vec<(Span, char, String)>,
+) -> String {
+    labels.sort_by_key(|(span, _, _)| span.start);
+    let lines: Vec<usize> = labels
+        .iter()
+        .map(|(span, _, _)| line_of(source, span.start).0)
+        .collect();
+    let gutter = lines
+        .iter()
+        .max()
+        .map_or(1, |line| (line + 1).to_string().len());
+
+    let mut out = match code {
+        Some(code) => format!("error[{}]: {}\n", code, message),
+        None => format!("error: {}\n", message),
+    };
+    out += &format!("{} |\n", " ".repeat(gutter));
+    let mut previous = None;
+    for (&line, (span, marker, text)) in lines.iter().zip(&labels) {
+        let (_, line_start) = line_of(source, span.start);
+        let text_line = source[line_start..].lines().next().unwrap_or("");
+        if previous != Some(line) {
+            out += &format!("{:>width$} | {}\n", line + 1, text_line, width = gutter);
+        }
+        previous = Some(line);
+
+        let column = source[line_start..span.start.min(source.len())]
+            .chars()
+            .count();
+        let width = source
+            .get(span.start..span.end.min(line_start + text_line.len()))
+            .map_or(1, |text| text.chars().count().max(1));
+        out += &format!(
+            "{} | {}{} {}\n",
+            " ".repeat(gutter),
+            " ".repeat(column),
+            marker.to_string().repeat(width),
+            text
+        );
+    }
+    out.trim_end().to_owned()
+}
+
+// A token before each language gives it a meaning. Numbers are left as
+// text, their syntax differing from one language to the next.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lexeme {
+    Number,
+    Identifier(String),
+    // With its escapes replaced
+    Str(String),
+    Symbol(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexError {
+    UnexpectedCharacter(char),
+    UnterminatedString,
+}
+
+// Splits `source` into lexemes, skipping whitespace and `//` comments.
+// `symbols` are tried in order, so longer ones must come first, and `number`
+// gives the length of the number a text starting with a digit starts with.
+pub fn lex(
+    source: &str,
+    symbols: &[&'static str],
+    number: impl Fn(&str) -> usize,
+) -> Result<Vec<Spanned<Lexeme>>, Spanned<LexError>> {
+    let mut lexemes = Vec::new();
+    let mut position = 0;
+
+    while let Some(c) = source[position..].chars().next() {
+        let start = position;
+        let rest = &source[start..];
+        if c.is_whitespace() {
+            position += c.len_utf8();
+            continue;
+        }
+        if rest.starts_with("//") {
+            position += rest.find('\n').unwrap_or(rest.len());
+            continue;
+        }
+
+        let lexeme = if c.is_ascii_digit() {
+            position += number(rest);
+            Lexeme::Number
+        } else if c.is_alphabetic() || c == '_' {
+            let length = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            position += length;
+            Lexeme::Identifier(rest[..length].to_owned())
+        } else if c == '"' {
+            let (text, length) = string(rest).ok_or(Spanned {
+                node: LexError::UnterminatedString,
+                span: Span {
+                    start,
+                    end: start + 1,
+                },
+            })?;
+            position += length;
+            Lexeme::Str(text)
+        } else if let Some(symbol) = symbols.iter().find(|symbol| rest.starts_with(*symbol)) {
+            position += symbol.len();
+            Lexeme::Symbol(symbol)
+        } else {
+            return Err(Spanned {
+                node: LexError::UnexpectedCharacter(c),
+                span: Span {
+                    start,
+                    end: start + c.len_utf8(),
+                },
+            });
+        };
+        lexemes.push(Spanned {
+            node: lexeme,
+            span: Span {
+                start,
+                end: position,
+            },
+        });
+    }
+    Ok(lexemes)
+}
+
+// The text of the string literal `rest` starts with, and the length of the
+// literal, quotes included
+fn string(rest: &str) -> Option<(String, usize)> {
+    let mut text = String::new();
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((text, index + 1)),
+            '\\' => match chars.next()?.1 {
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                escaped => text.push(escaped),
+            },
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let source = "let a = 1;\nlet b = 2;\n";
+        assert_eq!(line_of(source, 4), (0, 0));
+        assert_eq!(line_of(source, 15), (1, 11));
+        assert_eq!(line_of(source, 100), (2, 22));
+    }
+
+    #[test]
+    fn labels() {
+        let source = "let r = &s;\nlet t = s;\n";
+        let labels = vec![
+            (Span { start: 20, end: 21 }, '^', "moved".to_owned()),
+            (Span { start: 8, end: 10 }, '-', "borrowed".to_owned()),
+        ];
+        let expected = "error[E0505]: cannot move\n  |\n1 | let r = &s;\n  |         -- borrowed\n2 | let t = s;\n  |         ^ moved";
+        assert_eq!(
+            render(source, Some("E0505"), "cannot move", labels),
+            expected
+        );
+    }
+
+    #[test]
+    fn lexemes() {
+        let digits = |rest: &str| {
+            rest.find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len())
+        };
+        let lexemes = lex("x += 12; // no\n\"a\\\"b\"", &["+=", "+", ";"], digits).unwrap();
+        let nodes: Vec<Lexeme> = lexemes.into_iter().map(|lexeme| lexeme.node).collect();
+        assert_eq!(
+            nodes,
+            vec![
+                Lexeme::Identifier("x".to_owned()),
+                Lexeme::Symbol("+="),
+                Lexeme::Number,
+                Lexeme::Symbol(";"),
+                Lexeme::Str("a\"b".to_owned()),
+            ]
+        );
+        let error = lex("\"open", &[], digits).unwrap_err();
+        assert_eq!(error.node, LexError::UnterminatedString);
+        assert_eq!(
+            lex("#", &[], digits).unwrap_err().node,
+            LexError::UnexpectedCharacter('#')
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::fmt;
+
+use crate::convert::Color;
+use crate::shapes::{Outline, Point, Rectangle, Shape};
+
+// Writes shapes out as an SVG image:
+//
+//     let mut scene = Scene::new();
+//     scene.add(Circle::new(Point::new(0.0, 0.0), 6.0), Style::fill(red));
+//     fs::write("scene.svg", scene.to_string())?;
+//
+// The shapes have y going up and SVG has it going down, so every y is
+// negated on the way out. The view box is fitted around all the shapes,
+// with a margin.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub stroke: Option<Color>,
+    pub fill: Option<Color>,
+    pub stroke_width: f64,
+}
+
+#[derive(Debug)]
+pub struct Scene {
+    items: Vec<(Box<dyn Shape>, Style)>,
+    pub margin: f64,
+}
+
+const BLACK: Color = Color {
+    red: 0,
+    green: 0,
+    blue: 0,
+};
+
+impl Style {
+    // An outline, not filled
+    pub fn stroke(color: Color) -> Style {
+        Style {
+            stroke: Some(color),
+            fill: None,
+            stroke_width: 1.0,
+        }
+    }
+
+    // Filled, with a black outline
+    pub fn fill(color: Color) -> Style {
+        Style {
+            fill: Some(color),
+            ..Style::default()
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style::stroke(BLACK)
+    }
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        Scene {
+            items: Vec::new(),
+            margin: 1.0,
+        }
+    }
+
+    pub fn add<S: Shape + 'static>(&mut self, shape: S, style: Style) -> &mut Scene {
+        self.items.push((Box::new(shape), style));
+        self
+    }
+
+    // The rectangle holding every shape, `None` for an empty scene
+    pub fn bounds(&self) -> Option<Rectangle> {
+        self.items
+            .iter()
+            .map(|(shape, _)| shape.bounding_box())
+            .reduce(|all, bounds| all.union(&bounds))
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Scene {
+        Scene::new()
+    }
+}
+
+// Rounded to hide floating point noise, and without negative zeros
+struct Number(f64);
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rounded = (self.0 * 1000.0).round() / 1000.0;
+        write!(f, "{}", rounded + 0.0)
+    }
+}
+
+// From the shapes' coordinates to SVG's
+fn x(point: Point) -> Number {
+    Number(point.x)
+}
+
+fn y(point: Point) -> Number {
+    Number(-point.y)
+}
+
+fn paint(color: Option<Color>) -> String {
+    match color {
+        Some(Color { red, green, blue }) => format!("#{:02x}{:02x}{:02x}", red, green, blue),
+        None => "none".to_owned(),
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\"",
+            paint(self.fill),
+            paint(self.stroke),
+            Number(self.stroke_width)
+        )
+    }
+}
+
+impl fmt::Display for Scene {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bounds = self
+            .bounds()
+            .unwrap_or_else(|| Rectangle::new(Point::new(0.0, 0.0), Point::new(0.0, 0.0)));
+        writeln!(
+            f,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+            Number(bounds.top_left.x - self.margin),
+            Number(-bounds.top_left.y - self.margin),
+            Number(bounds.width() + 2.0 * self.margin),
+            Number(bounds.height() + 2.0 * self.margin),
+        )?;
+        for (shape, style) in &self.items {
+            writeln!(f, "  <!-- {} -->", shape)?;
+            match shape.outline() {
+                Outline::Rectangle(rectangle) => writeln!(
+                    f,
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+                    x(rectangle.top_left),
+                    y(rectangle.top_left),
+                    Number(rectangle.width()),
+                    Number(rectangle.height()),
+                    style
+                )?,
+                Outline::Ellipse {
+                    center,
+                    semi_x,
+                    semi_y,
+                } if semi_x == semi_y => writeln!(
+                    f,
+                    "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+                    x(center),
+                    y(center),
+                    Number(semi_x),
+                    style
+                )?,
+                Outline::Ellipse {
+                    center,
+                    semi_x,
+                    semi_y,
+                } => writeln!(
+                    f,
+                    "  <ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" {}/>",
+                    x(center),
+                    y(center),
+                    Number(semi_x),
+                    Number(semi_y),
+                    style
+                )?,
+                Outline::Polygon(vertices) => {
+                    let points: Vec<String> = vertices
+                        .iter()
+                        .map(|&vertex| format!("{},{}", x(vertex), y(vertex)))
+                        .collect();
+                    writeln!(f, "  <polygon points=\"{}\" {}/>", points.join(" "), style)?
+                }
+            }
+        }
+        writeln!(f, "</svg>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::examples::custom_types;
+
+    // The scene of the `custom_types` example, checked against
+    // `shapes.svg`. Regenerate it with
+    //
+    //     BINARY=custom_types cargo run 2>/dev/null | sed -n '/^<svg/,/^<\/svg>/p' > src/shapes.svg
+    #[test]
+    fn golden() {
+        let scene = custom_types::scene();
+        assert_eq!(scene.to_string(), include_str!("shapes.svg"));
+    }
+}
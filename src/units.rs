@@ -0,0 +1,380 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// Quantities that carry their unit, and through it their dimension, in their
+// type. Unlike the `NanoSecond` and `Inch` aliases of the types example,
+// adding a `Quantity<Nanosecond>` to a `Quantity<Inch>` doesn't compile.
+//
+//     let length: Quantity<Inch> = Quantity::new(2.0);
+//     let time: Quantity<Nanosecond> = Quantity::new(5.0);
+//     let speed = length.convert::<Meter>() / time.convert::<Second>();
+//     println!("{:.3}", speed); // 10160000.000 m/s
+//
+// Quantities of the same dimension only mix after an explicit `convert`.
+// Multiplying or dividing quantities makes a quantity of the derived unit
+// (`Per<Meter, Second>` above), whose dimension is worked out from the
+// dimensions of the operands.
+
+// What is being measured: length, time, ...
+pub trait Dimension {
+    const NAME: &'static str;
+}
+
+// The dimension of the product of quantities of dimensions `Self` and `Rhs`
+pub trait MulDimension<Rhs: Dimension>: Dimension {
+    type Output: Dimension;
+}
+
+// The dimension of the quotient of quantities of dimensions `Self` and `Rhs`
+pub trait DivDimension<Rhs: Dimension>: Dimension {
+    type Output: Dimension;
+}
+
+macro_rules! dimensions {
+    ($($dimension:ident => $name:expr),* $(,)?) => {
+        $(
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct $dimension;
+
+            impl Dimension for $dimension {
+                const NAME: &'static str = $name;
+            }
+
+            impl MulDimension<Dimensionless> for $dimension {
+                type Output = $dimension;
+            }
+
+            impl DivDimension<$dimension> for $dimension {
+                type Output = Dimensionless;
+            }
+        )*
+    };
+}
+
+dimensions!(
+    Dimensionless => "dimensionless",
+    Length => "length",
+    Time => "time",
+    Mass => "mass",
+    Area => "area",
+    Speed => "speed",
+    Acceleration => "acceleration",
+    Force => "force",
+);
+
+// `A * B = C`, which also gives `C / A = B`
+macro_rules! products {
+    ($($a:ident * $b:ident = $c:ident),* $(,)?) => {
+        $(
+            impl MulDimension<$b> for $a {
+                type Output = $c;
+            }
+
+            impl DivDimension<$a> for $c {
+                type Output = $b;
+            }
+        )*
+    };
+}
+
+products!(
+    Length * Length = Area,
+    Speed * Time = Length,
+    Time * Speed = Length,
+    Acceleration * Time = Speed,
+    Time * Acceleration = Speed,
+    Mass * Acceleration = Force,
+    Acceleration * Mass = Force,
+);
+
+// A unit of measure, `FACTOR` is its size in the SI unit of its dimension
+pub trait Unit {
+    type Dimension: Dimension;
+    const FACTOR: f64;
+
+    fn symbol() -> String;
+}
+
+macro_rules! units {
+    ($($unit:ident: $dimension:ident = $factor:expr, $symbol:expr;)*) => {
+        $(
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct $unit;
+
+            impl Unit for $unit {
+                type Dimension = $dimension;
+                const FACTOR: f64 = $factor;
+
+                fn symbol() -> String {
+                    $symbol.to_owned()
+                }
+            }
+        )*
+    };
+}
+
+units! {
+    Meter: Length = 1.0, "m";
+    Kilometer: Length = 1000.0, "km";
+    Centimeter: Length = 0.01, "cm";
+    Inch: Length = 0.0254, "in";
+    Foot: Length = 0.3048, "ft";
+    Mile: Length = 1609.344, "mi";
+    Second: Time = 1.0, "s";
+    Nanosecond: Time = 1e-9, "ns";
+    Millisecond: Time = 1e-3, "ms";
+    Minute: Time = 60.0, "min";
+    Hour: Time = 3600.0, "h";
+    Kilogram: Mass = 1.0, "kg";
+    Gram: Mass = 1e-3, "g";
+    Pound: Mass = 0.453_592_37, "lb";
+    Newton: Force = 1.0, "N";
+}
+
+// The unit of a product of quantities, like `Times<Newton, Meter>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Times<A, B>(PhantomData<(A, B)>);
+
+// The unit of a quotient of quantities, like `Per<Meter, Second>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Per<A, B>(PhantomData<(A, B)>);
+
+impl<A: Unit, B: Unit> Unit for Times<A, B>
+where
+    A::Dimension: MulDimension<B::Dimension>,
+{
+    type Dimension = <A::Dimension as MulDimension<B::Dimension>>::Output;
+    const FACTOR: f64 = A::FACTOR * B::FACTOR;
+
+    fn symbol() -> String {
+        format!("{}·{}", A::symbol(), B::symbol())
+    }
+}
+
+impl<A: Unit, B: Unit> Unit for Per<A, B>
+where
+    A::Dimension: DivDimension<B::Dimension>,
+{
+    type Dimension = <A::Dimension as DivDimension<B::Dimension>>::Output;
+    const FACTOR: f64 = A::FACTOR / B::FACTOR;
+
+    fn symbol() -> String {
+        format!("{}/{}", A::symbol(), B::symbol())
+    }
+}
+
+pub type SquareMeter = Times<Meter, Meter>;
+pub type MeterPerSecond = Per<Meter, Second>;
+pub type KilometerPerHour = Per<Kilometer, Hour>;
+pub type MilePerHour = Per<Mile, Hour>;
+pub type MeterPerSecondSquared = Per<MeterPerSecond, Second>;
+
+// A value measured in `U`
+pub struct Quantity<U> {
+    value: f64,
+    unit: PhantomData<U>,
+}
+
+impl<U: Unit> Quantity<U> {
+    pub fn new(value: f64) -> Quantity<U> {
+        Quantity {
+            value,
+            unit: PhantomData,
+        }
+    }
+
+    // The number of `U` in the quantity
+    pub fn value(self) -> f64 {
+        self.value
+    }
+
+    pub fn dimension(self) -> &'static str {
+        U::Dimension::NAME
+    }
+
+    // The same quantity measured in another unit of the same dimension
+    pub fn convert<V: Unit<Dimension = U::Dimension>>(self) -> Quantity<V> {
+        Quantity::new(self.value * U::FACTOR / V::FACTOR)
+    }
+}
+
+// Implemented by hand, derives would require `U` itself to implement the
+// traits
+impl<U> Clone for Quantity<U> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U> Copy for Quantity<U> {}
+
+impl<U: Unit> fmt::Debug for Quantity<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Quantity({:?} {})", self.value, U::symbol())
+    }
+}
+
+impl<U> PartialEq for Quantity<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<U> PartialOrd for Quantity<U> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+// Honors the precision, `{:.2}` gives `2.54 cm`
+impl<U: Unit> fmt::Display for Quantity<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*} {}", precision, self.value, U::symbol()),
+            None => write!(f, "{} {}", self.value, U::symbol()),
+        }
+    }
+}
+
+impl<U: Unit> Add for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn add(self, rhs: Quantity<U>) -> Quantity<U> {
+        Quantity::new(self.value + rhs.value)
+    }
+}
+
+impl<U: Unit> Sub for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn sub(self, rhs: Quantity<U>) -> Quantity<U> {
+        Quantity::new(self.value - rhs.value)
+    }
+}
+
+impl<U: Unit> Neg for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn neg(self) -> Quantity<U> {
+        Quantity::new(-self.value)
+    }
+}
+
+// Scaling by a plain number keeps the unit
+impl<U: Unit> Mul<f64> for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn mul(self, rhs: f64) -> Quantity<U> {
+        Quantity::new(self.value * rhs)
+    }
+}
+
+impl<U: Unit> Div<f64> for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn div(self, rhs: f64) -> Quantity<U> {
+        Quantity::new(self.value / rhs)
+    }
+}
+
+impl<A: Unit, B: Unit> Mul<Quantity<B>> for Quantity<A>
+where
+    A::Dimension: MulDimension<B::Dimension>,
+{
+    type Output = Quantity<Times<A, B>>;
+
+    fn mul(self, rhs: Quantity<B>) -> Quantity<Times<A, B>> {
+        Quantity::new(self.value * rhs.value)
+    }
+}
+
+impl<A: Unit, B: Unit> Div<Quantity<B>> for Quantity<A>
+where
+    A::Dimension: DivDimension<B::Dimension>,
+{
+    type Output = Quantity<Per<A, B>>;
+
+    fn div(self, rhs: Quantity<B>) -> Quantity<Per<A, B>> {
+        Quantity::new(self.value / rhs.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn conversions() {
+        let inches: Quantity<Inch> = Quantity::new(2.0);
+        let total = inches.convert::<Centimeter>() + Quantity::new(10.0);
+        assert!(close(total.value(), 15.08));
+        let feet = Quantity::<Foot>::new(3.0).convert::<Inch>();
+        assert!(close(feet.value(), 36.0));
+        let nanoseconds = Quantity::<Nanosecond>::new(5.0).convert::<Millisecond>();
+        assert!(close(nanoseconds.value(), 5e-6));
+    }
+
+    #[test]
+    fn derived_units() {
+        let speed = Quantity::<Inch>::new(2.0) / Quantity::<Nanosecond>::new(5.0);
+        assert_eq!(speed.dimension(), "speed");
+        assert!(close(
+            speed.convert::<MeterPerSecond>().value(),
+            10_160_000.0
+        ));
+
+        let time = Quantity::<Hour>::new(2.0) + Quantity::<Minute>::new(30.0).convert::<Hour>();
+        let pace: Quantity<KilometerPerHour> = Quantity::<Kilometer>::new(40.0) / time;
+        assert!(close(pace.value(), 16.0));
+        assert!(close(
+            pace.convert::<MilePerHour>().value(),
+            9.941_939_075_797_9
+        ));
+
+        let field: Quantity<SquareMeter> = Quantity::<Meter>::new(105.0) * Quantity::new(68.0);
+        assert_eq!(field.dimension(), "area");
+        assert_eq!(field.value(), 7140.0);
+    }
+
+    #[test]
+    fn force() {
+        // F = m·a
+        let mass = Quantity::<Pound>::new(150.0).convert::<Kilogram>();
+        let gravity: Quantity<MeterPerSecondSquared> = Quantity::new(9.81);
+        let weight = (mass * gravity).convert::<Newton>();
+        assert_eq!(weight.dimension(), "force");
+        assert!(weight > Quantity::new(667.0) && weight < Quantity::new(668.0));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let mass = Quantity::<Kilogram>::new(2.0);
+        let grams = Quantity::<Gram>::new(500.0).convert::<Kilogram>();
+        // Adding a negative quantity is the same as subtracting it
+        assert_eq!(mass - grams, mass + -grams);
+        assert_eq!(mass * 3.0, Quantity::new(6.0));
+        assert_eq!(mass / 4.0, Quantity::new(0.5));
+        assert!(grams < mass);
+    }
+
+    #[test]
+    fn formatting() {
+        let length = Quantity::<Centimeter>::new(2.54);
+        assert_eq!(length.to_string(), "2.54 cm");
+        assert_eq!(format!("{:.1}", length), "2.5 cm");
+        assert_eq!(
+            format!("{:?}", Quantity::<Gram>::new(500.0)),
+            "Quantity(500.0 g)"
+        );
+        let speed = Quantity::<Kilometer>::new(1.0) / Quantity::<Hour>::new(1.0);
+        assert_eq!(speed.to_string(), "1 km/h");
+        let area = Quantity::<Meter>::new(1.0) * Quantity::<Meter>::new(1.0);
+        assert_eq!(area.to_string(), "1 m·m");
+    }
+}
@@ -0,0 +1,568 @@
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+// Numbers written out in words, in English and French, the languages of the
+// `Number` and `Numero` of the conversion example:
+//
+//     assert_eq!(Words::english(30).to_string(), "thirty");
+//     assert_eq!(Words::french(71).to_string(), "soixante et onze");
+//     assert_eq!(Number::try_from("ninety-seven"), Ok(Number { value: 97 }));
+//     let numero: Numero = Number { value: 80 }.into(); // quatre-vingts
+//
+// English follows the American usage, without "and": "one hundred one".
+// French follows the traditional spelling, where hyphens only join tens and
+// units below one hundred ("vingt-deux", but "deux cents", "vingt et un").
+//
+// Parsing is strict: words are only accepted in the spelling they are
+// printed with, except for case and hyphens versus spaces, so that the 1990
+// reformed French spelling ("vingt-et-un", "deux-cents") parses too.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    French,
+}
+
+// A number, written in `language`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Words {
+    pub value: i64,
+    pub language: Language,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Number {
+    pub value: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Numero {
+    pub valeur: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordsError {
+    Empty,
+    UnknownWord(String),
+    // The words make a number, but aren't spelled the way it is written
+    NotCanonical { value: i64, expected: String },
+    Overflow,
+}
+
+impl Words {
+    pub fn english(value: i64) -> Words {
+        Words {
+            value,
+            language: Language::English,
+        }
+    }
+
+    pub fn french(value: i64) -> Words {
+        Words {
+            value,
+            language: Language::French,
+        }
+    }
+
+    pub fn parse(text: &str, language: Language) -> Result<Words, WordsError> {
+        let words: Vec<String> = text
+            .split(|c: char| c.is_whitespace() || c == '-')
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        if words.is_empty() {
+            return Err(WordsError::Empty);
+        }
+
+        let value = evaluate(&words, language)?;
+        let expected = Words { value, language }.to_string();
+        let normalized = expected.replace('-', " ");
+        if normalized != words.join(" ") {
+            return Err(WordsError::NotCanonical { value, expected });
+        }
+        Ok(Words { value, language })
+    }
+}
+
+// What a word stands for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Word {
+    Minus,
+    // "et" in "vingt et un"
+    And,
+    // Zero to nineteen, and the tens
+    Value(u64),
+    Hundred,
+    Thousand,
+    // Million and above, which are nouns in French
+    Scale(u64),
+}
+
+const ENGLISH_UNITS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const ENGLISH_TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const ENGLISH_SCALES: [(&str, u64); 5] = [
+    ("quintillion", 1_000_000_000_000_000_000),
+    ("quadrillion", 1_000_000_000_000_000),
+    ("trillion", 1_000_000_000_000),
+    ("billion", 1_000_000_000),
+    ("million", 1_000_000),
+];
+
+const FRENCH_UNITS: [&str; 17] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf", "dix", "onze",
+    "douze", "treize", "quatorze", "quinze", "seize",
+];
+
+// Seventy and ninety are sixty-ten and four-twenty-ten
+const FRENCH_TENS: [&str; 7] = [
+    "",
+    "",
+    "vingt",
+    "trente",
+    "quarante",
+    "cinquante",
+    "soixante",
+];
+
+const FRENCH_SCALES: [(&str, u64); 5] = [
+    ("trillion", 1_000_000_000_000_000_000),
+    ("billiard", 1_000_000_000_000_000),
+    ("billion", 1_000_000_000_000),
+    ("milliard", 1_000_000_000),
+    ("million", 1_000_000),
+];
+
+fn english(mut n: u64) -> String {
+    if n == 0 {
+        return ENGLISH_UNITS[0].to_owned();
+    }
+    let mut parts = Vec::new();
+    for &(name, scale) in ENGLISH_SCALES.iter().chain(&[("thousand", 1000)]) {
+        if n >= scale {
+            parts.push(format!("{} {}", english_below_1000(n / scale), name));
+            n %= scale;
+        }
+    }
+    if n > 0 {
+        parts.push(english_below_1000(n));
+    }
+    parts.join(" ")
+}
+
+fn english_below_1000(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let mut parts = Vec::new();
+    if hundreds > 0 {
+        parts.push(format!("{} hundred", ENGLISH_UNITS[hundreds as usize]));
+    }
+    if rest >= 20 {
+        let (tens, units) = (rest / 10, rest % 10);
+        if units == 0 {
+            parts.push(ENGLISH_TENS[tens as usize].to_owned());
+        } else {
+            parts.push(format!(
+                "{}-{}",
+                ENGLISH_TENS[tens as usize], ENGLISH_UNITS[units as usize]
+            ));
+        }
+    } else if rest > 0 {
+        parts.push(ENGLISH_UNITS[rest as usize].to_owned());
+    }
+    parts.join(" ")
+}
+
+fn french(mut n: u64) -> String {
+    if n == 0 {
+        return FRENCH_UNITS[0].to_owned();
+    }
+    let mut parts = Vec::new();
+    for &(name, scale) in &FRENCH_SCALES {
+        if n >= scale {
+            let count = n / scale;
+            // Millions are nouns: "un million", "deux millions", and they
+            // let "cents" and "vingts" keep their plural
+            let plural = if count > 1 { "s" } else { "" };
+            parts.push(format!(
+                "{} {}{}",
+                french_below_1000(count, true),
+                name,
+                plural
+            ));
+            n %= scale;
+        }
+    }
+    if n >= 1000 {
+        // "mille" is invariable, comes without "un", and being an
+        // adjective it takes the plural away from "cents" and "vingts"
+        let count = n / 1000;
+        if count > 1 {
+            parts.push(format!("{} mille", french_below_1000(count, false)));
+        } else {
+            parts.push("mille".to_owned());
+        }
+        n %= 1000;
+    }
+    if n > 0 {
+        parts.push(french_below_1000(n, true));
+    }
+    parts.join(" ")
+}
+
+// `plural` tells whether "cents" and "vingts" may take an "s" when they end
+// the number
+fn french_below_1000(n: u64, plural: bool) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let mut parts = Vec::new();
+    match hundreds {
+        0 => {}
+        1 => parts.push("cent".to_owned()),
+        _ => {
+            let s = if rest == 0 && plural { "s" } else { "" };
+            parts.push(format!("{} cent{}", FRENCH_UNITS[hundreds as usize], s));
+        }
+    }
+    if rest > 0 {
+        parts.push(french_below_100(rest, plural));
+    }
+    parts.join(" ")
+}
+
+fn french_below_100(n: u64, plural: bool) -> String {
+    if n <= 16 {
+        return FRENCH_UNITS[n as usize].to_owned();
+    }
+    if n < 20 {
+        return format!("dix-{}", FRENCH_UNITS[n as usize - 10]);
+    }
+
+    // Seventies and nineties count from sixty and eighty
+    let (tens, units) = match n {
+        70..=79 => (60, n - 60),
+        80..=99 => (80, n - 80),
+        _ => (n / 10 * 10, n % 10),
+    };
+    let tens_word = if tens == 80 {
+        let s = if units == 0 && plural { "s" } else { "" };
+        format!("quatre-vingt{}", s)
+    } else {
+        FRENCH_TENS[tens as usize / 10].to_owned()
+    };
+    match units {
+        0 => tens_word,
+        // "vingt et un" to "soixante et onze", but "quatre-vingt-un"
+        1 | 11 if tens != 80 => format!("{} et {}", tens_word, FRENCH_UNITS[units as usize]),
+        _ => format!("{}-{}", tens_word, french_below_100(units, plural)),
+    }
+}
+
+fn word(text: &str, language: Language) -> Option<Word> {
+    let position = |list: &[&str]| list.iter().position(|&w| w == text).map(|i| i as u64);
+    match language {
+        Language::English => match text {
+            "minus" => Some(Word::Minus),
+            "hundred" => Some(Word::Hundred),
+            "thousand" => Some(Word::Thousand),
+            _ => position(&ENGLISH_UNITS)
+                .or_else(|| position(&ENGLISH_TENS[2..]).map(|i| (i + 2) * 10))
+                .map(Word::Value)
+                .or_else(|| scale(&ENGLISH_SCALES, text)),
+        },
+        Language::French => match text {
+            "moins" => Some(Word::Minus),
+            "et" => Some(Word::And),
+            "cent" | "cents" => Some(Word::Hundred),
+            "mille" => Some(Word::Thousand),
+            "vingts" => Some(Word::Value(20)),
+            "zero" => Some(Word::Value(0)),
+            _ => position(&FRENCH_UNITS)
+                .or_else(|| position(&FRENCH_TENS[2..]).map(|i| (i + 2) * 10))
+                .map(Word::Value)
+                .or_else(|| scale(&FRENCH_SCALES, text.trim_end_matches('s'))),
+        },
+    }
+}
+
+fn scale(scales: &[(&str, u64)], text: &str) -> Option<Word> {
+    scales
+        .iter()
+        .find(|&&(name, _)| name == text)
+        .map(|&(_, value)| Word::Scale(value))
+}
+
+// The value of the words, with the spelling checked afterwards by writing
+// the value back out
+fn evaluate(words: &[String], language: Language) -> Result<i64, WordsError> {
+    let overflow = || WordsError::Overflow;
+    let (mut total, mut thousands, mut current): (u64, u64, u64) = (0, 0, 0);
+    let mut negative = false;
+    let mut previous = None;
+
+    for (index, text) in words.iter().enumerate() {
+        let word = word(text, language).ok_or_else(|| WordsError::UnknownWord(text.clone()))?;
+        match word {
+            Word::Minus if index == 0 => negative = true,
+            Word::Minus => return Err(WordsError::UnknownWord(text.clone())),
+            Word::And => {}
+            // "quatre-vingts" is four twenties
+            Word::Value(20) if language == Language::French && previous == Some(Word::Value(4)) => {
+                current = current.checked_add(76).ok_or_else(overflow)?
+            }
+            Word::Value(value) => current = current.checked_add(value).ok_or_else(overflow)?,
+            Word::Hundred => current = current.max(1).checked_mul(100).ok_or_else(overflow)?,
+            Word::Thousand => {
+                thousands = current.max(1).checked_mul(1000).ok_or_else(overflow)?;
+                current = 0;
+            }
+            Word::Scale(scale) => {
+                let group = current.max(1).checked_mul(scale).ok_or_else(overflow)?;
+                total = total.checked_add(group).ok_or_else(overflow)?;
+                current = 0;
+            }
+        }
+        previous = Some(word);
+    }
+
+    let magnitude = total
+        .checked_add(thousands.checked_add(current).ok_or_else(overflow)?)
+        .ok_or_else(overflow)?;
+    if negative {
+        0i64.checked_sub_unsigned(magnitude).ok_or_else(overflow)
+    } else {
+        i64::try_from(magnitude).map_err(|_| overflow())
+    }
+}
+
+impl fmt::Display for Words {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (minus, magnitude) = match self.language {
+            Language::English => ("minus ", english(self.value.unsigned_abs())),
+            Language::French => ("moins ", french(self.value.unsigned_abs())),
+        };
+        let sign = if self.value < 0 { minus } else { "" };
+        write!(f, "{}{}", sign, magnitude)
+    }
+}
+
+// Words in either language, English is tried first
+impl FromStr for Words {
+    type Err = WordsError;
+
+    fn from_str(s: &str) -> Result<Words, WordsError> {
+        Words::parse(s, Language::English).or_else(|english| {
+            Words::parse(s, Language::French).map_err(|french| match english {
+                // Report the error of the language the words seem to be in
+                WordsError::UnknownWord(_) => french,
+                _ => english,
+            })
+        })
+    }
+}
+
+impl From<Number> for Numero {
+    fn from(number: Number) -> Numero {
+        Numero {
+            valeur: number.value,
+        }
+    }
+}
+
+impl From<Numero> for Number {
+    fn from(numero: Numero) -> Number {
+        Number {
+            value: numero.valeur,
+        }
+    }
+}
+
+impl From<Number> for Words {
+    fn from(number: Number) -> Words {
+        Words::english(number.value)
+    }
+}
+
+impl From<Numero> for Words {
+    fn from(numero: Numero) -> Words {
+        Words::french(numero.valeur)
+    }
+}
+
+impl From<Words> for Number {
+    fn from(words: Words) -> Number {
+        Number { value: words.value }
+    }
+}
+
+impl From<Words> for Numero {
+    fn from(words: Words) -> Numero {
+        Numero {
+            valeur: words.value,
+        }
+    }
+}
+
+// English words
+impl TryFrom<&str> for Number {
+    type Error = WordsError;
+
+    fn try_from(text: &str) -> Result<Number, WordsError> {
+        Words::parse(text, Language::English).map(Number::from)
+    }
+}
+
+// Des mots en français
+impl TryFrom<&str> for Numero {
+    type Error = WordsError;
+
+    fn try_from(text: &str) -> Result<Numero, WordsError> {
+        Words::parse(text, Language::French).map(Numero::from)
+    }
+}
+
+// Digits, or English words
+impl FromStr for Number {
+    type Err = WordsError;
+
+    fn from_str(s: &str) -> Result<Number, WordsError> {
+        match s.trim().parse() {
+            Ok(value) => Ok(Number { value }),
+            Err(_) => Number::try_from(s),
+        }
+    }
+}
+
+// Des chiffres, ou des mots en français
+impl FromStr for Numero {
+    type Err = WordsError;
+
+    fn from_str(s: &str) -> Result<Numero, WordsError> {
+        match s.trim().parse() {
+            Ok(valeur) => Ok(Numero { valeur }),
+            Err(_) => Numero::try_from(s),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.value, Words::from(*self))
+    }
+}
+
+impl fmt::Display for Numero {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.valeur, Words::from(*self))
+    }
+}
+
+impl fmt::Display for WordsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordsError::Empty => write!(f, "no words to read a number from"),
+            WordsError::UnknownWord(word) => write!(f, "`{}` is not part of a number", word),
+            WordsError::NotCanonical { value, expected } => {
+                write!(f, "{} is written \"{}\"", value, expected)
+            }
+            WordsError::Overflow => write!(f, "the number doesn't fit in an i64"),
+        }
+    }
+}
+
+impl error::Error for WordsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spelling() {
+        assert_eq!(Words::english(0).to_string(), "zero");
+        assert_eq!(Words::english(101).to_string(), "one hundred one");
+        assert_eq!(Words::english(-17).to_string(), "minus seventeen");
+        assert_eq!(Words::french(71).to_string(), "soixante et onze");
+        assert_eq!(Words::french(80).to_string(), "quatre-vingts");
+        assert_eq!(Words::french(81).to_string(), "quatre-vingt-un");
+        assert_eq!(Words::french(200).to_string(), "deux cents");
+        assert_eq!(Words::french(80_000).to_string(), "quatre-vingt mille");
+        assert_eq!(Words::french(2_000_000).to_string(), "deux millions");
+    }
+
+    #[test]
+    fn round_trip() {
+        for &value in &[0, 1, 17, 71, 80, 99, 101, 221, 999_999, i64::MAX, i64::MIN] {
+            for words in [Words::english(value), Words::french(value)] {
+                assert_eq!(Words::parse(&words.to_string(), words.language), Ok(words));
+            }
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Numero::try_from("quatre-vingt-dix-sept"),
+            Ok(Numero { valeur: 97 })
+        );
+        assert_eq!(Number::try_from("Ninety Seven"), Ok(Number { value: 97 }));
+        assert_eq!("42".parse(), Ok(Number { value: 42 }));
+        assert_eq!("vingt-et-un".parse::<Words>(), Ok(Words::french(21)));
+        assert_eq!(
+            "quatre-vingt".parse::<Words>(),
+            Err(WordsError::NotCanonical {
+                value: 80,
+                expected: "quatre-vingts".to_owned(),
+            })
+        );
+        assert_eq!(
+            "one two".parse::<Words>(),
+            Err(WordsError::NotCanonical {
+                value: 3,
+                expected: "three".to_owned(),
+            })
+        );
+        assert_eq!(
+            Number::try_from("trente"),
+            Err(WordsError::UnknownWord("trente".to_owned()))
+        );
+        assert_eq!(Words::parse("  ", Language::French), Err(WordsError::Empty));
+    }
+
+    #[test]
+    fn overflow() {
+        let hundreds = format!("one{}", " hundred".repeat(11));
+        assert_eq!(
+            Words::parse(&hundreds, Language::English),
+            Err(WordsError::Overflow)
+        );
+        let thousands = format!("one{} thousand", " hundred".repeat(9));
+        assert_eq!(
+            Words::parse(&thousands, Language::English),
+            Err(WordsError::Overflow)
+        );
+    }
+}